@@ -0,0 +1,340 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+/// Rate provider combining rates of other providers and constants.
+///
+/// The rate is computed by applying `terms` in order to 1.0, each multiplying or dividing
+/// the rate by a factor, e.g. the rate of a wrapped liquid staking token of a liquid staking token
+/// is the product of the rates of both tokens, and the rate of the base token in a token is
+/// the inverse of the token rate.
+///
+/// Rates and factors have `RATE_DECIMALS` decimal places (rates of `RateProviderV2` factors
+/// are scaled to it, rounding down). Each intermediate rate is rounded to `RATE_DECIMALS`
+/// according to the `rounding` set by the owner.
+///
+/// `RateProviderV2::get_rate` reports the update time of the oldest `RateProviderV2` factor.
+/// `RateProvider` factors do not report when their rate was updated, so if there are no
+/// `RateProviderV2` factors, the rate is reported as updated at the current block.
+///
+/// Tags of `MathError`s returned when computing the rate:
+/// - `AddOverflow(211)`: rounded product overflows
+/// - `CastOverflow(211)`: rate exceeds u128
+/// - `DivByZero(211)`: divisor rate is zero
+#[ink::contract]
+pub mod composite_rate {
+    use amm_helpers::{
+        constants::{
+            composite_rate::MAX_TERMS,
+            stable_pool::{RATE_DECIMALS, RATE_PRECISION},
+        },
+        ensure,
+        math::{casted_mul, scale_rate},
+    };
+    use ink::{
+        codegen::TraitCallBuilder,
+        contract_ref,
+        prelude::{format, vec::Vec},
+    };
+    use traits::{
+        MathError, Ownable2Step, Ownable2StepData, Ownable2StepError, Ownable2StepResult, RateInfo,
+        RateProvider, RateProviderError, RateProviderV2,
+    };
+
+    /// Factor of a term of the rate.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum Factor {
+        /// Rate of a `RateProvider`.
+        Provider(AccountId),
+        /// Constant with `RATE_PRECISION`.
+        Constant(u128),
+        /// Rate of a `RateProviderV2`, scaled to `RATE_DECIMALS`.
+        ProviderV2(AccountId),
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum Term {
+        Mul(Factor),
+        Div(Factor),
+    }
+
+    /// Rounding of intermediate rates to `RATE_DECIMALS`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum Rounding {
+        Down,
+        Up,
+        Nearest,
+    }
+
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum CompositeRateError {
+        Ownable2StepError(Ownable2StepError),
+        MathError(MathError),
+        TooManyTerms,
+        ZeroConstant,
+        /// The call to the rate provider failed.
+        ProviderUnavailable(AccountId),
+        RateProviderError(RateProviderError),
+    }
+
+    impl From<Ownable2StepError> for CompositeRateError {
+        fn from(error: Ownable2StepError) -> Self {
+            CompositeRateError::Ownable2StepError(error)
+        }
+    }
+
+    impl From<MathError> for CompositeRateError {
+        fn from(error: MathError) -> Self {
+            CompositeRateError::MathError(error)
+        }
+    }
+
+    #[ink(event)]
+    pub struct TermsChanged {
+        pub terms: Vec<Term>,
+        pub rounding: Rounding,
+    }
+
+    #[ink(event)]
+    pub struct TransferOwnershipInitiated {
+        #[ink(topic)]
+        pub new_owner: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct TransferOwnershipAccepted {
+        #[ink(topic)]
+        pub new_owner: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct OwnershipRenounced {}
+
+    #[ink(storage)]
+    pub struct CompositeRateContract {
+        ownable: Ownable2StepData,
+        terms: Vec<Term>,
+        rounding: Rounding,
+        /// Token in which the rate is denominated (`None` for the native token).
+        base_token: Option<AccountId>,
+    }
+
+    impl CompositeRateContract {
+        #[ink(constructor)]
+        pub fn new(
+            owner: AccountId,
+            terms: Vec<Term>,
+            rounding: Rounding,
+            base_token: Option<AccountId>,
+        ) -> Result<Self, CompositeRateError> {
+            validate_terms(&terms)?;
+            Ok(Self {
+                ownable: Ownable2StepData::new(owner),
+                terms,
+                rounding,
+                base_token,
+            })
+        }
+
+        #[ink(message)]
+        pub fn terms(&self) -> Vec<Term> {
+            self.terms.clone()
+        }
+
+        #[ink(message)]
+        pub fn rounding(&self) -> Rounding {
+            self.rounding
+        }
+
+        /// Replaces the terms and rounding of the rate.
+        ///
+        /// Can be called only by the owner.
+        #[ink(message)]
+        pub fn set_terms(
+            &mut self,
+            terms: Vec<Term>,
+            rounding: Rounding,
+        ) -> Result<(), CompositeRateError> {
+            self.ensure_owner()?;
+            validate_terms(&terms)?;
+            self.terms = terms.clone();
+            self.rounding = rounding;
+            self.env().emit_event(TermsChanged { terms, rounding });
+            Ok(())
+        }
+
+        /// Returns the rate with the update time of its oldest `RateProviderV2` factor (if any).
+        fn compute_rate(&self) -> Result<(u128, Option<u64>), CompositeRateError> {
+            let mut last_updated: Option<u64> = None;
+            let rate = self.terms.iter().try_fold(RATE_PRECISION, |rate, term| {
+                let (factor, inverse) = match term {
+                    Term::Mul(factor) => (factor, false),
+                    Term::Div(factor) => (factor, true),
+                };
+                let (value, updated_at) = factor.value()?;
+                if let Some(updated_at) = updated_at {
+                    last_updated = Some(last_updated.map_or(updated_at, |t| t.min(updated_at)));
+                }
+                let (numerator, denominator) = if inverse {
+                    (RATE_PRECISION, value)
+                } else {
+                    (value, RATE_PRECISION)
+                };
+                mul_div(rate, numerator, denominator, self.rounding)
+                    .map_err(CompositeRateError::from)
+            })?;
+            Ok((rate, last_updated))
+        }
+    }
+
+    impl Factor {
+        /// Returns the value with `RATE_PRECISION` and, for a `RateProviderV2`,
+        /// the update time of the rate.
+        fn value(&self) -> Result<(u128, Option<u64>), CompositeRateError> {
+            match *self {
+                Factor::Provider(account) => {
+                    let mut rate_provider: contract_ref!(RateProvider) = account.into();
+                    match rate_provider.call_mut().get_rate().try_invoke() {
+                        Ok(Ok(rate)) => Ok((rate, None)),
+                        _ => Err(CompositeRateError::ProviderUnavailable(account)),
+                    }
+                }
+                Factor::Constant(value) => Ok((value, None)),
+                Factor::ProviderV2(account) => {
+                    let mut rate_provider: contract_ref!(RateProviderV2) = account.into();
+                    match rate_provider.call_mut().get_rate().try_invoke() {
+                        Ok(Ok(Ok(RateInfo {
+                            rate,
+                            decimals,
+                            last_updated,
+                        }))) => Ok((scale_rate(rate, decimals)?, Some(last_updated))),
+                        Ok(Ok(Err(error))) => Err(CompositeRateError::RateProviderError(error)),
+                        // the call failed or its result could not be decoded
+                        _ => Err(CompositeRateError::ProviderUnavailable(account)),
+                    }
+                }
+            }
+        }
+    }
+
+    fn validate_terms(terms: &[Term]) -> Result<(), CompositeRateError> {
+        ensure!(terms.len() <= MAX_TERMS, CompositeRateError::TooManyTerms);
+        ensure!(
+            terms.iter().all(|term| !matches!(
+                term,
+                Term::Mul(Factor::Constant(0)) | Term::Div(Factor::Constant(0))
+            )),
+            CompositeRateError::ZeroConstant
+        );
+        Ok(())
+    }
+
+    /// Computes `a * b / c`, rounded according to `rounding`.
+    fn mul_div(a: u128, b: u128, c: u128, rounding: Rounding) -> Result<u128, MathError> {
+        let offset = match rounding {
+            Rounding::Down => 0,
+            Rounding::Up => c.saturating_sub(1),
+            Rounding::Nearest => c / 2,
+        };
+        casted_mul(a, b)
+            .checked_add(offset.into())
+            .ok_or(MathError::AddOverflow(211))?
+            .checked_div(c.into())
+            .ok_or(MathError::DivByZero(211))?
+            .try_into()
+            .map_err(|_| MathError::CastOverflow(211))
+    }
+
+    impl RateProvider for CompositeRateContract {
+        /// Panics if the rate can not be computed, e.g. if it is divided by a zero rate
+        /// or a provider fails. Use `RateProviderV2::get_rate` to handle the error.
+        #[ink(message)]
+        fn get_rate(&mut self) -> u128 {
+            match self.compute_rate() {
+                Ok((rate, _)) => rate,
+                Err(error) => panic!("Composite rate error: {error:?}"),
+            }
+        }
+    }
+
+    impl RateProviderV2 for CompositeRateContract {
+        /// The rate is updated at the update time of its oldest `RateProviderV2` factor,
+        /// or at the current block if there is none (see the contract docs).
+        ///
+        /// Errors of `RateProviderV2` factors are returned as is.
+        #[ink(message)]
+        fn get_rate(&mut self) -> Result<RateInfo, RateProviderError> {
+            let (rate, last_updated) = self.compute_rate().map_err(|error| match error {
+                CompositeRateError::RateProviderError(error) => error,
+                CompositeRateError::MathError(error) => {
+                    RateProviderError::Custom(format!("{error}"))
+                }
+                error => RateProviderError::Custom(format!("{error:?}")),
+            })?;
+            Ok(RateInfo {
+                rate,
+                decimals: RATE_DECIMALS,
+                last_updated: last_updated.unwrap_or_else(|| self.env().block_timestamp()),
+            })
+        }
+
+        #[ink(message)]
+        fn rate_base_token(&self) -> Option<AccountId> {
+            self.base_token
+        }
+    }
+
+    impl Ownable2Step for CompositeRateContract {
+        #[ink(message)]
+        fn get_owner(&self) -> Ownable2StepResult<AccountId> {
+            self.ownable.get_owner()
+        }
+
+        #[ink(message)]
+        fn get_pending_owner(&self) -> Ownable2StepResult<AccountId> {
+            self.ownable.get_pending_owner()
+        }
+
+        #[ink(message)]
+        fn transfer_ownership(&mut self, new_owner: AccountId) -> Ownable2StepResult<()> {
+            self.ownable
+                .transfer_ownership(self.env().caller(), new_owner)?;
+            self.env()
+                .emit_event(TransferOwnershipInitiated { new_owner });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn accept_ownership(&mut self) -> Ownable2StepResult<()> {
+            let new_owner = self.env().caller();
+            self.ownable.accept_ownership(new_owner)?;
+            self.env()
+                .emit_event(TransferOwnershipAccepted { new_owner });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn renounce_ownership(&mut self) -> Ownable2StepResult<()> {
+            self.ownable
+                .renounce_ownership(self.env().caller(), self.env().account_id())?;
+            self.env().emit_event(OwnershipRenounced {});
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn ensure_owner(&self) -> Ownable2StepResult<()> {
+            self.ownable.ensure_owner(self.env().caller())
+        }
+    }
+}
@@ -0,0 +1,176 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+/// PSP22 token which calls back into a stable pool whenever the pool transfers it.
+///
+/// Used for testing the pool's protection against reentrancy.
+#[ink::contract]
+mod mock_reentrant_psp22 {
+    use ink::{
+        codegen::TraitCallBuilder,
+        contract_ref,
+        prelude::{string::String, vec::Vec},
+    };
+    use psp22::{PSP22Data, PSP22Error, PSP22Metadata, PSP22};
+    use traits::{StablePoolActions, StablePoolView};
+
+    #[ink(storage)]
+    pub struct MockReentrantPSP22Contract {
+        data: PSP22Data,
+        decimals: u8,
+        /// Pool to reenter (if any).
+        pool: Option<AccountId>,
+        /// Number of attempted reentrant calls.
+        reentry_attempts: u32,
+        /// Number of reentrant calls which failed.
+        reentry_blocked: u32,
+    }
+
+    impl MockReentrantPSP22Contract {
+        #[ink(constructor)]
+        pub fn new(supply: u128, decimals: u8) -> Self {
+            let (data, _) = PSP22Data::new(supply, Self::env().caller());
+            Self {
+                data,
+                decimals,
+                pool: None,
+                reentry_attempts: 0,
+                reentry_blocked: 0,
+            }
+        }
+
+        #[ink(message)]
+        pub fn set_pool(&mut self, pool: Option<AccountId>) {
+            self.pool = pool;
+        }
+
+        /// Returns a tuple of (attempted, blocked) reentrant calls.
+        #[ink(message)]
+        pub fn reentry_stats(&self) -> (u32, u32) {
+            (self.reentry_attempts, self.reentry_blocked)
+        }
+
+        /// Calls back into the pool if the pool is the caller.
+        /// Tries a mutating message, a quote message and the LP token `transfer` and `approve`.
+        fn reenter(&mut self) {
+            let Some(pool_address) = self.pool else {
+                return;
+            };
+            if self.env().caller() != pool_address {
+                return;
+            }
+            let this = self.env().account_id();
+            let mut pool: contract_ref!(StablePoolActions) = pool_address.into();
+            let mutating = pool.call_mut().add_liquidity_received(0, this).try_invoke();
+            self.record(!matches!(mutating, Ok(Ok(Ok(_)))));
+            let mut pool_view: contract_ref!(StablePoolView) = pool_address.into();
+            let quote = pool_view
+                .call_mut()
+                .get_amounts_for_liquidity_mint(1)
+                .try_invoke();
+            self.record(!matches!(quote, Ok(Ok(Ok(_)))));
+            let mut lp_token: contract_ref!(PSP22) = pool_address.into();
+            let transfer = lp_token
+                .call_mut()
+                .transfer(this, 0, Vec::new())
+                .try_invoke();
+            self.record(!matches!(transfer, Ok(Ok(Ok(_)))));
+            let approve = lp_token.call_mut().approve(this, u128::MAX).try_invoke();
+            self.record(!matches!(approve, Ok(Ok(Ok(_)))));
+        }
+
+        fn record(&mut self, blocked: bool) {
+            self.reentry_attempts += 1;
+            if blocked {
+                self.reentry_blocked += 1;
+            }
+        }
+    }
+
+    impl PSP22 for MockReentrantPSP22Contract {
+        #[ink(message)]
+        fn total_supply(&self) -> u128 {
+            self.data.total_supply()
+        }
+
+        #[ink(message)]
+        fn balance_of(&self, owner: AccountId) -> u128 {
+            self.data.balance_of(owner)
+        }
+
+        #[ink(message)]
+        fn allowance(&self, owner: AccountId, spender: AccountId) -> u128 {
+            self.data.allowance(owner, spender)
+        }
+
+        #[ink(message)]
+        fn transfer(
+            &mut self,
+            to: AccountId,
+            value: u128,
+            _data: Vec<u8>,
+        ) -> Result<(), PSP22Error> {
+            self.data.transfer(self.env().caller(), to, value)?;
+            self.reenter();
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn transfer_from(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: u128,
+            _data: Vec<u8>,
+        ) -> Result<(), PSP22Error> {
+            self.data
+                .transfer_from(self.env().caller(), from, to, value)?;
+            self.reenter();
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn approve(&mut self, spender: AccountId, value: u128) -> Result<(), PSP22Error> {
+            self.data.approve(self.env().caller(), spender, value)?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn increase_allowance(
+            &mut self,
+            spender: AccountId,
+            delta_value: u128,
+        ) -> Result<(), PSP22Error> {
+            self.data
+                .increase_allowance(self.env().caller(), spender, delta_value)?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn decrease_allowance(
+            &mut self,
+            spender: AccountId,
+            delta_value: u128,
+        ) -> Result<(), PSP22Error> {
+            self.data
+                .decrease_allowance(self.env().caller(), spender, delta_value)?;
+            Ok(())
+        }
+    }
+
+    impl PSP22Metadata for MockReentrantPSP22Contract {
+        #[ink(message)]
+        fn token_name(&self) -> Option<String> {
+            None
+        }
+
+        #[ink(message)]
+        fn token_symbol(&self) -> Option<String> {
+            None
+        }
+
+        #[ink(message)]
+        fn token_decimals(&self) -> u8 {
+            self.decimals
+        }
+    }
+}
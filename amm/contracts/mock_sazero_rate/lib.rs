@@ -2,9 +2,16 @@
 
 #[ink::contract]
 mod mock_sazero_rate {
+    use traits::{RateInfo, RateProviderError};
+
     #[ink(storage)]
     pub struct MockSazeroRateContract {
         rate: u128,
+        /// Decimals of the rate reported by `RateProviderV2`.
+        decimals: u8,
+        /// Update time of the rate reported by `RateProviderV2` (`None` for the current block).
+        last_updated: Option<u64>,
+        available: bool,
     }
 
     impl MockSazeroRateContract {
@@ -13,6 +20,9 @@ mod mock_sazero_rate {
         pub fn new() -> Self {
             Self {
                 rate: 10u128.pow(12u32),
+                decimals: 12,
+                last_updated: None,
+                available: true,
             }
         }
 
@@ -20,6 +30,18 @@ mod mock_sazero_rate {
         pub fn set_rate(&mut self, rate: u128) {
             self.rate = rate;
         }
+
+        /// Sets the precision and update time of the rate reported by `RateProviderV2`.
+        #[ink(message)]
+        pub fn set_rate_info(&mut self, decimals: u8, last_updated: Option<u64>) {
+            self.decimals = decimals;
+            self.last_updated = last_updated;
+        }
+
+        #[ink(message)]
+        pub fn set_available(&mut self, available: bool) {
+            self.available = available;
+        }
     }
 
     impl traits::RateProvider for MockSazeroRateContract {
@@ -28,4 +50,25 @@ mod mock_sazero_rate {
             self.rate
         }
     }
+
+    impl traits::RateProviderV2 for MockSazeroRateContract {
+        #[ink(message)]
+        fn get_rate(&mut self) -> Result<RateInfo, RateProviderError> {
+            if !self.available {
+                return Err(RateProviderError::Unavailable);
+            }
+            Ok(RateInfo {
+                rate: self.rate,
+                decimals: self.decimals,
+                last_updated: self
+                    .last_updated
+                    .unwrap_or_else(|| self.env().block_timestamp()),
+            })
+        }
+
+        #[ink(message)]
+        fn rate_base_token(&self) -> Option<AccountId> {
+            None
+        }
+    }
 }
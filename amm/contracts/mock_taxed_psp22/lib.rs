@@ -0,0 +1,139 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+/// PSP22 token charging a fee on every transfer.
+///
+/// The fee is deducted from the transferred value and burned,
+/// so the recipient is credited with less than the sent value.
+#[ink::contract]
+mod mock_taxed_psp22 {
+    use ink::prelude::{string::String, vec::Vec};
+    use psp22::{PSP22Data, PSP22Error, PSP22Metadata, PSP22};
+
+    /// Fee denominator (1e9 precision).
+    const FEE_DENOM: u128 = 1_000_000_000;
+
+    #[ink(storage)]
+    pub struct MockTaxedPSP22Contract {
+        data: PSP22Data,
+        decimals: u8,
+        /// Fee charged on transfer, in 1e9 precision.
+        tax: u32,
+    }
+
+    impl MockTaxedPSP22Contract {
+        #[ink(constructor)]
+        pub fn new(supply: u128, decimals: u8, tax: u32) -> Self {
+            let (data, _) = PSP22Data::new(supply, Self::env().caller());
+            Self {
+                data,
+                decimals,
+                tax,
+            }
+        }
+
+        #[ink(message)]
+        pub fn set_tax(&mut self, tax: u32) {
+            self.tax = tax;
+        }
+
+        #[ink(message)]
+        pub fn tax(&self) -> u32 {
+            self.tax
+        }
+
+        /// Burns the tax from the value credited to `to`.
+        fn charge_tax(&mut self, to: AccountId, value: u128) -> Result<(), PSP22Error> {
+            let tax = value * self.tax as u128 / FEE_DENOM;
+            if tax > 0 {
+                self.data.burn(to, tax)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl PSP22 for MockTaxedPSP22Contract {
+        #[ink(message)]
+        fn total_supply(&self) -> u128 {
+            self.data.total_supply()
+        }
+
+        #[ink(message)]
+        fn balance_of(&self, owner: AccountId) -> u128 {
+            self.data.balance_of(owner)
+        }
+
+        #[ink(message)]
+        fn allowance(&self, owner: AccountId, spender: AccountId) -> u128 {
+            self.data.allowance(owner, spender)
+        }
+
+        #[ink(message)]
+        fn transfer(
+            &mut self,
+            to: AccountId,
+            value: u128,
+            _data: Vec<u8>,
+        ) -> Result<(), PSP22Error> {
+            self.data.transfer(self.env().caller(), to, value)?;
+            self.charge_tax(to, value)
+        }
+
+        #[ink(message)]
+        fn transfer_from(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: u128,
+            _data: Vec<u8>,
+        ) -> Result<(), PSP22Error> {
+            self.data
+                .transfer_from(self.env().caller(), from, to, value)?;
+            self.charge_tax(to, value)
+        }
+
+        #[ink(message)]
+        fn approve(&mut self, spender: AccountId, value: u128) -> Result<(), PSP22Error> {
+            self.data.approve(self.env().caller(), spender, value)?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn increase_allowance(
+            &mut self,
+            spender: AccountId,
+            delta_value: u128,
+        ) -> Result<(), PSP22Error> {
+            self.data
+                .increase_allowance(self.env().caller(), spender, delta_value)?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn decrease_allowance(
+            &mut self,
+            spender: AccountId,
+            delta_value: u128,
+        ) -> Result<(), PSP22Error> {
+            self.data
+                .decrease_allowance(self.env().caller(), spender, delta_value)?;
+            Ok(())
+        }
+    }
+
+    impl PSP22Metadata for MockTaxedPSP22Contract {
+        #[ink(message)]
+        fn token_name(&self) -> Option<String> {
+            None
+        }
+
+        #[ink(message)]
+        fn token_symbol(&self) -> Option<String> {
+            None
+        }
+
+        #[ink(message)]
+        fn token_decimals(&self) -> u8 {
+            self.decimals
+        }
+    }
+}
@@ -0,0 +1,429 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+/// Rate provider returning the median of rates of several sources.
+///
+/// A source is either a `RateProvider` or a `RateProviderV2`, queried when the rate
+/// is requested, or a reporter account pushing its rate with `report`. Rates older than
+/// the max age of their source are ignored, as are providers which fail or return a zero rate.
+/// A `RateProvider` rate is treated as updated in the current block.
+///
+/// The rate is rejected if fewer than `quorum` sources are available, or if any
+/// of the available rates deviates from their median by more than `max_deviation`.
+/// In that case the last accepted rate is returned instead, if it is not older
+/// than `fallback_max_age`.
+///
+/// Reported and `RateProvider` rates are expected to have `RATE_DECIMALS` decimal places,
+/// `RateProviderV2` rates are scaled to it, rounding down.
+#[ink::contract]
+pub mod rate_aggregator {
+    use amm_helpers::{
+        constants::{
+            rate_aggregator::{DEVIATION_PRECISION, MAX_SOURCES},
+            stable_pool::RATE_DECIMALS,
+        },
+        ensure,
+        math::{casted_mul, scale_rate},
+    };
+    use ink::{
+        codegen::TraitCallBuilder,
+        contract_ref,
+        prelude::{format, vec::Vec},
+        storage::Mapping,
+    };
+    use traits::{
+        Ownable2Step, Ownable2StepData, Ownable2StepError, Ownable2StepResult, RateInfo,
+        RateProvider, RateProviderError, RateProviderV2,
+    };
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum Source {
+        /// Rate of a `RateProvider`, queried in the current block.
+        Provider(AccountId),
+        /// Rate reported by the account and its max age (in milliseconds).
+        Reporter(AccountId, u64),
+        /// Rate of a `RateProviderV2` and its max age (in milliseconds).
+        ProviderV2(AccountId, u64),
+    }
+
+    impl Source {
+        fn account(&self) -> AccountId {
+            match self {
+                Source::Provider(account)
+                | Source::Reporter(account, _)
+                | Source::ProviderV2(account, _) => *account,
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum RateAggregatorError {
+        Ownable2StepError(Ownable2StepError),
+        NotReporter,
+        ZeroRate,
+        QuorumNotReached,
+        DeviationTooLarge,
+        TooManySources,
+        DuplicateSource,
+        InvalidQuorum,
+        InvalidMaxDeviation,
+    }
+
+    impl From<Ownable2StepError> for RateAggregatorError {
+        fn from(error: Ownable2StepError) -> Self {
+            RateAggregatorError::Ownable2StepError(error)
+        }
+    }
+
+    #[ink(event)]
+    pub struct RateReported {
+        #[ink(topic)]
+        pub reporter: AccountId,
+        pub rate: u128,
+        pub timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct ConfigChanged {
+        pub sources: Vec<Source>,
+        pub quorum: u8,
+        pub max_deviation: u32,
+        pub fallback_max_age: Option<u64>,
+    }
+
+    #[ink(event)]
+    pub struct TransferOwnershipInitiated {
+        #[ink(topic)]
+        pub new_owner: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct TransferOwnershipAccepted {
+        #[ink(topic)]
+        pub new_owner: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct OwnershipRenounced {}
+
+    #[ink(storage)]
+    pub struct RateAggregatorContract {
+        ownable: Ownable2StepData,
+        sources: Vec<Source>,
+        /// Last rate reported by each reporter and its timestamp.
+        reports: Mapping<AccountId, (u128, u64)>,
+        /// Minimum number of available sources.
+        quorum: u8,
+        /// Maximum deviation of a source rate from the median, with `DEVIATION_PRECISION`.
+        max_deviation: u32,
+        /// Max age (in milliseconds) of the last accepted rate returned instead of
+        /// a rejected one (`None` disables the fallback).
+        fallback_max_age: Option<u64>,
+        /// Last accepted rate and the timestamp of its oldest source rate.
+        last_rate: Option<(u128, u64)>,
+        /// Token in which the rate is denominated (`None` for the native token).
+        base_token: Option<AccountId>,
+    }
+
+    impl RateAggregatorContract {
+        #[ink(constructor)]
+        pub fn new(
+            owner: AccountId,
+            sources: Vec<Source>,
+            quorum: u8,
+            max_deviation: u32,
+            fallback_max_age: Option<u64>,
+            base_token: Option<AccountId>,
+        ) -> Result<Self, RateAggregatorError> {
+            validate_config(&sources, quorum, max_deviation)?;
+            Ok(Self {
+                ownable: Ownable2StepData::new(owner),
+                sources,
+                reports: Mapping::default(),
+                quorum,
+                max_deviation,
+                fallback_max_age,
+                last_rate: None,
+                base_token,
+            })
+        }
+
+        #[ink(message)]
+        pub fn sources(&self) -> Vec<Source> {
+            self.sources.clone()
+        }
+
+        #[ink(message)]
+        pub fn quorum(&self) -> u8 {
+            self.quorum
+        }
+
+        #[ink(message)]
+        pub fn max_deviation(&self) -> u32 {
+            self.max_deviation
+        }
+
+        #[ink(message)]
+        pub fn fallback_max_age(&self) -> Option<u64> {
+            self.fallback_max_age
+        }
+
+        /// Last accepted rate and the timestamp of its oldest source rate.
+        #[ink(message)]
+        pub fn last_rate(&self) -> Option<(u128, u64)> {
+            self.last_rate
+        }
+
+        /// Last rate reported by `reporter` and its timestamp.
+        #[ink(message)]
+        pub fn report_of(&self, reporter: AccountId) -> Option<(u128, u64)> {
+            self.reports.get(reporter)
+        }
+
+        /// Reports the rate of the caller.
+        ///
+        /// Can be called only by reporters in `sources`.
+        #[ink(message)]
+        pub fn report(&mut self, rate: u128) -> Result<(), RateAggregatorError> {
+            let reporter = self.env().caller();
+            ensure!(
+                self.sources.iter().any(
+                    |source| matches!(source, Source::Reporter(account, _) if *account == reporter)
+                ),
+                RateAggregatorError::NotReporter
+            );
+            ensure!(rate > 0, RateAggregatorError::ZeroRate);
+            let timestamp = self.env().block_timestamp();
+            self.reports.insert(reporter, &(rate, timestamp));
+            self.env().emit_event(RateReported {
+                reporter,
+                rate,
+                timestamp,
+            });
+            Ok(())
+        }
+
+        /// Replaces the sources and the rules of accepting their rates.
+        ///
+        /// Clears the last accepted rate. Can be called only by the owner.
+        #[ink(message)]
+        pub fn set_config(
+            &mut self,
+            sources: Vec<Source>,
+            quorum: u8,
+            max_deviation: u32,
+            fallback_max_age: Option<u64>,
+        ) -> Result<(), RateAggregatorError> {
+            self.ensure_owner()?;
+            validate_config(&sources, quorum, max_deviation)?;
+            self.sources = sources.clone();
+            self.quorum = quorum;
+            self.max_deviation = max_deviation;
+            self.fallback_max_age = fallback_max_age;
+            self.last_rate = None;
+            self.env().emit_event(ConfigChanged {
+                sources,
+                quorum,
+                max_deviation,
+                fallback_max_age,
+            });
+            Ok(())
+        }
+
+        /// Returns the rate with the timestamp of its oldest source rate,
+        /// updating the last accepted rate.
+        fn update_rate(&mut self) -> Result<(u128, u64), RateAggregatorError> {
+            match self.aggregate_rate() {
+                Ok(rate) => {
+                    self.last_rate = Some(rate);
+                    Ok(rate)
+                }
+                Err(error) => {
+                    let now = self.env().block_timestamp();
+                    match (self.last_rate, self.fallback_max_age) {
+                        (Some((rate, updated_at)), Some(max_age))
+                            if now.saturating_sub(updated_at) <= max_age =>
+                        {
+                            Ok((rate, updated_at))
+                        }
+                        _ => Err(error),
+                    }
+                }
+            }
+        }
+
+        /// Median of the available source rates with the timestamp of the oldest one.
+        fn aggregate_rate(&self) -> Result<(u128, u64), RateAggregatorError> {
+            let now = self.env().block_timestamp();
+            let mut rates = Vec::with_capacity(self.sources.len());
+            let mut updated_at = now;
+            for source in self.sources.iter() {
+                if let Some((rate, timestamp)) = self.source_rate(source, now) {
+                    rates.push(rate);
+                    updated_at = updated_at.min(timestamp);
+                }
+            }
+            ensure!(
+                rates.len() >= self.quorum.into(),
+                RateAggregatorError::QuorumNotReached
+            );
+            rates.sort_unstable();
+            let median = median(&rates);
+            ensure!(
+                rates.iter().all(|&rate| casted_mul(
+                    rate.abs_diff(median),
+                    DEVIATION_PRECISION.into()
+                ) <= casted_mul(median, self.max_deviation.into())),
+                RateAggregatorError::DeviationTooLarge
+            );
+            Ok((median, updated_at))
+        }
+
+        /// Rate of the source with its timestamp, if it is available and fresh.
+        fn source_rate(&self, source: &Source, now: u64) -> Option<(u128, u64)> {
+            let (rate, timestamp, max_age) = match *source {
+                Source::Provider(rate_provider) => {
+                    let mut rate_provider: contract_ref!(RateProvider) = rate_provider.into();
+                    match rate_provider.call_mut().get_rate().try_invoke() {
+                        Ok(Ok(rate)) => (rate, now, 0),
+                        _ => return None,
+                    }
+                }
+                Source::ProviderV2(rate_provider, max_age) => {
+                    let mut rate_provider: contract_ref!(RateProviderV2) = rate_provider.into();
+                    let RateInfo {
+                        rate,
+                        decimals,
+                        last_updated,
+                    } = match rate_provider.call_mut().get_rate().try_invoke() {
+                        Ok(Ok(Ok(rate_info))) => rate_info,
+                        _ => return None,
+                    };
+                    (scale_rate(rate, decimals).ok()?, last_updated, max_age)
+                }
+                Source::Reporter(reporter, max_age) => {
+                    let (rate, timestamp) = self.reports.get(reporter)?;
+                    (rate, timestamp, max_age)
+                }
+            };
+            (rate > 0 && now.saturating_sub(timestamp) <= max_age).then_some((rate, timestamp))
+        }
+    }
+
+    /// Median of sorted, non-empty `rates`, rounded down.
+    fn median(rates: &[u128]) -> u128 {
+        let mid = rates.len() / 2;
+        if rates.len() % 2 == 1 {
+            rates[mid]
+        } else {
+            rates[mid - 1] + (rates[mid] - rates[mid - 1]) / 2
+        }
+    }
+
+    fn validate_config(
+        sources: &[Source],
+        quorum: u8,
+        max_deviation: u32,
+    ) -> Result<(), RateAggregatorError> {
+        ensure!(
+            sources.len() <= MAX_SOURCES,
+            RateAggregatorError::TooManySources
+        );
+        ensure!(
+            quorum > 0 && usize::from(quorum) <= sources.len(),
+            RateAggregatorError::InvalidQuorum
+        );
+        ensure!(
+            max_deviation <= DEVIATION_PRECISION,
+            RateAggregatorError::InvalidMaxDeviation
+        );
+        for (i, source) in sources.iter().enumerate() {
+            ensure!(
+                sources[i + 1..]
+                    .iter()
+                    .all(|other| other.account() != source.account()),
+                RateAggregatorError::DuplicateSource
+            );
+        }
+        Ok(())
+    }
+
+    impl RateProvider for RateAggregatorContract {
+        /// Panics if the rate is rejected and there is no fallback rate.
+        /// Use `RateProviderV2::get_rate` to handle the error.
+        #[ink(message)]
+        fn get_rate(&mut self) -> u128 {
+            match self.update_rate() {
+                Ok((rate, _)) => rate,
+                Err(error) => panic!("Rate aggregator error: {error:?}"),
+            }
+        }
+    }
+
+    impl RateProviderV2 for RateAggregatorContract {
+        /// The rate is updated at the timestamp of its oldest source rate.
+        #[ink(message)]
+        fn get_rate(&mut self) -> Result<RateInfo, RateProviderError> {
+            let (rate, last_updated) = self
+                .update_rate()
+                .map_err(|error| RateProviderError::Custom(format!("{error:?}")))?;
+            Ok(RateInfo {
+                rate,
+                decimals: RATE_DECIMALS,
+                last_updated,
+            })
+        }
+
+        #[ink(message)]
+        fn rate_base_token(&self) -> Option<AccountId> {
+            self.base_token
+        }
+    }
+
+    impl Ownable2Step for RateAggregatorContract {
+        #[ink(message)]
+        fn get_owner(&self) -> Ownable2StepResult<AccountId> {
+            self.ownable.get_owner()
+        }
+
+        #[ink(message)]
+        fn get_pending_owner(&self) -> Ownable2StepResult<AccountId> {
+            self.ownable.get_pending_owner()
+        }
+
+        #[ink(message)]
+        fn transfer_ownership(&mut self, new_owner: AccountId) -> Ownable2StepResult<()> {
+            self.ownable
+                .transfer_ownership(self.env().caller(), new_owner)?;
+            self.env()
+                .emit_event(TransferOwnershipInitiated { new_owner });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn accept_ownership(&mut self) -> Ownable2StepResult<()> {
+            let new_owner = self.env().caller();
+            self.ownable.accept_ownership(new_owner)?;
+            self.env()
+                .emit_event(TransferOwnershipAccepted { new_owner });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn renounce_ownership(&mut self) -> Ownable2StepResult<()> {
+            self.ownable
+                .renounce_ownership(self.env().caller(), self.env().account_id())?;
+            self.env().emit_event(OwnershipRenounced {});
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn ensure_owner(&self) -> Ownable2StepResult<()> {
+            self.ownable.ensure_owner(self.env().caller())
+        }
+    }
+}
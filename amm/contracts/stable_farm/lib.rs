@@ -0,0 +1,518 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+/// Farm incentivizing liquidity provision to a stable pool.
+///
+/// Users stake LP tokens of the pool and earn rewards in up to
+/// `MAX_REWARD_TOKENS` PSP22 tokens.
+///
+/// Rewards are distributed at a constant rate during reward periods
+/// scheduled by the owner. Each reward token has a reward per share accumulator,
+/// which is increased proportionally to the elapsed time and inversely proportionally
+/// to the total amount of staked shares.
+///
+/// NOTE: Rewards for the time when no shares are staked are not distributed
+/// and stay locked in the contract.
+///
+/// Tags of `MathError`s returned by the farm:
+/// - `AddOverflow(201)`: unclaimed rewards overflow
+/// - `AddOverflow(202)`: reward per share overflows
+/// - `AddOverflow(203)`: deposited shares overflow
+/// - `AddOverflow(204)`: total shares overflow
+/// - `CastOverflow(201)`: earned rewards exceed u128
+/// - `CastOverflow(202)`: reward per share increase exceeds u128
+/// - `DivByZero(201)`, `DivByZero(202)`: reward precision or total shares is zero
+/// - `MulOverflow(202)`: reward per share increase overflows
+/// - `MulOverflow(203)`, `MulOverflow(204)`: reward amount overflows
+/// - `SubUnderflow(201)`: reward per share decreases
+/// - `SubUnderflow(202)`, `SubUnderflow(203)`: amount exceeds total shares
+#[ink::contract]
+pub mod stable_farm {
+    use amm_helpers::{
+        constants::stable_farm::{MAX_REWARD_TOKENS, REWARD_PER_SHARE_PRECISION},
+        ensure,
+        math::casted_mul,
+    };
+    use ink::{
+        contract_ref,
+        prelude::{vec, vec::Vec},
+        storage::Mapping,
+    };
+    use psp22::PSP22;
+    use traits::{
+        MathError, Ownable2Step, Ownable2StepData, Ownable2StepResult, StableFarm, StableFarmError,
+    };
+
+    #[ink(event)]
+    pub struct Deposit {
+        #[ink(topic)]
+        pub account: AccountId,
+        pub amount: u128,
+    }
+
+    #[ink(event)]
+    pub struct Withdraw {
+        #[ink(topic)]
+        pub account: AccountId,
+        pub amount: u128,
+    }
+
+    #[ink(event)]
+    pub struct EmergencyWithdraw {
+        #[ink(topic)]
+        pub account: AccountId,
+        pub amount: u128,
+    }
+
+    #[ink(event)]
+    pub struct Claim {
+        #[ink(topic)]
+        pub account: AccountId,
+        pub amounts: Vec<u128>,
+    }
+
+    #[ink(event)]
+    pub struct RewardsStarted {
+        pub start: u64,
+        pub end: u64,
+        pub reward_rates: Vec<u128>,
+    }
+
+    #[ink(event)]
+    pub struct RewardsStopped {
+        pub time: u64,
+        pub returned_amounts: Vec<u128>,
+    }
+
+    #[ink(event)]
+    pub struct TransferOwnershipInitiated {
+        #[ink(topic)]
+        pub new_owner: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct TransferOwnershipAccepted {
+        #[ink(topic)]
+        pub new_owner: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct OwnershipRenounced {}
+
+    #[derive(Debug, Default, Clone, scale::Encode, scale::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct UserPosition {
+        /// Staked LP tokens.
+        shares: u128,
+        /// Reward per share accumulators at the time of the last position update.
+        reward_per_share_paid: Vec<u128>,
+        /// Accrued, not yet claimed rewards.
+        unclaimed: Vec<u128>,
+    }
+
+    impl UserPosition {
+        fn new(reward_token_count: usize) -> Self {
+            Self {
+                shares: 0,
+                reward_per_share_paid: vec![0; reward_token_count],
+                unclaimed: vec![0; reward_token_count],
+            }
+        }
+
+        /// Moves rewards accrued since the last update to `unclaimed`.
+        fn accrue(&mut self, reward_per_share: &[u128]) -> Result<(), MathError> {
+            for (i, &rps) in reward_per_share.iter().enumerate() {
+                let earned: u128 = casted_mul(
+                    self.shares,
+                    rps.checked_sub(self.reward_per_share_paid[i])
+                        .ok_or(MathError::SubUnderflow(201))?,
+                )
+                .checked_div(REWARD_PER_SHARE_PRECISION.into())
+                .ok_or(MathError::DivByZero(201))?
+                .try_into()
+                .map_err(|_| MathError::CastOverflow(201))?;
+                self.unclaimed[i] = self.unclaimed[i]
+                    .checked_add(earned)
+                    .ok_or(MathError::AddOverflow(201))?;
+                self.reward_per_share_paid[i] = rps;
+            }
+            Ok(())
+        }
+    }
+
+    #[ink(storage)]
+    pub struct StableFarmContract {
+        ownable: Ownable2StepData,
+        /// Stable pool which LP tokens are staked.
+        pool_id: AccountId,
+        /// List of reward tokens.
+        reward_tokens: Vec<AccountId>,
+        /// Total amount of staked LP tokens.
+        total_shares: u128,
+        /// Current (or last) reward period start time.
+        start: u64,
+        /// Current (or last) reward period end time.
+        end: u64,
+        /// Rewards distributed per millisecond during the current (or last) reward period.
+        reward_rates: Vec<u128>,
+        /// Accumulated rewards per share, multiplied by `REWARD_PER_SHARE_PRECISION`.
+        reward_per_share: Vec<u128>,
+        /// Time up to which `reward_per_share` is accumulated.
+        last_update: u64,
+        /// Users' positions.
+        positions: Mapping<AccountId, UserPosition>,
+    }
+
+    impl StableFarmContract {
+        #[ink(constructor)]
+        pub fn new(
+            pool_id: AccountId,
+            reward_tokens: Vec<AccountId>,
+            owner: AccountId,
+        ) -> Result<Self, StableFarmError> {
+            let mut unique_tokens = reward_tokens.clone();
+            unique_tokens.sort();
+            unique_tokens.dedup();
+            ensure!(
+                unique_tokens.len() == reward_tokens.len(),
+                StableFarmError::DuplicateRewardToken
+            );
+            ensure!(
+                (1..=MAX_REWARD_TOKENS).contains(&reward_tokens.len()),
+                StableFarmError::TooManyRewardTokens
+            );
+            // rewards would be paid out of the staked LP tokens
+            ensure!(
+                !reward_tokens.contains(&pool_id),
+                StableFarmError::PoolTokenAsReward
+            );
+            let reward_token_count = reward_tokens.len();
+            Ok(Self {
+                ownable: Ownable2StepData::new(owner),
+                pool_id,
+                reward_tokens,
+                total_shares: 0,
+                start: 0,
+                end: 0,
+                reward_rates: vec![0; reward_token_count],
+                reward_per_share: vec![0; reward_token_count],
+                last_update: 0,
+                positions: Mapping::new(),
+            })
+        }
+
+        #[inline]
+        fn token_by_address(&self, address: AccountId) -> contract_ref!(PSP22) {
+            address.into()
+        }
+
+        /// Returns reward per share accumulators updated up to `time`.
+        fn reward_per_share_at(&self, time: u64) -> Result<Vec<u128>, MathError> {
+            let time = time.min(self.end);
+            if time <= self.last_update || self.total_shares == 0 {
+                return Ok(self.reward_per_share.clone());
+            }
+            let elapsed = (time - self.last_update) as u128;
+            self.reward_per_share
+                .iter()
+                .zip(self.reward_rates.iter())
+                .map(|(&rps, &rate)| {
+                    let increase: u128 = casted_mul(rate, elapsed)
+                        .checked_mul(REWARD_PER_SHARE_PRECISION.into())
+                        .ok_or(MathError::MulOverflow(202))?
+                        .checked_div(self.total_shares.into())
+                        .ok_or(MathError::DivByZero(202))?
+                        .try_into()
+                        .map_err(|_| MathError::CastOverflow(202))?;
+                    rps.checked_add(increase).ok_or(MathError::AddOverflow(202))
+                })
+                .collect()
+        }
+
+        /// Accumulates rewards up to the current time.
+        fn update_rewards(&mut self) -> Result<(), MathError> {
+            let now = self.env().block_timestamp();
+            self.reward_per_share = self.reward_per_share_at(now)?;
+            self.last_update = self.last_update.max(now.min(self.end));
+            Ok(())
+        }
+
+        fn position(&self, account: AccountId) -> UserPosition {
+            self.positions
+                .get(account)
+                .unwrap_or_else(|| UserPosition::new(self.reward_tokens.len()))
+        }
+
+        /// Returns `account` position with rewards accrued up to the current time.
+        /// Accumulators have to be updated beforehand.
+        fn updated_position(&self, account: AccountId) -> Result<UserPosition, MathError> {
+            let mut position = self.position(account);
+            position.accrue(&self.reward_per_share)?;
+            Ok(position)
+        }
+
+        fn transfer_rewards(&self, to: AccountId, amounts: &[u128]) -> Result<(), StableFarmError> {
+            for (&token, &amount) in self.reward_tokens.iter().zip(amounts.iter()) {
+                if amount > 0 {
+                    self.token_by_address(token).transfer(to, amount, vec![])?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl StableFarm for StableFarmContract {
+        #[ink(message)]
+        fn pool_id(&self) -> AccountId {
+            self.pool_id
+        }
+
+        #[ink(message)]
+        fn reward_tokens(&self) -> Vec<AccountId> {
+            self.reward_tokens.clone()
+        }
+
+        #[ink(message)]
+        fn total_shares(&self) -> u128 {
+            self.total_shares
+        }
+
+        #[ink(message)]
+        fn shares_of(&self, account: AccountId) -> u128 {
+            self.positions
+                .get(account)
+                .map(|position| position.shares)
+                .unwrap_or(0)
+        }
+
+        #[ink(message)]
+        fn reward_period(&self) -> Option<(u64, u64)> {
+            if self.end == 0 {
+                None
+            } else {
+                Some((self.start, self.end))
+            }
+        }
+
+        #[ink(message)]
+        fn reward_rates(&self) -> Vec<u128> {
+            self.reward_rates.clone()
+        }
+
+        #[ink(message)]
+        fn claimable(&self, account: AccountId) -> Result<Vec<u128>, StableFarmError> {
+            let reward_per_share = self.reward_per_share_at(self.env().block_timestamp())?;
+            let mut position = self.position(account);
+            position.accrue(&reward_per_share)?;
+            Ok(position.unclaimed)
+        }
+
+        #[ink(message)]
+        fn deposit(&mut self, amount: u128) -> Result<(), StableFarmError> {
+            ensure!(amount > 0, StableFarmError::ZeroAmount);
+            let account = self.env().caller();
+            self.update_rewards()?;
+            let mut position = self.updated_position(account)?;
+            position.shares = position
+                .shares
+                .checked_add(amount)
+                .ok_or(MathError::AddOverflow(203))?;
+            self.total_shares = self
+                .total_shares
+                .checked_add(amount)
+                .ok_or(MathError::AddOverflow(204))?;
+            self.positions.insert(account, &position);
+
+            self.token_by_address(self.pool_id).transfer_from(
+                account,
+                self.env().account_id(),
+                amount,
+                vec![],
+            )?;
+            self.env().emit_event(Deposit { account, amount });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn withdraw(&mut self, amount: u128) -> Result<(), StableFarmError> {
+            ensure!(amount > 0, StableFarmError::ZeroAmount);
+            let account = self.env().caller();
+            self.update_rewards()?;
+            let mut position = self.updated_position(account)?;
+            position.shares = position
+                .shares
+                .checked_sub(amount)
+                .ok_or(StableFarmError::InsufficientShares)?;
+            self.total_shares = self
+                .total_shares
+                .checked_sub(amount)
+                .ok_or(MathError::SubUnderflow(202))?;
+            self.positions.insert(account, &position);
+
+            self.token_by_address(self.pool_id)
+                .transfer(account, amount, vec![])?;
+            self.env().emit_event(Withdraw { account, amount });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn claim(&mut self) -> Result<Vec<u128>, StableFarmError> {
+            let account = self.env().caller();
+            self.update_rewards()?;
+            let mut position = self.updated_position(account)?;
+            let amounts =
+                core::mem::replace(&mut position.unclaimed, vec![0; self.reward_tokens.len()]);
+            self.positions.insert(account, &position);
+
+            self.transfer_rewards(account, &amounts)?;
+            self.env().emit_event(Claim {
+                account,
+                amounts: amounts.clone(),
+            });
+            Ok(amounts)
+        }
+
+        #[ink(message)]
+        fn emergency_withdraw(&mut self) -> Result<u128, StableFarmError> {
+            let account = self.env().caller();
+            let amount = self.shares_of(account);
+            ensure!(amount > 0, StableFarmError::InsufficientShares);
+            // the exit must not depend on the rewards math, accumulators which overflow
+            // are left as they are
+            _ = self.update_rewards();
+            self.total_shares = self
+                .total_shares
+                .checked_sub(amount)
+                .ok_or(MathError::SubUnderflow(203))?;
+            self.positions.remove(account);
+
+            self.token_by_address(self.pool_id)
+                .transfer(account, amount, vec![])?;
+            self.env().emit_event(EmergencyWithdraw { account, amount });
+            Ok(amount)
+        }
+
+        #[ink(message)]
+        fn start_rewards(
+            &mut self,
+            start: u64,
+            end: u64,
+            rewards: Vec<u128>,
+        ) -> Result<(), StableFarmError> {
+            self.ensure_owner()?;
+            ensure!(
+                rewards.len() == self.reward_tokens.len(),
+                StableFarmError::IncorrectAmountsCount
+            );
+            let now = self.env().block_timestamp();
+            ensure!(now >= self.end, StableFarmError::RewardPeriodActive);
+            ensure!(
+                now <= start && start < end,
+                StableFarmError::InvalidRewardPeriod
+            );
+            self.update_rewards()?;
+
+            let duration = (end - start) as u128;
+            let reward_rates: Vec<u128> = rewards.iter().map(|&r| r / duration).collect();
+            ensure!(
+                reward_rates.iter().any(|&rate| rate > 0),
+                StableFarmError::ZeroRewardRate
+            );
+            self.start = start;
+            self.end = end;
+            self.last_update = start;
+            self.reward_rates = reward_rates.clone();
+
+            let caller = self.env().caller();
+            let this = self.env().account_id();
+            for (&token, &rate) in self.reward_tokens.iter().zip(reward_rates.iter()) {
+                if rate > 0 {
+                    let amount = rate
+                        .checked_mul(duration)
+                        .ok_or(MathError::MulOverflow(203))?;
+                    self.token_by_address(token)
+                        .transfer_from(caller, this, amount, vec![])?;
+                }
+            }
+            self.env().emit_event(RewardsStarted {
+                start,
+                end,
+                reward_rates,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn stop_rewards(&mut self) -> Result<Vec<u128>, StableFarmError> {
+            self.ensure_owner()?;
+            let now = self.env().block_timestamp();
+            ensure!(now < self.end, StableFarmError::NoActiveRewardPeriod);
+            self.update_rewards()?;
+
+            let remaining_duration = (self.end - now.max(self.start)) as u128;
+            let returned_amounts = self
+                .reward_rates
+                .iter()
+                .map(|&rate| {
+                    rate.checked_mul(remaining_duration)
+                        .ok_or(MathError::MulOverflow(204))
+                })
+                .collect::<Result<Vec<u128>, MathError>>()?;
+            self.start = self.start.min(now);
+            self.end = now;
+            self.last_update = self.last_update.min(now);
+
+            self.transfer_rewards(self.env().caller(), &returned_amounts)?;
+            self.env().emit_event(RewardsStopped {
+                time: now,
+                returned_amounts: returned_amounts.clone(),
+            });
+            Ok(returned_amounts)
+        }
+    }
+
+    impl Ownable2Step for StableFarmContract {
+        #[ink(message)]
+        fn get_owner(&self) -> Ownable2StepResult<AccountId> {
+            self.ownable.get_owner()
+        }
+
+        #[ink(message)]
+        fn get_pending_owner(&self) -> Ownable2StepResult<AccountId> {
+            self.ownable.get_pending_owner()
+        }
+
+        #[ink(message)]
+        fn transfer_ownership(&mut self, new_owner: AccountId) -> Ownable2StepResult<()> {
+            self.ownable
+                .transfer_ownership(self.env().caller(), new_owner)?;
+            self.env()
+                .emit_event(TransferOwnershipInitiated { new_owner });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn accept_ownership(&mut self) -> Ownable2StepResult<()> {
+            let new_owner = self.env().caller();
+            self.ownable.accept_ownership(new_owner)?;
+            self.env()
+                .emit_event(TransferOwnershipAccepted { new_owner });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn renounce_ownership(&mut self) -> Ownable2StepResult<()> {
+            self.ownable
+                .renounce_ownership(self.env().caller(), self.env().account_id())?;
+            self.env().emit_event(OwnershipRenounced {});
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn ensure_owner(&self) -> Ownable2StepResult<()> {
+            self.ownable.ensure_owner(self.env().caller())
+        }
+    }
+}
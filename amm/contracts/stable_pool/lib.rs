@@ -1,5 +1,4 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
-mod amp_coef;
 mod token_rate;
 /// Stabelswap implementation based on the CurveFi stableswap model.
 ///
@@ -14,20 +13,31 @@ mod token_rate;
 /// its total supply to try and maintain a stable price a.k.a. rebasing tokens.
 #[ink::contract]
 pub mod stable_pool {
-    use crate::{amp_coef::AmpCoef, token_rate::TokenRate};
+    use crate::token_rate::{RateSpec, TokenRate};
     use amm_helpers::{
-        constants::stable_pool::{MAX_COINS, RATE_PRECISION, TOKEN_TARGET_DECIMALS},
+        constants::stable_pool::{
+            FEE_DENOM, MAX_COINS, MAX_TOKEN_DECIMALS, MINIMUM_LIQUIDITY, MIN_INITIAL_D,
+            RATE_PRECISION, TOKEN_TARGET_DECIMALS,
+        },
         ensure,
-        stable_swap_math::{self as math, fees::Fees},
+        stable_swap_math::{
+            self as math,
+            amp_coef::{AmpCoef, RampShape},
+            auto_amp::{AutoAmp, AutoAmpConfig},
+            fees::{amount_gross_of_transfer_fee, amount_net_of_transfer_fee, FeeSchedule, Fees},
+            ScaledRate,
+        },
     };
-    use ink::contract_ref;
     use ink::prelude::{
         string::{String, ToString},
         {vec, vec::Vec},
     };
+    use ink::{codegen::TraitCallBuilder, contract_ref, storage::Lazy};
     use psp22::{PSP22Data, PSP22Error, PSP22Event, PSP22Metadata, PSP22};
     use traits::{
-        MathError, Ownable2Step, Ownable2StepData, Ownable2StepResult, StablePool, StablePoolError,
+        MathError, Ownable2Step, Ownable2StepData, Ownable2StepError, Ownable2StepResult,
+        PoolState, StablePoolActions, StablePoolAdmin, StablePoolError, StablePoolQuoter,
+        StablePoolView, WrappedAZERO,
     };
 
     #[ink(event)]
@@ -113,12 +123,34 @@ pub mod stable_pool {
         pub new_fee_receiver: Option<AccountId>,
     }
 
+    #[ink(event)]
+    pub struct WazeroChanged {
+        #[ink(topic)]
+        pub new_wazero: Option<AccountId>,
+    }
+
     #[ink(event)]
     pub struct AmpCoefChange {
         pub init_amp_coef: u128,
         pub future_amp_coef: u128,
         pub init_time: u64,
         pub future_time: u64,
+        /// Intermediate waypoints `(amp_coef, time)` of the ramp.
+        pub waypoints: Vec<(u128, u64)>,
+        pub ramp_shape: RampShape,
+    }
+
+    /// Same as `AmpCoefChange`, with amplification coefficients with `A_PRECISION` (100),
+    /// emitted by `ramp_amp_coef_precise` and `ramp_amp_coef_schedule_precise`.
+    #[ink(event)]
+    pub struct AmpCoefChangePrecise {
+        pub init_amp_coef: u128,
+        pub future_amp_coef: u128,
+        pub init_time: u64,
+        pub future_time: u64,
+        /// Intermediate waypoints `(amp_coef, time)` of the ramp.
+        pub waypoints: Vec<(u128, u64)>,
+        pub ramp_shape: RampShape,
     }
 
     #[ink(event)]
@@ -127,31 +159,83 @@ pub mod stable_pool {
         pub time: u64,
     }
 
+    #[ink(event)]
+    pub struct AutoAmpChanged {
+        pub config: Option<AutoAmpConfig>,
+    }
+
     #[ink(event)]
     pub struct FeeChanged {
         pub trade_fee: u32,
         pub protocol_fee: u32,
     }
 
+    #[ink(event)]
+    pub struct FeeRamp {
+        pub init_trade_fee: u32,
+        pub init_protocol_fee: u32,
+        pub future_trade_fee: u32,
+        pub future_protocol_fee: u32,
+        pub init_time: u64,
+        pub future_time: u64,
+    }
+
+    #[ink(event)]
+    pub struct FeeRampStop {
+        pub trade_fee: u32,
+        pub protocol_fee: u32,
+        pub time: u64,
+    }
+
+    /// Amount (or amounts) of tokens deposited into the pool by the caller.
+    enum Deposit<T> {
+        /// Transferred from the caller to this contract.
+        FromCaller(T),
+        /// Already held by this contract on behalf of the caller, e.g. wrapped native tokens.
+        Held(T),
+        /// Difference between this contract balance and the recorded reserves.
+        Received,
+    }
+
     #[ink::storage_item]
     #[derive(Debug)]
     pub struct StablePoolData {
         /// List of tokens.
         tokens: Vec<AccountId>,
-        /// Tokens precision factors used for normalization.
-        precisions: Vec<u128>,
+        /// Tokens decimals, used for normalization.
+        tokens_decimals: Vec<u8>,
         /// Reserves of tokens
         reserves: Vec<u128>,
         /// Means of getting token rates, either constant or external contract call.
         token_rates: Vec<TokenRate>,
+        /// Expected fees charged by tokens on transfer, in 1e9 precision (`None` for regular tokens).
+        ///
+        /// Amounts of these tokens credited to the pool are measured by the balance difference.
+        /// The expected fee is only used for quotes and for grossing up exact-out swap inputs.
+        transfer_fees: Vec<Option<u32>>,
         /// Amplification coefficient.
         amp_coef: AmpCoef,
-        /// Fees
-        fees: Fees,
+        /// Automatic adjustment of the amplification coefficient (if enabled).
+        auto_amp: Option<AutoAmp>,
+        /// Fees, ramped over time.
+        fees: FeeSchedule,
         /// Who receives protocol fees (if any).
         fee_receiver: Option<AccountId>,
+        /// wAZERO token used for wrapping and unwrapping the native token (if any).
+        wazero: Option<AccountId>,
+        /// Reentrancy lock, held for the duration of operations calling external contracts.
+        ///
+        /// Calls into tokens do not allow reentry, so the lock is a second layer of
+        /// protection, e.g. against reentry through a call which does allow it.
+        ///
+        /// Stored lazily so that the lock is written to the contract storage immediately
+        /// and is visible to reentrant calls.
+        locked: Lazy<bool>,
     }
 
+    /// Address holding the permanently locked `MINIMUM_LIQUIDITY`.
+    pub const DEAD_ADDRESS: [u8; 32] = [0u8; 32];
+
     #[ink(storage)]
     pub struct StablePoolContract {
         ownable: Ownable2StepData,
@@ -160,11 +244,13 @@ pub mod stable_pool {
     }
 
     impl StablePoolContract {
+        #[allow(clippy::too_many_arguments)]
         pub fn new_pool(
             tokens: Vec<AccountId>,
             tokens_decimals: Vec<u8>,
             token_rates: Vec<TokenRate>,
-            amp_coef: u128,
+            transfer_fees: Vec<Option<u32>>,
+            amp_coef: AmpCoef,
             owner: AccountId,
             fees: Option<Fees>,
             fee_receiver: Option<AccountId>,
@@ -180,31 +266,45 @@ pub mod stable_pool {
             ensure!(
                 token_count == tokens_decimals.len()
                     && token_count == token_rates.len()
+                    && token_count == transfer_fees.len()
                     && (2..=MAX_COINS).contains(&token_count),
                 StablePoolError::IncorrectTokenCount
             );
 
             ensure!(
-                tokens_decimals.iter().all(|&d| d <= TOKEN_TARGET_DECIMALS),
+                tokens_decimals.iter().all(|&d| d <= MAX_TOKEN_DECIMALS),
                 StablePoolError::TooLargeTokenDecimal
             );
 
-            let precisions = tokens_decimals
-                .iter()
-                .map(|&decimal| {
-                    10u128.pow(TOKEN_TARGET_DECIMALS.checked_sub(decimal).unwrap() as u32)
-                })
-                .collect();
+            ensure!(
+                token_rates
+                    .iter()
+                    .zip(tokens_decimals.iter())
+                    .all(|(rate, &decimals)| rate.is_valid(decimals)),
+                StablePoolError::InvalidTokenRate
+            );
+
+            ensure!(
+                transfer_fees
+                    .iter()
+                    .all(|fee| fee.map_or(true, |fee| fee < FEE_DENOM)),
+                StablePoolError::InvalidFee
+            );
+
             Ok(Self {
                 ownable: Ownable2StepData::new(owner),
                 pool: StablePoolData {
                     tokens,
                     reserves: vec![0; token_count],
-                    precisions,
+                    tokens_decimals,
                     token_rates,
-                    amp_coef: AmpCoef::new(amp_coef)?,
-                    fees: fees.ok_or(StablePoolError::InvalidFee)?,
+                    transfer_fees,
+                    amp_coef,
+                    auto_amp: None,
+                    fees: FeeSchedule::new(fees.ok_or(StablePoolError::InvalidFee)?),
                     fee_receiver,
+                    wazero: None,
+                    locked: Lazy::new(),
                 },
                 psp22: PSP22Data::default(),
             })
@@ -221,11 +321,13 @@ pub mod stable_pool {
             fee_receiver: Option<AccountId>,
         ) -> Result<Self, StablePoolError> {
             let token_rates = vec![TokenRate::new_constant(RATE_PRECISION); tokens.len()];
+            let transfer_fees = vec![None; tokens.len()];
             Self::new_pool(
                 tokens,
                 tokens_decimals,
                 token_rates,
-                init_amp_coef,
+                transfer_fees,
+                AmpCoef::new(init_amp_coef)?,
                 owner,
                 Fees::new(trade_fee, protocol_fee),
                 fee_receiver,
@@ -243,6 +345,74 @@ pub mod stable_pool {
             trade_fee: u32,
             protocol_fee: u32,
             fee_receiver: Option<AccountId>,
+        ) -> Result<Self, StablePoolError> {
+            let transfer_fees = vec![None; tokens.len()];
+            Self::new_with_transfer_fees(
+                tokens,
+                tokens_decimals,
+                external_rates,
+                transfer_fees,
+                init_amp_coef,
+                owner,
+                trade_fee,
+                protocol_fee,
+                fee_receiver,
+            )
+        }
+
+        /// Creates a pool of tokens with rates of `RateProviderV2` contracts.
+        ///
+        /// `external_rates` are the rate providers with max age (in milliseconds) of their rates,
+        /// `None` for tokens with a constant rate of 1. Operations relying on a rate older than
+        /// its max age fail with `StaleRate`.
+        #[ink(constructor)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn new_rated_v2(
+            tokens: Vec<AccountId>,
+            tokens_decimals: Vec<u8>,
+            external_rates: Vec<Option<(AccountId, u64)>>,
+            init_amp_coef: u128,
+            owner: AccountId,
+            trade_fee: u32,
+            protocol_fee: u32,
+            fee_receiver: Option<AccountId>,
+        ) -> Result<Self, StablePoolError> {
+            let token_rates: Vec<TokenRate> = external_rates
+                .into_iter()
+                .map(|rate| match rate {
+                    Some((contract, max_age)) => TokenRate::new_external_v2(contract, max_age),
+                    None => TokenRate::new_constant(RATE_PRECISION),
+                })
+                .collect();
+            let transfer_fees = vec![None; tokens.len()];
+            Self::new_pool(
+                tokens,
+                tokens_decimals,
+                token_rates,
+                transfer_fees,
+                AmpCoef::new(init_amp_coef)?,
+                owner,
+                Fees::new(trade_fee, protocol_fee),
+                fee_receiver,
+            )
+        }
+
+        /// Creates a pool which may contain tokens charging a fee on transfer.
+        ///
+        /// `transfer_fees` are the expected fees charged by each token on transfer,
+        /// in 1e9 precision (`None` for regular tokens).
+        #[ink(constructor)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn new_with_transfer_fees(
+            tokens: Vec<AccountId>,
+            tokens_decimals: Vec<u8>,
+            external_rates: Vec<Option<AccountId>>,
+            transfer_fees: Vec<Option<u32>>,
+            init_amp_coef: u128,
+            owner: AccountId,
+            trade_fee: u32,
+            protocol_fee: u32,
+            fee_receiver: Option<AccountId>,
         ) -> Result<Self, StablePoolError> {
             let token_rates: Vec<TokenRate> = external_rates
                 .into_iter()
@@ -255,13 +425,153 @@ pub mod stable_pool {
                 tokens,
                 tokens_decimals,
                 token_rates,
-                init_amp_coef,
+                transfer_fees,
+                AmpCoef::new(init_amp_coef)?,
+                owner,
+                Fees::new(trade_fee, protocol_fee),
+                fee_receiver,
+            )
+        }
+
+        /// Creates a pool with a rate of each token given by `rates`, e.g. a constant rate
+        /// of a token pegged at a fixed ratio other than 1.
+        ///
+        /// `transfer_fees` are as in `new_with_transfer_fees`.
+        /// Constant rates (and factors) must be positive and, scaled for the token decimals,
+        /// fit in u128 (see `get_scaled_rates`).
+        #[ink(constructor)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn new_with_rates(
+            tokens: Vec<AccountId>,
+            tokens_decimals: Vec<u8>,
+            rates: Vec<RateSpec>,
+            transfer_fees: Vec<Option<u32>>,
+            init_amp_coef: u128,
+            owner: AccountId,
+            trade_fee: u32,
+            protocol_fee: u32,
+            fee_receiver: Option<AccountId>,
+        ) -> Result<Self, StablePoolError> {
+            Self::new_pool(
+                tokens,
+                tokens_decimals,
+                rates.into_iter().map(TokenRate::from).collect(),
+                transfer_fees,
+                AmpCoef::new(init_amp_coef)?,
+                owner,
+                Fees::new(trade_fee, protocol_fee),
+                fee_receiver,
+            )
+        }
+
+        /// Same as `new_with_rates`, with `init_amp_coef` given with `A_PRECISION` (100).
+        #[ink(constructor)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn new_with_rates_precise(
+            tokens: Vec<AccountId>,
+            tokens_decimals: Vec<u8>,
+            rates: Vec<RateSpec>,
+            transfer_fees: Vec<Option<u32>>,
+            init_amp_coef: u128,
+            owner: AccountId,
+            trade_fee: u32,
+            protocol_fee: u32,
+            fee_receiver: Option<AccountId>,
+        ) -> Result<Self, StablePoolError> {
+            Self::new_pool(
+                tokens,
+                tokens_decimals,
+                rates.into_iter().map(TokenRate::from).collect(),
+                transfer_fees,
+                AmpCoef::new_precise(init_amp_coef)?,
                 owner,
                 Fees::new(trade_fee, protocol_fee),
                 fee_receiver,
             )
         }
 
+        /// Creates a stable pool, querying tokens decimals via `PSP22Metadata::token_decimals`.
+        ///
+        /// If `expected_decimals` are supplied, the discovered decimals have to match them.
+        #[ink(constructor)]
+        pub fn new_stable_discover_decimals(
+            tokens: Vec<AccountId>,
+            expected_decimals: Option<Vec<u8>>,
+            init_amp_coef: u128,
+            owner: AccountId,
+            trade_fee: u32,
+            protocol_fee: u32,
+            fee_receiver: Option<AccountId>,
+        ) -> Result<Self, StablePoolError> {
+            let tokens_decimals = Self::discover_decimals(&tokens, expected_decimals)?;
+            Self::new_stable(
+                tokens,
+                tokens_decimals,
+                init_amp_coef,
+                owner,
+                trade_fee,
+                protocol_fee,
+                fee_receiver,
+            )
+        }
+
+        /// Creates a rated pool, querying tokens decimals via `PSP22Metadata::token_decimals`.
+        ///
+        /// If `expected_decimals` are supplied, the discovered decimals have to match them.
+        #[ink(constructor)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn new_rated_discover_decimals(
+            tokens: Vec<AccountId>,
+            expected_decimals: Option<Vec<u8>>,
+            external_rates: Vec<Option<AccountId>>,
+            init_amp_coef: u128,
+            owner: AccountId,
+            trade_fee: u32,
+            protocol_fee: u32,
+            fee_receiver: Option<AccountId>,
+        ) -> Result<Self, StablePoolError> {
+            let tokens_decimals = Self::discover_decimals(&tokens, expected_decimals)?;
+            Self::new_rated(
+                tokens,
+                tokens_decimals,
+                external_rates,
+                init_amp_coef,
+                owner,
+                trade_fee,
+                protocol_fee,
+                fee_receiver,
+            )
+        }
+
+        /// Queries decimals of each token and checks them against `expected_decimals` (if any).
+        fn discover_decimals(
+            tokens: &[AccountId],
+            expected_decimals: Option<Vec<u8>>,
+        ) -> Result<Vec<u8>, StablePoolError> {
+            if let Some(expected) = &expected_decimals {
+                ensure!(
+                    expected.len() == tokens.len(),
+                    StablePoolError::IncorrectTokenCount
+                );
+            }
+            let mut tokens_decimals = Vec::with_capacity(tokens.len());
+            for (id, &token) in tokens.iter().enumerate() {
+                let metadata: contract_ref!(PSP22Metadata) = token.into();
+                let decimals = match metadata.call().token_decimals().try_invoke() {
+                    Ok(Ok(decimals)) => decimals,
+                    _ => return Err(StablePoolError::TokenDecimalsUnavailable(token)),
+                };
+                if let Some(expected) = &expected_decimals {
+                    ensure!(
+                        expected[id] == decimals,
+                        StablePoolError::TokenDecimalsMismatch(token)
+                    );
+                }
+                tokens_decimals.push(decimals);
+            }
+            Ok(tokens_decimals)
+        }
+
         /// A helper function emitting events contained in a vector of PSP22Events.
         fn emit_events(&self, events: Vec<PSP22Event>) {
             for event in events {
@@ -282,6 +592,41 @@ pub mod stable_pool {
             }
         }
 
+        /// Returns an error if the reentrancy lock is held.
+        fn ensure_unlocked(&self) -> Result<(), StablePoolError> {
+            ensure!(
+                !self.pool.locked.get().unwrap_or(false),
+                StablePoolError::Reentrancy
+            );
+            Ok(())
+        }
+
+        /// `ensure_unlocked` for PSP22 messages of the LP token.
+        fn ensure_unlocked_psp22(&self) -> Result<(), PSP22Error> {
+            self.ensure_unlocked()
+                .map_err(|_| PSP22Error::Custom(String::from("Reentrancy")))
+        }
+
+        /// `ensure_unlocked` for `Ownable2Step` messages.
+        fn ensure_unlocked_ownable(&self) -> Ownable2StepResult<()> {
+            self.ensure_unlocked()
+                .map_err(|_| Ownable2StepError::Reentrancy)
+        }
+
+        /// Executes `f` holding the reentrancy lock.
+        ///
+        /// NOTE: If `f` returns an error, the message reverts along with the lock.
+        fn non_reentrant<T>(
+            &mut self,
+            f: impl FnOnce(&mut Self) -> Result<T, StablePoolError>,
+        ) -> Result<T, StablePoolError> {
+            self.ensure_unlocked()?;
+            self.pool.locked.set(&true);
+            let result = f(self)?;
+            self.pool.locked.set(&false);
+            Ok(result)
+        }
+
         #[inline]
         fn token_by_address(&self, address: AccountId) -> contract_ref!(PSP22) {
             address.into()
@@ -292,22 +637,53 @@ pub mod stable_pool {
             self.pool.tokens[token_id].into()
         }
 
-        /// Scaled rates are rates multiplied by precision. They are assumed to fit in u128.
-        /// If TOKEN_TARGET_DECIMALS is 18 and RATE_DECIMALS is 12, then rates not exceeding ~340282366 should fit.
-        /// That's because if precision <= 10^18 and rate <= 10^12 * 340282366, then rate * precision < 2^128.
-        fn get_scaled_rates(&mut self) -> Result<Vec<u128>, MathError> {
+        /// Scaled rates are rates combined with tokens precisions.
+        /// Tokens with up to TOKEN_TARGET_DECIMALS decimals have rates multiplied by precision,
+        /// which are assumed to fit in u128. If TOKEN_TARGET_DECIMALS is 18 and RATE_DECIMALS is 12,
+        /// then rates not exceeding ~340282366 should fit. That's because if precision <= 10^18
+        /// and rate <= 10^12 * 340282366, then rate * precision < 2^128.
+        /// Tokens with more decimals have amounts divided by precision instead.
+        fn get_scaled_rates(&mut self) -> Result<Vec<ScaledRate>, StablePoolError> {
             self.pool
                 .token_rates
                 .iter_mut()
-                .zip(self.pool.precisions.iter())
-                .map(|(rate, &precision)| {
-                    rate.get_rate()
-                        .checked_mul(precision)
-                        .ok_or(MathError::MulOverflow(104))
-                })
+                .zip(self.pool.tokens_decimals.iter())
+                .map(|(rate, &decimals)| Ok(ScaledRate::new(rate.get_rate()?, decimals)?))
                 .collect()
         }
 
+        /// Scaled rates as `get_scaled_rates`, without updating the cached rates.
+        fn peek_scaled_rates(&self) -> Result<Vec<ScaledRate>, StablePoolError> {
+            self.pool
+                .token_rates
+                .iter()
+                .zip(self.pool.tokens_decimals.iter())
+                .map(|(rate, &decimals)| Ok(ScaledRate::new(rate.peek_rate()?, decimals)?))
+                .collect()
+        }
+
+        /// Fees at the current block timestamp.
+        fn current_fees(&self) -> Fees {
+            self.pool.fees.compute_fees(self.env().block_timestamp())
+        }
+
+        /// Returns expected amount of `token_id` credited to the recipient of a transfer of `amount`.
+        fn net_of_transfer_fee(&self, token_id: usize, amount: u128) -> Result<u128, MathError> {
+            match self.pool.transfer_fees[token_id] {
+                Some(fee) => amount_net_of_transfer_fee(amount, fee),
+                None => Ok(amount),
+            }
+        }
+
+        /// Returns amount of `token_id` which has to be transferred
+        /// for the recipient to be credited with at least `amount`.
+        fn gross_of_transfer_fee(&self, token_id: usize, amount: u128) -> Result<u128, MathError> {
+            match self.pool.transfer_fees[token_id] {
+                Some(fee) => amount_gross_of_transfer_fee(amount, fee),
+                None => Ok(amount),
+            }
+        }
+
         fn token_id(&self, token: AccountId) -> Result<usize, StablePoolError> {
             self.pool
                 .tokens
@@ -333,7 +709,7 @@ pub mod stable_pool {
         /// NOTE: Rates should be updated prior to calling this function
         fn mint_protocol_fee(&mut self, fee: u128, token_id: usize) -> Result<(), StablePoolError> {
             if let Some(fee_to) = self.fee_receiver() {
-                let protocol_fee = self.pool.fees.protocol_trade_fee(fee)?;
+                let protocol_fee = self.current_fees().protocol_trade_fee(fee)?;
                 if protocol_fee > 0 {
                     let rates = self.get_scaled_rates()?;
                     let mut protocol_deposit_amounts = vec![0u128; self.pool.tokens.len()];
@@ -348,7 +724,7 @@ pub mod stable_pool {
                         &reserves,
                         self.psp22.total_supply(),
                         None, // no fees
-                        self.amp_coef()?,
+                        self.amp_coef_precise()?,
                     )?;
                     // mint fee (shares) to protocol
                     let events = self.psp22.mint(fee_to, protocol_fee_lp)?;
@@ -358,6 +734,35 @@ pub mod stable_pool {
             Ok(())
         }
 
+        /// Updates the imbalance EMA of the automatic amplification coefficient adjustment (if enabled)
+        /// and ramps the amplification coefficient if needed.
+        ///
+        /// NOTE: Should be called before the reserves change, except for withdrawals by shares,
+        /// which do not use rates.
+        fn update_auto_amp(&mut self) -> Result<(), StablePoolError> {
+            let Some(mut auto_amp) = self.pool.auto_amp else {
+                return Ok(());
+            };
+            let rates = self.get_scaled_rates()?;
+            let imbalance = math::rated_imbalance(&rates, &self.pool.reserves)?;
+            let init_amp_coef = self.amp_coef()?;
+            let now = self.env().block_timestamp();
+            if let Some((future_amp_coef, future_time)) =
+                auto_amp.update(&mut self.pool.amp_coef, imbalance, now)?
+            {
+                self.env().emit_event(AmpCoefChange {
+                    init_amp_coef,
+                    future_amp_coef,
+                    init_time: now,
+                    future_time,
+                    waypoints: Vec::new(),
+                    ramp_shape: RampShape::Linear,
+                });
+            }
+            self.pool.auto_amp = Some(auto_amp);
+            Ok(())
+        }
+
         fn decrease_reserve(
             &mut self,
             token_id: usize,
@@ -384,13 +789,16 @@ pub mod stable_pool {
             &mut self,
             token_in: AccountId,
             token_out: AccountId,
-            token_in_amount: Option<u128>,
+            token_in_amount: Deposit<u128>,
             min_token_out_amount: u128,
             to: AccountId,
+            to_native: bool,
         ) -> Result<(u128, u128), StablePoolError> {
             //check token ids
             let (token_in_id, token_out_id) = self.check_tokens(token_in, token_out)?;
 
+            self.update_auto_amp()?;
+
             // get transfered token_in amount
             let token_in_amount = self._transfer_in(token_in_id, token_in_amount)?;
 
@@ -404,14 +812,18 @@ pub mod stable_pool {
                 token_in_amount,
                 token_out_id,
                 &self.reserves(),
-                &self.pool.fees,
-                self.amp_coef()?,
+                &self.current_fees(),
+                self.amp_coef_precise()?,
             )?;
 
             // Check if swapped amount is not less than min_token_out_amount
             ensure!(
                 token_out_amount >= min_token_out_amount,
-                StablePoolError::InsufficientOutputAmount
+                StablePoolError::OutputAmountBelowMin {
+                    token: token_out,
+                    expected: min_token_out_amount,
+                    actual: token_out_amount,
+                }
             );
             // update reserves
             self.increase_reserve(token_in_id, token_in_amount)?;
@@ -421,8 +833,7 @@ pub mod stable_pool {
             self.mint_protocol_fee(fee, token_out_id)?;
 
             // transfer token_out
-            self.token_by_address(token_out)
-                .transfer(to, token_out_amount, vec![])?;
+            self._transfer_out(token_out_id, token_out_amount, to, to_native)?;
 
             self.env().emit_event(Swap {
                 sender: self.env().caller(),
@@ -454,6 +865,8 @@ pub mod stable_pool {
                 StablePoolError::InsufficientOutputAmount
             );
 
+            self.update_auto_amp()?;
+
             // Make sure rates are up to date before we attempt any calculations
             let rates = self.get_scaled_rates()?;
 
@@ -464,33 +877,49 @@ pub mod stable_pool {
                 token_out_amount,
                 token_out_id,
                 &self.reserves(),
-                &self.pool.fees,
-                self.amp_coef()?,
+                &self.current_fees(),
+                self.amp_coef_precise()?,
             )?;
 
+            // Account for the transfer fee of token_in (if any)
+            let token_in_gross_amount = self.gross_of_transfer_fee(token_in_id, token_in_amount)?;
+
             // Check if in token_in_amount is as constrained by the user
             ensure!(
-                token_in_amount <= max_token_in_amount,
-                StablePoolError::InsufficientInputAmount
+                token_in_gross_amount <= max_token_in_amount,
+                StablePoolError::InputAmountAboveMax {
+                    token: token_in,
+                    expected: max_token_in_amount,
+                    actual: token_in_gross_amount,
+                }
+            );
+
+            // transfer token_in
+            let token_in_credited =
+                self._transfer_in(token_in_id, Deposit::FromCaller(token_in_gross_amount))?;
+            ensure!(
+                token_in_credited >= token_in_amount,
+                StablePoolError::InputAmountCreditedBelowExpected {
+                    token: token_in,
+                    expected: token_in_amount,
+                    actual: token_in_credited,
+                }
             );
+
             // update reserves
-            self.increase_reserve(token_in_id, token_in_amount)?;
+            self.increase_reserve(token_in_id, token_in_credited)?;
             self.decrease_reserve(token_out_id, token_out_amount)?;
 
             // mint protocol fee
             self.mint_protocol_fee(fee, token_out_id)?;
 
-            // transfer token_in
-            _ = self._transfer_in(token_in_id, Some(token_in_amount))?;
-
             // transfer token_out
-            self.token_by_address(token_out)
-                .transfer(to, token_out_amount, vec![])?;
+            self.transfer_token(token_out_id, to, token_out_amount)?;
 
             self.env().emit_event(Swap {
                 sender: self.env().caller(),
                 token_in,
-                amount_in: token_in_amount,
+                amount_in: token_in_credited,
                 token_out,
                 amount_out: token_out_amount,
                 to,
@@ -499,129 +928,131 @@ pub mod stable_pool {
                 reserves: self.reserves(),
             });
             // note that fee is applied to token_out (same as in _swap_exact_in)
-            Ok((token_in_amount, fee))
+            Ok((token_in_gross_amount, fee))
         }
 
         /// Handles PSP22 token transfer,
         ///
-        /// If `amount` is `Some(amount)`, transfer this amount of `token_id`
-        /// from the caller to this contract.
+        /// If `amount` is `FromCaller(amount)`, transfer this amount of `token_id`
+        /// from the caller to this contract. For tokens charging a fee on transfer,
+        /// the returned amount is the one actually credited to this contract.
         ///
-        /// If `amount` of `None`, calculate the difference between
+        /// If `amount` is `Held(amount)`, the amount is already held by this contract.
+        ///
+        /// If `amount` is `Received`, calculate the difference between
         /// this contract balance and recorded reserve of `token_id`.
         fn _transfer_in(
             &self,
             token_id: usize,
-            amount: Option<u128>,
+            amount: Deposit<u128>,
         ) -> Result<u128, StablePoolError> {
-            let mut token = self.token_by_id(token_id);
-            let amount = if let Some(token_amount) = amount {
-                token.transfer_from(
-                    self.env().caller(),
-                    self.env().account_id(),
-                    token_amount,
-                    vec![],
-                )?;
-                token_amount
-            } else {
-                token
+            let amount = match amount {
+                Deposit::FromCaller(token_amount) => {
+                    self.transfer_token_from_caller(token_id, token_amount)?
+                }
+                Deposit::Held(token_amount) => token_amount,
+                Deposit::Received => self
+                    .token_by_id(token_id)
                     .balance_of(self.env().account_id())
                     .checked_sub(self.pool.reserves[token_id])
-                    .ok_or(MathError::SubUnderflow(103))?
+                    .ok_or(MathError::SubUnderflow(103))?,
             };
             ensure!(amount > 0, StablePoolError::InsufficientInputAmount);
             Ok(amount)
         }
-    }
 
-    impl StablePool for StablePoolContract {
-        #[ink(message)]
-        fn add_liquidity(
-            &mut self,
-            min_share_amount: u128,
-            amounts: Vec<u128>,
+        /// Handles token transfer from this contract to `to`.
+        ///
+        /// If `to_native` is `true` and `token_id` is wAZERO,
+        /// unwrap `amount` of wAZERO and transfer it as the native token.
+        fn _transfer_out(
+            &self,
+            token_id: usize,
+            amount: u128,
             to: AccountId,
-        ) -> Result<(u128, u128), StablePoolError> {
-            ensure!(
-                amounts.len() == self.pool.tokens.len(),
-                StablePoolError::IncorrectAmountsCount
-            );
-            // Check that at least one amount is non-zero
-            ensure!(
-                amounts.iter().any(|&amount| amount > 0),
-                StablePoolError::ZeroAmounts
-            );
-
-            // Make sure rates are up to date before we attempt any calculations
-            let rates = self.get_scaled_rates()?;
-
-            // calc lp tokens (shares_to_mint, fee)
-            let (shares, fee_part) = math::rated_compute_lp_amount_for_deposit(
-                &rates,
-                &amounts,
-                &self.reserves(),
-                self.psp22.total_supply(),
-                Some(&self.pool.fees),
-                self.amp_coef()?,
-            )?;
-
-            // Check min shares
-            ensure!(
-                shares >= min_share_amount,
-                StablePoolError::InsufficientLiquidityMinted
-            );
-
-            // transfer amounts
-            for (id, &token) in self.pool.tokens.iter().enumerate() {
-                if amounts[id] > 0 {
-                    self.token_by_address(token).transfer_from(
-                        self.env().caller(),
-                        self.env().account_id(),
-                        amounts[id],
-                        vec![],
-                    )?;
-                }
+            to_native: bool,
+        ) -> Result<(), StablePoolError> {
+            if to_native && Some(self.pool.tokens[token_id]) == self.pool.wazero {
+                let mut wazero: contract_ref!(WrappedAZERO) = self.pool.tokens[token_id].into();
+                wazero.withdraw(amount)?;
+                self.env()
+                    .transfer(to, amount)
+                    .map_err(|_| StablePoolError::NativeTransferFailed)?;
+            } else {
+                self.transfer_token(token_id, to, amount)?;
             }
+            Ok(())
+        }
 
-            // mint shares
-            let events = self.psp22.mint(to, shares)?;
-            self.emit_events(events);
+        /// Transfers `amount` of `token_id` from this contract to `to`.
+        fn transfer_token(
+            &self,
+            token_id: usize,
+            to: AccountId,
+            amount: u128,
+        ) -> Result<(), StablePoolError> {
+            self.token_by_id(token_id)
+                .call_mut()
+                .transfer(to, amount, vec![])
+                .invoke()?;
+            Ok(())
+        }
 
-            // mint protocol fee
-            if let Some(fee_to) = self.fee_receiver() {
-                let protocol_fee = self.pool.fees.protocol_trade_fee(fee_part)?;
-                if protocol_fee > 0 {
-                    let events = self.psp22.mint(fee_to, protocol_fee)?;
-                    self.emit_events(events);
-                }
+        /// Transfers `amount` of `token_id` from the caller to this contract.
+        ///
+        /// Returns the amount credited to this contract. For tokens charging a fee on transfer
+        /// it is measured by the difference of this contract balance before and after the transfer.
+        fn transfer_token_from_caller(
+            &self,
+            token_id: usize,
+            amount: u128,
+        ) -> Result<u128, StablePoolError> {
+            let mut token = self.token_by_id(token_id);
+            let this = self.env().account_id();
+            let balance_before = self.pool.transfer_fees[token_id].map(|_| token.balance_of(this));
+            token
+                .call_mut()
+                .transfer_from(self.env().caller(), this, amount, vec![])
+                .invoke()?;
+            match balance_before {
+                Some(balance_before) => Ok(token
+                    .balance_of(this)
+                    .checked_sub(balance_before)
+                    .ok_or(MathError::SubUnderflow(105))?),
+                None => Ok(amount),
             }
+        }
 
-            // update reserves
-            for (i, &amount) in amounts.iter().enumerate() {
-                self.increase_reserve(i, amount)?;
-            }
+        /// Returns id of the wAZERO token.
+        fn wazero_id(&self) -> Result<usize, StablePoolError> {
+            let wazero = self
+                .pool
+                .wazero
+                .ok_or(StablePoolError::NativeTokenNotSupported)?;
+            self.token_id(wazero)
+        }
 
-            self.env().emit_event(AddLiquidity {
-                provider: self.env().caller(),
-                token_amounts: amounts,
-                shares,
-                to,
-            });
-            self.env().emit_event(Sync {
-                reserves: self.reserves(),
-            });
-            Ok((shares, fee_part))
+        /// Wraps `amount` of the native token transferred to this contract into wAZERO.
+        fn wrap_native(&self, amount: u128) -> Result<(), StablePoolError> {
+            let mut wazero: contract_ref!(WrappedAZERO) =
+                self.pool.tokens[self.wazero_id()?].into();
+            wazero
+                .call_mut()
+                .deposit()
+                .transferred_value(amount)
+                .invoke()?;
+            Ok(())
         }
 
-        // Note that this method does not require to update rates, neither it uses rates.
-        // Thus it's always possible to call it, even if the rate is outdated, or the rate provider is down.
-        #[ink(message)]
-        fn remove_liquidity_by_shares(
+        fn _remove_liquidity_by_shares(
             &mut self,
             shares: u128,
             min_amounts: Vec<u128>,
             to: AccountId,
+            to_native: bool,
         ) -> Result<Vec<u128>, StablePoolError> {
+            // auto amp is not updated, as it requires rates: a proportional withdrawal
+            // does not change the imbalance, which the next update accounts for
             let amounts = math::compute_amounts_given_lp(
                 shares,
                 &self.reserves(),
@@ -629,13 +1060,18 @@ pub mod stable_pool {
             )?;
 
             // Check if enough tokens are withdrawn
-            ensure!(
-                amounts
-                    .iter()
-                    .zip(min_amounts.iter())
-                    .all(|(amount, min_amount)| amount >= min_amount),
-                StablePoolError::InsufficientOutputAmount
-            );
+            if let Some((token_id, (&amount, &min_amount))) = amounts
+                .iter()
+                .zip(min_amounts.iter())
+                .enumerate()
+                .find(|(_, (amount, min_amount))| amount < min_amount)
+            {
+                return Err(StablePoolError::OutputAmountBelowMin {
+                    token: self.pool.tokens[token_id],
+                    expected: min_amount,
+                    actual: amount,
+                });
+            }
             // Check that at least one amount is non-zero
             ensure!(
                 amounts.iter().any(|&amount| amount > 0),
@@ -643,9 +1079,9 @@ pub mod stable_pool {
             );
 
             // transfer tokens
-            for (&token, &amount) in self.pool.tokens.iter().zip(amounts.iter()) {
+            for (id, &amount) in amounts.iter().enumerate() {
                 if amount > 0 {
-                    self.token_by_address(token).transfer(to, amount, vec![])?;
+                    self._transfer_out(id, amount, to, to_native)?;
                 }
             }
 
@@ -670,72 +1106,328 @@ pub mod stable_pool {
             Ok(amounts)
         }
 
-        #[ink(message)]
-        fn remove_liquidity_by_amounts(
+        fn _add_liquidity(
             &mut self,
-            max_share_amount: u128,
-            amounts: Vec<u128>,
+            min_share_amount: u128,
+            amounts: Deposit<Vec<u128>>,
             to: AccountId,
         ) -> Result<(u128, u128), StablePoolError> {
-            ensure!(
-                amounts.len() == self.pool.tokens.len(),
-                StablePoolError::IncorrectAmountsCount
-            );
+            self.update_auto_amp()?;
+            // get deposited amounts
+            let amounts = match amounts {
+                Deposit::FromCaller(amounts) => {
+                    ensure!(
+                        amounts.len() == self.pool.tokens.len(),
+                        StablePoolError::IncorrectAmountsCount
+                    );
+                    // transfer amounts, measuring the credited amounts
+                    let mut credited = Vec::with_capacity(amounts.len());
+                    for (id, &amount) in amounts.iter().enumerate() {
+                        credited.push(if amount > 0 {
+                            self.transfer_token_from_caller(id, amount)?
+                        } else {
+                            0
+                        });
+                    }
+                    credited
+                }
+                Deposit::Held(amounts) => amounts,
+                Deposit::Received => self.received_amounts()?,
+            };
             // Check that at least one amount is non-zero
             ensure!(
                 amounts.iter().any(|&amount| amount > 0),
                 StablePoolError::ZeroAmounts
             );
 
+            // Make sure rates are up to date before we attempt any calculations
             let rates = self.get_scaled_rates()?;
 
-            // calc comparable amounts
-            let (shares_to_burn, fee_part) = math::rated_compute_lp_amount_for_withdraw(
+            // calc lp tokens (shares_to_mint, fee)
+            let (shares, fee_part) = math::rated_compute_lp_amount_for_deposit(
                 &rates,
                 &amounts,
                 &self.reserves(),
                 self.psp22.total_supply(),
-                Some(&self.pool.fees),
-                self.amp_coef()?,
+                Some(&self.current_fees()),
+                self.amp_coef_precise()?,
             )?;
 
-            // check max shares
+            // lock minimum liquidity on the first deposit
+            let locked = self.locked_liquidity(shares)?;
+            if locked > 0 {
+                let events = self.psp22.mint(DEAD_ADDRESS.into(), locked)?;
+                self.emit_events(events);
+            }
+            let shares = shares - locked;
+
+            // Check min shares
             ensure!(
-                shares_to_burn <= max_share_amount,
-                StablePoolError::InsufficientLiquidityBurned
+                shares >= min_share_amount,
+                StablePoolError::LiquidityMintedBelowMin {
+                    expected: min_share_amount,
+                    actual: shares,
+                }
             );
-            // burn shares
-            let events = self.psp22.burn(self.env().caller(), shares_to_burn)?;
+
+            // mint shares
+            let events = self.psp22.mint(to, shares)?;
             self.emit_events(events);
+
             // mint protocol fee
             if let Some(fee_to) = self.fee_receiver() {
-                let protocol_fee = self.pool.fees.protocol_trade_fee(fee_part)?;
+                let protocol_fee = self.current_fees().protocol_trade_fee(fee_part)?;
                 if protocol_fee > 0 {
                     let events = self.psp22.mint(fee_to, protocol_fee)?;
                     self.emit_events(events);
                 }
             }
-            // transfer tokens
-            for (&token, &amount) in self.pool.tokens.iter().zip(amounts.iter()) {
-                if amount > 0 {
-                    self.token_by_address(token).transfer(to, amount, vec![])?;
-                }
-            }
+
             // update reserves
             for (i, &amount) in amounts.iter().enumerate() {
-                self.decrease_reserve(i, amount)?;
+                self.increase_reserve(i, amount)?;
             }
 
-            self.env().emit_event(RemoveLiquidity {
+            self.env().emit_event(AddLiquidity {
                 provider: self.env().caller(),
                 token_amounts: amounts,
-                shares: shares_to_burn,
+                shares,
                 to,
             });
             self.env().emit_event(Sync {
                 reserves: self.reserves(),
             });
-            Ok((shares_to_burn, fee_part))
+            Ok((shares, fee_part))
+        }
+
+        /// Returns the amount of `shares` to be locked forever.
+        ///
+        /// On the first deposit it is `MINIMUM_LIQUIDITY`, provided the initial invariant
+        /// (equal to `shares`) is at least `MIN_INITIAL_D`. Zero on subsequent deposits.
+        fn locked_liquidity(&self, shares: u128) -> Result<u128, StablePoolError> {
+            if self.psp22.total_supply() > 0 {
+                return Ok(0);
+            }
+            ensure!(
+                shares >= MIN_INITIAL_D,
+                StablePoolError::InsufficientInitialLiquidity
+            );
+            Ok(MINIMUM_LIQUIDITY)
+        }
+
+        /// Calculates the difference between this contract balance
+        /// and recorded reserve for each token.
+        fn received_amounts(&self) -> Result<Vec<u128>, StablePoolError> {
+            let mut amounts = Vec::with_capacity(self.pool.tokens.len());
+            for (&token, &reserve) in self.pool.tokens.iter().zip(self.pool.reserves.iter()) {
+                amounts.push(
+                    self.token_by_address(token)
+                        .balance_of(self.env().account_id())
+                        .checked_sub(reserve)
+                        .ok_or(MathError::SubUnderflow(104))?,
+                );
+            }
+            Ok(amounts)
+        }
+
+        /// Implements `get_swap_amount_out` and `quote_swap_amount_out` given scaled `rates`.
+        fn swap_amount_out(
+            &self,
+            rates: &[ScaledRate],
+            token_in: AccountId,
+            token_out: AccountId,
+            token_in_amount: u128,
+        ) -> Result<(u128, u128), StablePoolError> {
+            let (token_in_id, token_out_id) = self.check_tokens(token_in, token_out)?;
+            let token_in_amount = self.net_of_transfer_fee(token_in_id, token_in_amount)?;
+            Ok(math::rated_swap_to(
+                rates,
+                token_in_id,
+                token_in_amount,
+                token_out_id,
+                &self.reserves(),
+                &self.current_fees(),
+                self.amp_coef_precise()?,
+            )?)
+        }
+
+        /// Implements `get_swap_amount_in` and `quote_swap_amount_in` given scaled `rates`.
+        fn swap_amount_in(
+            &self,
+            rates: &[ScaledRate],
+            token_in: AccountId,
+            token_out: AccountId,
+            token_out_amount: u128,
+        ) -> Result<(u128, u128), StablePoolError> {
+            let (token_in_id, token_out_id) = self.check_tokens(token_in, token_out)?;
+            let (token_in_amount, fee) = math::rated_swap_from(
+                rates,
+                token_in_id,
+                token_out_amount,
+                token_out_id,
+                &self.reserves(),
+                &self.current_fees(),
+                self.amp_coef_precise()?,
+            )?;
+            Ok((
+                self.gross_of_transfer_fee(token_in_id, token_in_amount)?,
+                fee,
+            ))
+        }
+
+        /// Implements `get_spot_price` and `quote_spot_price` given scaled `rates`.
+        fn spot_price(
+            &self,
+            rates: &[ScaledRate],
+            token_in: AccountId,
+            token_out: AccountId,
+        ) -> Result<u128, StablePoolError> {
+            let (token_in_id, token_out_id) = self.check_tokens(token_in, token_out)?;
+            Ok(math::rated_spot_price(
+                rates,
+                token_in_id,
+                token_out_id,
+                &self.reserves(),
+                self.amp_coef_precise()?,
+            )?)
+        }
+
+        /// Implements `get_mint_liquidity_for_amounts` and `quote_mint_liquidity_for_amounts`
+        /// given scaled `rates`.
+        fn mint_liquidity_for_amounts(
+            &self,
+            rates: &[ScaledRate],
+            amounts: Vec<u128>,
+        ) -> Result<(u128, u128), StablePoolError> {
+            ensure!(
+                amounts.len() == self.pool.tokens.len(),
+                StablePoolError::IncorrectAmountsCount
+            );
+            let amounts = amounts
+                .iter()
+                .enumerate()
+                .map(|(id, &amount)| self.net_of_transfer_fee(id, amount))
+                .collect::<Result<Vec<u128>, MathError>>()?;
+            let (shares, fee_part) = math::rated_compute_lp_amount_for_deposit(
+                rates,
+                &amounts,
+                &self.reserves(),
+                self.psp22.total_supply(),
+                Some(&self.current_fees()),
+                self.amp_coef_precise()?,
+            )?;
+            Ok((shares - self.locked_liquidity(shares)?, fee_part))
+        }
+    }
+
+    impl StablePoolActions for StablePoolContract {
+        #[ink(message)]
+        fn add_liquidity(
+            &mut self,
+            min_share_amount: u128,
+            amounts: Vec<u128>,
+            to: AccountId,
+        ) -> Result<(u128, u128), StablePoolError> {
+            self.non_reentrant(|this| {
+                this._add_liquidity(min_share_amount, Deposit::FromCaller(amounts), to)
+            })
+        }
+
+        #[ink(message)]
+        fn add_liquidity_received(
+            &mut self,
+            min_share_amount: u128,
+            to: AccountId,
+        ) -> Result<(u128, u128), StablePoolError> {
+            self.non_reentrant(|this| this._add_liquidity(min_share_amount, Deposit::Received, to))
+        }
+
+        // Note that this method does not require to update rates, neither it uses rates.
+        // Thus it's always possible to call it, even if the rate is outdated, or the rate provider is down.
+        #[ink(message)]
+        fn remove_liquidity_by_shares(
+            &mut self,
+            shares: u128,
+            min_amounts: Vec<u128>,
+            to: AccountId,
+        ) -> Result<Vec<u128>, StablePoolError> {
+            self.non_reentrant(|this| {
+                this._remove_liquidity_by_shares(shares, min_amounts, to, false)
+            })
+        }
+
+        #[ink(message)]
+        fn remove_liquidity_by_amounts(
+            &mut self,
+            max_share_amount: u128,
+            amounts: Vec<u128>,
+            to: AccountId,
+        ) -> Result<(u128, u128), StablePoolError> {
+            self.non_reentrant(|this| {
+                ensure!(
+                    amounts.len() == this.pool.tokens.len(),
+                    StablePoolError::IncorrectAmountsCount
+                );
+                // Check that at least one amount is non-zero
+                ensure!(
+                    amounts.iter().any(|&amount| amount > 0),
+                    StablePoolError::ZeroAmounts
+                );
+
+                this.update_auto_amp()?;
+                let rates = this.get_scaled_rates()?;
+
+                // calc comparable amounts
+                let (shares_to_burn, fee_part) = math::rated_compute_lp_amount_for_withdraw(
+                    &rates,
+                    &amounts,
+                    &this.reserves(),
+                    this.psp22.total_supply(),
+                    Some(&this.current_fees()),
+                    this.amp_coef_precise()?,
+                )?;
+
+                // check max shares
+                ensure!(
+                    shares_to_burn <= max_share_amount,
+                    StablePoolError::LiquidityBurnedAboveMax {
+                        expected: max_share_amount,
+                        actual: shares_to_burn,
+                    }
+                );
+                // burn shares
+                let events = this.psp22.burn(this.env().caller(), shares_to_burn)?;
+                this.emit_events(events);
+                // mint protocol fee
+                if let Some(fee_to) = this.fee_receiver() {
+                    let protocol_fee = this.current_fees().protocol_trade_fee(fee_part)?;
+                    if protocol_fee > 0 {
+                        let events = this.psp22.mint(fee_to, protocol_fee)?;
+                        this.emit_events(events);
+                    }
+                }
+                // transfer tokens
+                for (id, &amount) in amounts.iter().enumerate() {
+                    if amount > 0 {
+                        this.transfer_token(id, to, amount)?;
+                    }
+                }
+                // update reserves
+                for (i, &amount) in amounts.iter().enumerate() {
+                    this.decrease_reserve(i, amount)?;
+                }
+
+                this.env().emit_event(RemoveLiquidity {
+                    provider: this.env().caller(),
+                    token_amounts: amounts,
+                    shares: shares_to_burn,
+                    to,
+                });
+                this.env().emit_event(Sync {
+                    reserves: this.reserves(),
+                });
+                Ok((shares_to_burn, fee_part))
+            })
         }
 
         #[ink(message)]
@@ -747,13 +1439,16 @@ pub mod stable_pool {
             min_token_out_amount: u128,
             to: AccountId,
         ) -> Result<(u128, u128), StablePoolError> {
-            self._swap_exact_in(
-                token_in,
-                token_out,
-                Some(token_in_amount),
-                min_token_out_amount,
-                to,
-            )
+            self.non_reentrant(|this| {
+                this._swap_exact_in(
+                    token_in,
+                    token_out,
+                    Deposit::FromCaller(token_in_amount),
+                    min_token_out_amount,
+                    to,
+                    false,
+                )
+            })
         }
 
         #[ink(message)]
@@ -765,13 +1460,15 @@ pub mod stable_pool {
             max_token_in_amount: u128,
             to: AccountId,
         ) -> Result<(u128, u128), StablePoolError> {
-            self._swap_exact_out(
-                token_in,
-                token_out,
-                token_out_amount,
-                max_token_in_amount,
-                to,
-            )
+            self.non_reentrant(|this| {
+                this._swap_exact_out(
+                    token_in,
+                    token_out,
+                    token_out_amount,
+                    max_token_in_amount,
+                    to,
+                )
+            })
         }
 
         #[ink(message)]
@@ -782,15 +1479,123 @@ pub mod stable_pool {
             min_token_out_amount: u128,
             to: AccountId,
         ) -> Result<(u128, u128), StablePoolError> {
-            self._swap_exact_in(token_in, token_out, None, min_token_out_amount, to)
+            self.non_reentrant(|this| {
+                this._swap_exact_in(
+                    token_in,
+                    token_out,
+                    Deposit::Received,
+                    min_token_out_amount,
+                    to,
+                    false,
+                )
+            })
+        }
+
+        #[ink(message, payable)]
+        fn swap_exact_in_native(
+            &mut self,
+            token_out: AccountId,
+            min_token_out_amount: u128,
+            to: AccountId,
+        ) -> Result<(u128, u128), StablePoolError> {
+            self.non_reentrant(|this| {
+                let token_in = this.pool.tokens[this.wazero_id()?];
+                let token_in_amount = this.env().transferred_value();
+                ensure!(
+                    token_in_amount > 0,
+                    StablePoolError::InsufficientInputAmount
+                );
+                this.wrap_native(token_in_amount)?;
+                // credit only the wrapped amount, not any excess balance of this contract
+                this._swap_exact_in(
+                    token_in,
+                    token_out,
+                    Deposit::Held(token_in_amount),
+                    min_token_out_amount,
+                    to,
+                    false,
+                )
+            })
+        }
+
+        #[ink(message)]
+        fn swap_exact_in_to_native(
+            &mut self,
+            token_in: AccountId,
+            token_in_amount: u128,
+            min_token_out_amount: u128,
+            to: AccountId,
+        ) -> Result<(u128, u128), StablePoolError> {
+            self.non_reentrant(|this| {
+                let token_out = this.pool.tokens[this.wazero_id()?];
+                this._swap_exact_in(
+                    token_in,
+                    token_out,
+                    Deposit::FromCaller(token_in_amount),
+                    min_token_out_amount,
+                    to,
+                    true,
+                )
+            })
+        }
+
+        #[ink(message, payable)]
+        fn add_liquidity_native(
+            &mut self,
+            min_share_amount: u128,
+            amounts: Vec<u128>,
+            to: AccountId,
+        ) -> Result<(u128, u128), StablePoolError> {
+            self.non_reentrant(|this| {
+                ensure!(
+                    amounts.len() == this.pool.tokens.len(),
+                    StablePoolError::IncorrectAmountsCount
+                );
+                let wazero_id = this.wazero_id()?;
+                ensure!(
+                    amounts[wazero_id] == this.env().transferred_value(),
+                    StablePoolError::IncorrectNativeAmount
+                );
+                // transfer amounts, measuring the credited amounts
+                let mut credited = Vec::with_capacity(amounts.len());
+                for (id, &amount) in amounts.iter().enumerate() {
+                    credited.push(if id != wazero_id && amount > 0 {
+                        this.transfer_token_from_caller(id, amount)?
+                    } else {
+                        amount
+                    });
+                }
+                if amounts[wazero_id] > 0 {
+                    this.wrap_native(amounts[wazero_id])?;
+                }
+                // deposit only the transferred and wrapped amounts,
+                // not any excess balance of this contract
+                this._add_liquidity(min_share_amount, Deposit::Held(credited), to)
+            })
         }
 
+        #[ink(message)]
+        fn remove_liquidity_by_shares_to_native(
+            &mut self,
+            shares: u128,
+            min_amounts: Vec<u128>,
+            to: AccountId,
+        ) -> Result<Vec<u128>, StablePoolError> {
+            self.non_reentrant(|this| {
+                _ = this.wazero_id()?;
+                this._remove_liquidity_by_shares(shares, min_amounts, to, true)
+            })
+        }
+    }
+
+    impl StablePoolAdmin for StablePoolContract {
         #[ink(message)]
         fn set_fee_receiver(
             &mut self,
             fee_receiver: Option<AccountId>,
         ) -> Result<(), StablePoolError> {
             self.ensure_owner()?;
+            self.ensure_unlocked()?;
             self.pool.fee_receiver = fee_receiver;
             self.env().emit_event(FeeReceiverChanged {
                 new_fee_receiver: fee_receiver,
@@ -798,11 +1603,25 @@ pub mod stable_pool {
             Ok(())
         }
 
+        #[ink(message)]
+        fn set_wazero(&mut self, wazero: Option<AccountId>) -> Result<(), StablePoolError> {
+            self.ensure_owner()?;
+            self.ensure_unlocked()?;
+            if let Some(token) = wazero {
+                _ = self.token_id(token)?;
+            }
+            self.pool.wazero = wazero;
+            self.env().emit_event(WazeroChanged { new_wazero: wazero });
+            Ok(())
+        }
+
         #[ink(message)]
         fn set_fees(&mut self, trade_fee: u32, protocol_fee: u32) -> Result<(), StablePoolError> {
             self.ensure_owner()?;
-            self.pool.fees =
-                Fees::new(trade_fee, protocol_fee).ok_or(StablePoolError::InvalidFee)?;
+            self.ensure_unlocked()?;
+            self.pool
+                .fees
+                .set_fees(Fees::new(trade_fee, protocol_fee).ok_or(StablePoolError::InvalidFee)?);
             self.env().emit_event(FeeChanged {
                 trade_fee,
                 protocol_fee,
@@ -810,37 +1629,160 @@ pub mod stable_pool {
             Ok(())
         }
 
+        #[ink(message)]
+        fn ramp_fees(
+            &mut self,
+            future_trade_fee: u32,
+            future_protocol_fee: u32,
+            future_time: u64,
+        ) -> Result<(), StablePoolError> {
+            self.ensure_owner()?;
+            self.ensure_unlocked()?;
+            let init_fees = self.current_fees();
+            self.pool.fees.ramp_fees(
+                future_trade_fee,
+                future_protocol_fee,
+                future_time,
+                self.env().block_timestamp(),
+            )?;
+            self.env().emit_event(FeeRamp {
+                init_trade_fee: init_fees.trade_fee,
+                init_protocol_fee: init_fees.protocol_fee,
+                future_trade_fee,
+                future_protocol_fee,
+                init_time: self.env().block_timestamp(),
+                future_time,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn stop_ramp_fees(&mut self) -> Result<(), StablePoolError> {
+            self.ensure_owner()?;
+            self.ensure_unlocked()?;
+            self.pool.fees.stop_ramp_fees(self.env().block_timestamp());
+            let fees = self.current_fees();
+            self.env().emit_event(FeeRampStop {
+                trade_fee: fees.trade_fee,
+                protocol_fee: fees.protocol_fee,
+                time: self.env().block_timestamp(),
+            });
+            Ok(())
+        }
+
         #[ink(message)]
         fn ramp_amp_coef(
             &mut self,
             future_amp_coef: u128,
             future_time: u64,
+        ) -> Result<(), StablePoolError> {
+            self.ramp_amp_coef_schedule(vec![(future_amp_coef, future_time)], RampShape::Linear)
+        }
+
+        #[ink(message)]
+        fn ramp_amp_coef_schedule(
+            &mut self,
+            waypoints: Vec<(u128, u64)>,
+            ramp_shape: RampShape,
         ) -> Result<(), StablePoolError> {
             self.ensure_owner()?;
+            self.ensure_unlocked()?;
             let init_amp_coef = self.amp_coef()?;
-            self.pool
-                .amp_coef
-                .ramp_amp_coef(future_amp_coef, future_time)?;
+            self.pool.amp_coef.ramp_amp_coef_schedule(
+                waypoints.clone(),
+                ramp_shape,
+                self.env().block_timestamp(),
+            )?;
+            let mut waypoints = waypoints;
+            // validated to be non-empty
+            let (future_amp_coef, future_time) = waypoints.pop().unwrap_or_default();
             self.env().emit_event(AmpCoefChange {
                 init_amp_coef,
                 future_amp_coef,
                 init_time: self.env().block_timestamp(),
                 future_time,
+                waypoints,
+                ramp_shape,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn ramp_amp_coef_precise(
+            &mut self,
+            future_amp_coef: u128,
+            future_time: u64,
+        ) -> Result<(), StablePoolError> {
+            self.ramp_amp_coef_schedule_precise(
+                vec![(future_amp_coef, future_time)],
+                RampShape::Linear,
+            )
+        }
+
+        #[ink(message)]
+        fn ramp_amp_coef_schedule_precise(
+            &mut self,
+            waypoints: Vec<(u128, u64)>,
+            ramp_shape: RampShape,
+        ) -> Result<(), StablePoolError> {
+            self.ensure_owner()?;
+            self.ensure_unlocked()?;
+            let init_amp_coef = self.amp_coef_precise()?;
+            self.pool.amp_coef.ramp_amp_coef_schedule_precise(
+                waypoints.clone(),
+                ramp_shape,
+                self.env().block_timestamp(),
+            )?;
+            let mut waypoints = waypoints;
+            // validated to be non-empty
+            let (future_amp_coef, future_time) = waypoints.pop().unwrap_or_default();
+            self.env().emit_event(AmpCoefChangePrecise {
+                init_amp_coef,
+                future_amp_coef,
+                init_time: self.env().block_timestamp(),
+                future_time,
+                waypoints,
+                ramp_shape,
             });
             Ok(())
         }
 
+        #[ink(message)]
+        fn set_auto_amp(&mut self, config: Option<AutoAmpConfig>) -> Result<(), StablePoolError> {
+            self.ensure_owner()?;
+            self.ensure_unlocked()?;
+            self.pool.auto_amp = match config {
+                Some(config) => {
+                    let rates = self.get_scaled_rates()?;
+                    let imbalance = math::rated_imbalance(&rates, &self.pool.reserves)?;
+                    Some(AutoAmp::new(
+                        config,
+                        imbalance,
+                        self.env().block_timestamp(),
+                    )?)
+                }
+                None => None,
+            };
+            self.env().emit_event(AutoAmpChanged { config });
+            Ok(())
+        }
+
         #[ink(message)]
         fn stop_ramp_amp_coef(&mut self) -> Result<(), StablePoolError> {
             self.ensure_owner()?;
-            self.pool.amp_coef.stop_ramp_amp_coef()?;
+            self.ensure_unlocked()?;
+            self.pool
+                .amp_coef
+                .stop_ramp_amp_coef(self.env().block_timestamp())?;
             self.env().emit_event(AmpCoefChangeStop {
                 amp_coef: self.amp_coef()?,
                 time: self.env().block_timestamp(),
             });
             Ok(())
         }
+    }
 
+    impl StablePoolView for StablePoolContract {
         #[ink(message)]
         fn tokens(&self) -> Vec<AccountId> {
             self.pool.tokens.clone()
@@ -851,19 +1793,60 @@ pub mod stable_pool {
             self.pool.reserves.clone()
         }
 
+        #[ink(message)]
+        fn tokens_decimals(&self) -> Vec<u8> {
+            self.pool.tokens_decimals.clone()
+        }
+
         #[ink(message)]
         fn amp_coef(&self) -> Result<u128, StablePoolError> {
-            Ok(self.pool.amp_coef.compute_amp_coef()?)
+            Ok(self
+                .pool
+                .amp_coef
+                .compute_amp_coef(self.env().block_timestamp())?)
+        }
+
+        #[ink(message)]
+        fn amp_coef_precise(&self) -> Result<u128, StablePoolError> {
+            Ok(self
+                .pool
+                .amp_coef
+                .compute_amp_coef_precise(self.env().block_timestamp())?)
         }
 
         #[ink(message)]
         fn future_amp_coef(&self) -> Option<(u128, u64)> {
-            self.pool.amp_coef.future_amp_coef()
+            self.pool
+                .amp_coef
+                .future_amp_coef(self.env().block_timestamp())
+        }
+
+        #[ink(message)]
+        fn future_amp_coef_precise(&self) -> Option<(u128, u64)> {
+            self.pool
+                .amp_coef
+                .future_amp_coef_precise(self.env().block_timestamp())
+        }
+
+        #[ink(message)]
+        fn auto_amp(&self) -> Option<(AutoAmpConfig, u32)> {
+            self.pool
+                .auto_amp
+                .map(|auto_amp| (auto_amp.config(), auto_amp.imbalance_ema()))
         }
 
         #[ink(message)]
         fn fees(&self) -> (u32, u32) {
-            (self.pool.fees.trade_fee, self.pool.fees.protocol_fee)
+            let fees = self.current_fees();
+            (fees.trade_fee, fees.protocol_fee)
+        }
+
+        #[ink(message)]
+        fn future_fees(&self) -> Option<(u32, u32, u64)> {
+            self.pool
+                .fees
+                .future_fees(self.env().block_timestamp())
+                .map(|(fees, future_time)| (fees.trade_fee, fees.protocol_fee, future_time))
         }
 
         #[ink(message)]
@@ -871,8 +1854,24 @@ pub mod stable_pool {
             self.pool.fee_receiver
         }
 
+        #[ink(message)]
+        fn wazero(&self) -> Option<AccountId> {
+            self.pool.wazero
+        }
+
+        /// Panics if a `RateProviderV2` fails or its rate is stale (or the pool is locked).
+        /// Use `try_token_rates` to handle the error.
         #[ink(message)]
         fn token_rates(&mut self) -> Vec<u128> {
+            match self.try_token_rates() {
+                Ok(rates) => rates,
+                Err(error) => panic!("Token rates error: {error:?}"),
+            }
+        }
+
+        #[ink(message)]
+        fn try_token_rates(&mut self) -> Result<Vec<u128>, StablePoolError> {
+            self.ensure_unlocked()?;
             self.pool
                 .token_rates
                 .iter_mut()
@@ -889,6 +1888,73 @@ pub mod stable_pool {
                 .collect()
         }
 
+        #[ink(message)]
+        fn quote_token_rates(&self) -> Result<Vec<u128>, StablePoolError> {
+            self.ensure_unlocked()?;
+            self.pool
+                .token_rates
+                .iter()
+                .map(|rate| rate.peek_rate())
+                .collect()
+        }
+
+        #[ink(message)]
+        fn cached_token_rates(&self) -> Vec<(u128, Option<u32>)> {
+            self.pool
+                .token_rates
+                .iter()
+                .map(|rate| rate.get_cached_rate())
+                .collect()
+        }
+
+        #[ink(message)]
+        fn get_pool_state(&self) -> Result<PoolState, StablePoolError> {
+            // a snapshot taken during an operation would be inconsistent
+            self.ensure_unlocked()?;
+            let rates = self.peek_scaled_rates()?;
+            let amp_coef_precise = self.amp_coef_precise()?;
+            let invariant = math::rated_compute_d(&rates, &self.pool.reserves, amp_coef_precise)?
+                .try_into()
+                .map_err(|_| MathError::CastOverflow(106))?;
+            let (trade_fee, protocol_fee) = self.fees();
+            Ok(PoolState {
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                tokens: self.tokens(),
+                tokens_decimals: self.tokens_decimals(),
+                tokens_precisions: self
+                    .pool
+                    .tokens_decimals
+                    .iter()
+                    .map(|&decimals| 10u128.pow(decimals.abs_diff(TOKEN_TARGET_DECIMALS).into()))
+                    .collect(),
+                reserves: self.reserves(),
+                token_rates: self.quote_token_rates()?,
+                token_rates_providers: self.token_rates_providers(),
+                token_rates_updated_at: self
+                    .pool
+                    .token_rates
+                    .iter()
+                    .map(|rate| rate.get_last_update_block_no())
+                    .collect(),
+                transfer_fees: self.transfer_fees(),
+                amp_coef: self.amp_coef()?,
+                amp_coef_precise,
+                future_amp_coef: self.future_amp_coef(),
+                trade_fee,
+                protocol_fee,
+                future_fees: self.future_fees(),
+                fee_receiver: self.fee_receiver(),
+                total_supply: self.psp22.total_supply(),
+                invariant,
+            })
+        }
+
+        #[ink(message)]
+        fn transfer_fees(&self) -> Vec<Option<u32>> {
+            self.pool.transfer_fees.clone()
+        }
+
         #[ink(message)]
         fn get_swap_amount_out(
             &mut self,
@@ -896,37 +1962,76 @@ pub mod stable_pool {
             token_out: AccountId,
             token_in_amount: u128,
         ) -> Result<(u128, u128), StablePoolError> {
+            self.ensure_unlocked()?;
+            let rates = self.get_scaled_rates()?;
+            self.swap_amount_out(&rates, token_in, token_out, token_in_amount)
+        }
+
+        #[ink(message)]
+        fn get_swap_amount_in(
+            &mut self,
+            token_in: AccountId,
+            token_out: AccountId,
+            token_out_amount: u128,
+        ) -> Result<(u128, u128), StablePoolError> {
+            self.ensure_unlocked()?;
+            let rates = self.get_scaled_rates()?;
+            self.swap_amount_in(&rates, token_in, token_out, token_out_amount)
+        }
+
+        #[ink(message)]
+        fn get_spot_price(
+            &mut self,
+            token_in: AccountId,
+            token_out: AccountId,
+        ) -> Result<u128, StablePoolError> {
+            self.ensure_unlocked()?;
+            let rates = self.get_scaled_rates()?;
+            self.spot_price(&rates, token_in, token_out)
+        }
+
+        #[ink(message)]
+        fn get_swap_amount_in_for_price(
+            &mut self,
+            token_in: AccountId,
+            token_out: AccountId,
+            target_price: u128,
+        ) -> Result<u128, StablePoolError> {
+            self.ensure_unlocked()?;
             let (token_in_id, token_out_id) = self.check_tokens(token_in, token_out)?;
             let rates = self.get_scaled_rates()?;
-            Ok(math::rated_swap_to(
+            let token_in_amount = math::rated_swap_amount_for_price(
                 &rates,
                 token_in_id,
-                token_in_amount,
                 token_out_id,
+                target_price,
                 &self.reserves(),
-                &self.pool.fees,
-                self.amp_coef()?,
-            )?)
+                &self.current_fees(),
+                self.amp_coef_precise()?,
+            )?;
+            Ok(self.gross_of_transfer_fee(token_in_id, token_in_amount)?)
         }
 
         #[ink(message)]
-        fn get_swap_amount_in(
+        fn get_swap_amount_in_for_ratio(
             &mut self,
             token_in: AccountId,
             token_out: AccountId,
-            token_out_amount: u128,
-        ) -> Result<(u128, u128), StablePoolError> {
+            target_ratio: u128,
+        ) -> Result<u128, StablePoolError> {
+            self.ensure_unlocked()?;
             let (token_in_id, token_out_id) = self.check_tokens(token_in, token_out)?;
             let rates = self.get_scaled_rates()?;
-            Ok(math::rated_swap_from(
+            let token_in_amount = math::rated_swap_amount_for_ratio(
                 &rates,
                 token_in_id,
-                token_out_amount,
                 token_out_id,
+                target_ratio,
                 &self.reserves(),
-                &self.pool.fees,
-                self.amp_coef()?,
-            )?)
+                &self.current_fees(),
+                self.amp_coef_precise()?,
+            )?;
+            Ok(self.gross_of_transfer_fee(token_in_id, token_in_amount)?)
         }
 
         #[ink(message)]
@@ -934,19 +2039,19 @@ pub mod stable_pool {
             &mut self,
             amounts: Vec<u128>,
         ) -> Result<(u128, u128), StablePoolError> {
-            ensure!(
-                amounts.len() == self.pool.tokens.len(),
-                StablePoolError::IncorrectAmountsCount
-            );
+            self.ensure_unlocked()?;
             let rates = self.get_scaled_rates()?;
-            Ok(math::rated_compute_lp_amount_for_deposit(
-                &rates,
-                &amounts,
-                &self.reserves(),
-                self.psp22.total_supply(),
-                Some(&self.pool.fees),
-                self.amp_coef()?,
-            )?)
+            self.mint_liquidity_for_amounts(&rates, amounts)
+        }
+
+        #[ink(message)]
+        fn quote_mint_liquidity_for_amounts(
+            &self,
+            amounts: Vec<u128>,
+        ) -> Result<(u128, u128), StablePoolError> {
+            self.ensure_unlocked()?;
+            let rates = self.peek_scaled_rates()?;
+            self.mint_liquidity_for_amounts(&rates, amounts)
         }
 
         #[ink(message)]
@@ -954,6 +2059,7 @@ pub mod stable_pool {
             &mut self,
             liquidity: u128,
         ) -> Result<Vec<u128>, StablePoolError> {
+            self.ensure_unlocked()?;
             Ok(math::compute_amounts_given_lp(
                 liquidity,
                 &self.reserves(),
@@ -966,6 +2072,7 @@ pub mod stable_pool {
             &mut self,
             amounts: Vec<u128>,
         ) -> Result<(u128, u128), StablePoolError> {
+            self.ensure_unlocked()?;
             ensure!(
                 amounts.len() == self.pool.tokens.len(),
                 StablePoolError::IncorrectAmountsCount
@@ -976,8 +2083,8 @@ pub mod stable_pool {
                 &amounts,
                 &self.reserves(),
                 self.psp22.total_supply(),
-                Some(&self.pool.fees),
-                self.amp_coef()?,
+                Some(&self.current_fees()),
+                self.amp_coef_precise()?,
             )
             .map_err(StablePoolError::MathError)
         }
@@ -987,6 +2094,7 @@ pub mod stable_pool {
             &mut self,
             liquidity: u128,
         ) -> Result<Vec<u128>, StablePoolError> {
+            self.ensure_unlocked()?;
             ensure!(
                 liquidity <= self.psp22.total_supply(),
                 StablePoolError::InsufficientLiquidity
@@ -999,6 +2107,48 @@ pub mod stable_pool {
         }
     }
 
+    impl StablePoolQuoter for StablePoolContract {
+        #[ink(message)]
+        fn pool_tokens(&self) -> Vec<AccountId> {
+            self.tokens()
+        }
+
+        #[ink(message)]
+        fn quote_swap_amount_out(
+            &self,
+            token_in: AccountId,
+            token_out: AccountId,
+            token_in_amount: u128,
+        ) -> Result<(u128, u128), StablePoolError> {
+            self.ensure_unlocked()?;
+            let rates = self.peek_scaled_rates()?;
+            self.swap_amount_out(&rates, token_in, token_out, token_in_amount)
+        }
+
+        #[ink(message)]
+        fn quote_swap_amount_in(
+            &self,
+            token_in: AccountId,
+            token_out: AccountId,
+            token_out_amount: u128,
+        ) -> Result<(u128, u128), StablePoolError> {
+            self.ensure_unlocked()?;
+            let rates = self.peek_scaled_rates()?;
+            self.swap_amount_in(&rates, token_in, token_out, token_out_amount)
+        }
+
+        #[ink(message)]
+        fn quote_spot_price(
+            &self,
+            token_in: AccountId,
+            token_out: AccountId,
+        ) -> Result<u128, StablePoolError> {
+            self.ensure_unlocked()?;
+            let rates = self.peek_scaled_rates()?;
+            self.spot_price(&rates, token_in, token_out)
+        }
+    }
+
     impl PSP22 for StablePoolContract {
         #[ink(message)]
         fn total_supply(&self) -> u128 {
@@ -1022,6 +2172,7 @@ pub mod stable_pool {
             value: u128,
             _data: Vec<u8>,
         ) -> Result<(), PSP22Error> {
+            self.ensure_unlocked_psp22()?;
             let events = self.psp22.transfer(self.env().caller(), to, value)?;
             self.emit_events(events);
             Ok(())
@@ -1035,6 +2186,7 @@ pub mod stable_pool {
             value: u128,
             _data: Vec<u8>,
         ) -> Result<(), PSP22Error> {
+            self.ensure_unlocked_psp22()?;
             let events = self
                 .psp22
                 .transfer_from(self.env().caller(), from, to, value)?;
@@ -1044,6 +2196,7 @@ pub mod stable_pool {
 
         #[ink(message)]
         fn approve(&mut self, spender: AccountId, value: u128) -> Result<(), PSP22Error> {
+            self.ensure_unlocked_psp22()?;
             let events = self.psp22.approve(self.env().caller(), spender, value)?;
             self.emit_events(events);
             Ok(())
@@ -1055,6 +2208,7 @@ pub mod stable_pool {
             spender: AccountId,
             delta_value: u128,
         ) -> Result<(), PSP22Error> {
+            self.ensure_unlocked_psp22()?;
             let events =
                 self.psp22
                     .increase_allowance(self.env().caller(), spender, delta_value)?;
@@ -1068,6 +2222,7 @@ pub mod stable_pool {
             spender: AccountId,
             delta_value: u128,
         ) -> Result<(), PSP22Error> {
+            self.ensure_unlocked_psp22()?;
             let events =
                 self.psp22
                     .decrease_allowance(self.env().caller(), spender, delta_value)?;
@@ -1106,6 +2261,7 @@ pub mod stable_pool {
 
         #[ink(message)]
         fn transfer_ownership(&mut self, new_owner: AccountId) -> Ownable2StepResult<()> {
+            self.ensure_unlocked_ownable()?;
             self.ownable
                 .transfer_ownership(self.env().caller(), new_owner)?;
             self.env()
@@ -1115,6 +2271,7 @@ pub mod stable_pool {
 
         #[ink(message)]
         fn accept_ownership(&mut self) -> Ownable2StepResult<()> {
+            self.ensure_unlocked_ownable()?;
             let new_owner = self.env().caller();
             self.ownable.accept_ownership(new_owner)?;
             self.env()
@@ -1124,6 +2281,7 @@ pub mod stable_pool {
 
         #[ink(message)]
         fn renounce_ownership(&mut self) -> Ownable2StepResult<()> {
+            self.ensure_unlocked_ownable()?;
             self.ownable
                 .renounce_ownership(self.env().caller(), self.env().account_id())?;
             self.env().emit_event(OwnershipRenounced {});
@@ -1,6 +1,13 @@
-use ink::{contract_ref, env::DefaultEnvironment, primitives::AccountId};
+use amm_helpers::{
+    constants::stable_pool::RATE_PRECISION,
+    math::{casted_mul, scale_rate, MathError},
+    stable_swap_math::ScaledRate,
+};
+use ink::{
+    codegen::TraitCallBuilder, contract_ref, env::DefaultEnvironment, primitives::AccountId,
+};
 use scale::{Decode, Encode};
-use traits::RateProvider;
+use traits::{RateInfo, RateProvider, RateProviderError, RateProviderV2, StablePoolError};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
@@ -10,11 +17,56 @@ pub struct ExternalTokenRate {
     last_update_block_no: u32,
 }
 
+/// Rate of a `RateProviderV2`, scaled to `RATE_DECIMALS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct ExternalTokenRateV2 {
+    rate_provider: AccountId,
+    /// Max age (in milliseconds) of the rate, as reported by the provider.
+    max_age: u64,
+    cached_token_rate: u128,
+    /// Update timestamp of the cached rate, as reported by the provider.
+    cached_rate_updated_at: u64,
+    last_update_block_no: u32,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub enum TokenRate {
     Constant(u128),
     External(ExternalTokenRate),
+    ExternalV2(ExternalTokenRateV2),
+    /// Rate of a `RateProvider` multiplied by a constant factor with `RATE_PRECISION`.
+    ExternalScaled(ExternalTokenRate, u128),
+}
+
+/// Rate of a token given to a pool constructor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum RateSpec {
+    /// Constant rate with `RATE_PRECISION`.
+    Constant(u128),
+    /// Rate of a `RateProvider`.
+    External(AccountId),
+    /// Rate of a `RateProviderV2` and the max age (in milliseconds) of its rates.
+    ExternalV2(AccountId, u64),
+    /// Rate of a `RateProvider` multiplied by a constant factor with `RATE_PRECISION`.
+    ExternalScaled(AccountId, u128),
+}
+
+impl From<RateSpec> for TokenRate {
+    fn from(spec: RateSpec) -> Self {
+        match spec {
+            RateSpec::Constant(rate) => Self::new_constant(rate),
+            RateSpec::External(rate_provider) => Self::new_external(rate_provider),
+            RateSpec::ExternalV2(rate_provider, max_age) => {
+                Self::new_external_v2(rate_provider, max_age)
+            }
+            RateSpec::ExternalScaled(rate_provider, factor) => {
+                Self::ExternalScaled(ExternalTokenRate::new(rate_provider), factor)
+            }
+        }
+    }
 }
 
 impl TokenRate {
@@ -26,20 +78,81 @@ impl TokenRate {
         Self::External(ExternalTokenRate::new(rate_provider))
     }
 
+    pub fn new_external_v2(rate_provider: AccountId, max_age: u64) -> Self {
+        Self::ExternalV2(ExternalTokenRateV2::new(rate_provider, max_age))
+    }
+
+    /// Whether the constant rate (or factor) is positive and, scaled for a token
+    /// with `decimals`, fits in u128 (see `ScaledRate::new`).
+    pub fn is_valid(&self, decimals: u8) -> bool {
+        match self {
+            Self::Constant(rate) | Self::ExternalScaled(_, rate) => {
+                *rate > 0 && ScaledRate::new(*rate, decimals).is_ok()
+            }
+            Self::External(_) | Self::ExternalV2(_) => true,
+        }
+    }
+
     /// Get current rate and update the cache.
-    pub fn get_rate(&mut self) -> u128 {
+    pub fn get_rate(&mut self) -> Result<u128, StablePoolError> {
         match self {
-            Self::External(external) => external.get_rate_update(),
-            Self::Constant(rate) => *rate,
+            Self::External(external) => Ok(external.get_rate_update()),
+            Self::ExternalV2(external) => external.get_rate_update(),
+            Self::ExternalScaled(external, factor) => {
+                Ok(scale_by_factor(external.get_rate_update(), *factor)?)
+            }
+            Self::Constant(rate) => Ok(*rate),
+        }
+    }
+
+    /// Get current rate without updating the cache.
+    ///
+    /// Queries the rate provider unless the cache was updated in the current block.
+    pub fn peek_rate(&self) -> Result<u128, StablePoolError> {
+        match self {
+            Self::External(external) => Ok(external.get_rate_peek()),
+            Self::ExternalV2(external) => external.get_rate_peek(),
+            Self::ExternalScaled(external, factor) => {
+                Ok(scale_by_factor(external.get_rate_peek(), *factor)?)
+            }
+            Self::Constant(rate) => Ok(*rate),
+        }
+    }
+
+    /// Cached rate with the block number of its update (`None` for constant rates).
+    ///
+    /// Scaled rates saturate at `u128::MAX`.
+    pub fn get_cached_rate(&self) -> (u128, Option<u32>) {
+        match self {
+            Self::External(external) => (
+                external.cached_token_rate,
+                Some(external.last_update_block_no),
+            ),
+            Self::ExternalV2(external) => (
+                external.cached_token_rate,
+                Some(external.last_update_block_no),
+            ),
+            Self::ExternalScaled(external, factor) => (
+                scale_by_factor(external.cached_token_rate, *factor).unwrap_or(u128::MAX),
+                Some(external.last_update_block_no),
+            ),
+            Self::Constant(rate) => (*rate, None),
         }
     }
 
     pub fn get_rate_provider(&self) -> Option<AccountId> {
         match self {
             Self::External(external) => Some(external.rate_provider),
+            Self::ExternalV2(external) => Some(external.rate_provider),
+            Self::ExternalScaled(external, _) => Some(external.rate_provider),
             Self::Constant(_) => None,
         }
     }
+
+    /// Block number of the cached rate (`None` for constant rates).
+    pub fn get_last_update_block_no(&self) -> Option<u32> {
+        self.get_cached_rate().1
+    }
 }
 
 impl ExternalTokenRate {
@@ -60,9 +173,86 @@ impl ExternalTokenRate {
         self.cached_token_rate
     }
 
+    pub fn get_rate_peek(&self) -> u128 {
+        let current_block_no = ink::env::block_number::<DefaultEnvironment>();
+        if self.last_update_block_no < current_block_no {
+            self.query_rate()
+        } else {
+            self.cached_token_rate
+        }
+    }
+
     fn query_rate(&self) -> u128 {
         let mut rate_provider: contract_ref!(RateProvider, DefaultEnvironment) =
             self.rate_provider.into();
         rate_provider.get_rate()
     }
 }
+
+impl ExternalTokenRateV2 {
+    pub fn new(rate_provider: AccountId, max_age: u64) -> Self {
+        Self {
+            rate_provider,
+            max_age,
+            cached_token_rate: 0,
+            cached_rate_updated_at: 0,
+            last_update_block_no: 0,
+        }
+    }
+
+    pub fn get_rate_update(&mut self) -> Result<u128, StablePoolError> {
+        let current_block_no = ink::env::block_number::<DefaultEnvironment>();
+        if self.last_update_block_no < current_block_no {
+            (self.cached_token_rate, self.cached_rate_updated_at) = self.query_rate()?;
+            self.last_update_block_no = current_block_no;
+        }
+        self.ensure_fresh(self.cached_rate_updated_at)?;
+        Ok(self.cached_token_rate)
+    }
+
+    pub fn get_rate_peek(&self) -> Result<u128, StablePoolError> {
+        let current_block_no = ink::env::block_number::<DefaultEnvironment>();
+        let (rate, updated_at) = if self.last_update_block_no < current_block_no {
+            self.query_rate()?
+        } else {
+            (self.cached_token_rate, self.cached_rate_updated_at)
+        };
+        self.ensure_fresh(updated_at)?;
+        Ok(rate)
+    }
+
+    fn ensure_fresh(&self, updated_at: u64) -> Result<(), StablePoolError> {
+        let now = ink::env::block_timestamp::<DefaultEnvironment>();
+        if now.saturating_sub(updated_at) > self.max_age {
+            return Err(StablePoolError::StaleRate(self.rate_provider));
+        }
+        Ok(())
+    }
+
+    /// Returns the rate scaled to `RATE_DECIMALS` and its update timestamp.
+    fn query_rate(&self) -> Result<(u128, u64), StablePoolError> {
+        let mut rate_provider: contract_ref!(RateProviderV2, DefaultEnvironment) =
+            self.rate_provider.into();
+        let RateInfo {
+            rate,
+            decimals,
+            last_updated,
+        } = match rate_provider.call_mut().get_rate().try_invoke() {
+            Ok(Ok(result)) => result?,
+            // the call failed or its result could not be decoded
+            _ => {
+                return Err(StablePoolError::RateProviderError(
+                    RateProviderError::Unavailable,
+                ))
+            }
+        };
+        Ok((scale_rate(rate, decimals)?, last_updated))
+    }
+}
+
+/// Multiplies `rate` by `factor` with `RATE_PRECISION`, rounding down.
+fn scale_by_factor(rate: u128, factor: u128) -> Result<u128, MathError> {
+    (casted_mul(rate, factor) / RATE_PRECISION)
+        .try_into()
+        .map_err(|_| MathError::CastOverflow(124))
+}
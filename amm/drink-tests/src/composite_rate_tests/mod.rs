@@ -0,0 +1,122 @@
+mod tests_rate;
+
+use crate::composite_rate_contract::{self, CompositeRateError, Rounding, Term};
+use crate::mock_sazero_rate_contract;
+pub use crate::utils::*;
+
+use drink::{self, runtime::MinimalRuntime, session::Session};
+use ink_primitives::AccountId;
+use ink_wrapper_types::{Connection, ToAccountId};
+
+pub const RATE_PRECISION: u128 = 10u128.pow(12);
+
+/// Deploys a mock rate provider returning `rate`.
+pub fn deploy_rate_provider(
+    session: &mut Session<MinimalRuntime>,
+    salt: Vec<u8>,
+    rate: u128,
+) -> AccountId {
+    let rate_provider: AccountId = session
+        .instantiate(mock_sazero_rate_contract::Instance::new().with_salt(salt))
+        .unwrap()
+        .result
+        .to_account_id()
+        .into();
+    _ = handle_ink_error(
+        session
+            .execute(mock_sazero_rate_contract::Instance::from(rate_provider).set_rate(rate))
+            .unwrap(),
+    );
+    rate_provider
+}
+
+/// Sets the precision and update time of the rate reported by a mock `RateProviderV2`.
+pub fn set_rate_info(
+    session: &mut Session<MinimalRuntime>,
+    rate_provider: AccountId,
+    decimals: u8,
+    last_updated: Option<u64>,
+) {
+    _ = handle_ink_error(
+        session
+            .execute(
+                mock_sazero_rate_contract::Instance::from(rate_provider)
+                    .set_rate_info(decimals, last_updated),
+            )
+            .unwrap(),
+    );
+}
+
+pub fn set_available(
+    session: &mut Session<MinimalRuntime>,
+    rate_provider: AccountId,
+    available: bool,
+) {
+    _ = handle_ink_error(
+        session
+            .execute(
+                mock_sazero_rate_contract::Instance::from(rate_provider).set_available(available),
+            )
+            .unwrap(),
+    );
+}
+
+/// Deploys a composite rate owned by BOB.
+pub fn setup_composite_rate(
+    session: &mut Session<MinimalRuntime>,
+    terms: Vec<Term>,
+    rounding: Rounding,
+) -> AccountId {
+    let _ = session.set_actor(BOB);
+    session
+        .instantiate(composite_rate_contract::Instance::new(
+            bob(),
+            terms,
+            rounding,
+            None,
+        ))
+        .unwrap()
+        .result
+        .to_account_id()
+        .into()
+}
+
+pub fn get_rate(session: &mut Session<MinimalRuntime>, composite_rate: AccountId) -> u128 {
+    handle_ink_error(
+        session
+            .query(composite_rate_contract::RateProvider::get_rate(
+                &composite_rate_contract::Instance::from(composite_rate),
+            ))
+            .unwrap(),
+    )
+}
+
+pub fn get_rate_info(
+    session: &mut Session<MinimalRuntime>,
+    composite_rate: AccountId,
+) -> Result<composite_rate_contract::RateInfo, composite_rate_contract::RateProviderError> {
+    handle_ink_error(
+        session
+            .query(composite_rate_contract::RateProviderV2::get_rate(
+                &composite_rate_contract::Instance::from(composite_rate),
+            ))
+            .unwrap(),
+    )
+}
+
+pub fn set_terms(
+    session: &mut Session<MinimalRuntime>,
+    composite_rate: AccountId,
+    caller: drink::AccountId32,
+    terms: Vec<Term>,
+    rounding: Rounding,
+) -> Result<(), CompositeRateError> {
+    _ = session.set_actor(caller);
+    handle_ink_error(
+        session
+            .execute(
+                composite_rate_contract::Instance::from(composite_rate).set_terms(terms, rounding),
+            )
+            .unwrap(),
+    )
+}
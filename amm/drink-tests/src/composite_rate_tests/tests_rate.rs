@@ -0,0 +1,220 @@
+use super::*;
+
+use composite_rate_contract::{Factor, RateProviderError};
+use drink::session::Session;
+
+#[drink::test]
+fn test_product_of_providers(mut session: Session) {
+    upload_all(&mut session);
+    let lst_rate = deploy_rate_provider(&mut session, vec![0], 3 * RATE_PRECISION / 2);
+    let wrapped_lst_rate = deploy_rate_provider(&mut session, vec![1], 6 * RATE_PRECISION / 5);
+    let composite_rate = setup_composite_rate(
+        &mut session,
+        vec![
+            Term::Mul(Factor::Provider(lst_rate)),
+            Term::Mul(Factor::Provider(wrapped_lst_rate)),
+        ],
+        Rounding::Down,
+    );
+    assert_eq!(
+        get_rate(&mut session, composite_rate),
+        9 * RATE_PRECISION / 5,
+        "Rate should be a product of the providers rates"
+    );
+
+    _ = set_terms(
+        &mut session,
+        composite_rate,
+        BOB,
+        vec![
+            Term::Mul(Factor::Provider(lst_rate)),
+            Term::Mul(Factor::Constant(2 * RATE_PRECISION)),
+        ],
+        Rounding::Down,
+    )
+    .expect("Owner should set terms");
+    assert_eq!(
+        get_rate(&mut session, composite_rate),
+        3 * RATE_PRECISION,
+        "Rate should be multiplied by the constant"
+    );
+}
+
+#[drink::test]
+fn test_inverse_rate_rounding(mut session: Session) {
+    upload_all(&mut session);
+    let rate_provider = deploy_rate_provider(&mut session, vec![0], 3 * RATE_PRECISION);
+    let composite_rate = setup_composite_rate(
+        &mut session,
+        vec![Term::Div(Factor::Provider(rate_provider))],
+        Rounding::Down,
+    );
+    assert_eq!(get_rate(&mut session, composite_rate), 333_333_333_333);
+
+    for (rounding, expected_rate) in [
+        (Rounding::Up, 333_333_333_334),
+        (Rounding::Nearest, 333_333_333_333),
+    ] {
+        _ = set_terms(
+            &mut session,
+            composite_rate,
+            BOB,
+            vec![Term::Div(Factor::Provider(rate_provider))],
+            rounding,
+        )
+        .expect("Owner should set terms");
+        assert_eq!(
+            get_rate(&mut session, composite_rate),
+            expected_rate,
+            "Incorrect rate rounded {rounding:?}"
+        );
+    }
+}
+
+#[drink::test]
+fn test_set_terms_restrictions(mut session: Session) {
+    upload_all(&mut session);
+    let composite_rate = setup_composite_rate(&mut session, vec![], Rounding::Down);
+    assert_eq!(get_rate(&mut session, composite_rate), RATE_PRECISION);
+
+    assert!(
+        matches!(
+            set_terms(&mut session, composite_rate, CHARLIE, vec![], Rounding::Up),
+            Err(CompositeRateError::Ownable2StepError(_))
+        ),
+        "Only owner should set terms"
+    );
+    assert_eq!(
+        set_terms(
+            &mut session,
+            composite_rate,
+            BOB,
+            vec![Term::Div(Factor::Constant(0))],
+            Rounding::Down
+        ),
+        Err(CompositeRateError::ZeroConstant),
+        "Zero constant should be rejected"
+    );
+    assert_eq!(
+        set_terms(
+            &mut session,
+            composite_rate,
+            BOB,
+            vec![Term::Mul(Factor::Constant(RATE_PRECISION)); 9],
+            Rounding::Down
+        ),
+        Err(CompositeRateError::TooManyTerms),
+        "Too many terms should be rejected"
+    );
+}
+
+#[drink::test]
+fn test_zero_divisor_rate(mut session: Session) {
+    upload_all(&mut session);
+    let rate_provider = deploy_rate_provider(&mut session, vec![0], 0);
+    let composite_rate = setup_composite_rate(
+        &mut session,
+        vec![Term::Div(Factor::Provider(rate_provider))],
+        Rounding::Down,
+    );
+    assert!(
+        matches!(
+            get_rate_info(&mut session, composite_rate),
+            Err(RateProviderError::Custom(_))
+        ),
+        "Division by zero rate should fail"
+    );
+}
+
+#[drink::test]
+fn test_provider_v2_factors(mut session: Session) {
+    upload_all(&mut session);
+    let now = get_timestamp(&mut session) + 100_000;
+    set_timestamp(&mut session, now);
+    let lst_rate = deploy_rate_provider(&mut session, vec![0], 3 * RATE_PRECISION / 2);
+    set_rate_info(&mut session, lst_rate, 12, Some(now - 1_000));
+    // 1.2 with 18 decimals
+    let wrapped_lst_rate = deploy_rate_provider(&mut session, vec![1], 12 * 10u128.pow(17));
+    set_rate_info(&mut session, wrapped_lst_rate, 18, Some(now - 500));
+    let composite_rate = setup_composite_rate(
+        &mut session,
+        vec![
+            Term::Mul(Factor::ProviderV2(lst_rate)),
+            Term::Mul(Factor::ProviderV2(wrapped_lst_rate)),
+        ],
+        Rounding::Down,
+    );
+    let rate_info = get_rate_info(&mut session, composite_rate).expect("Should return rate");
+    assert_eq!(
+        rate_info.rate,
+        9 * RATE_PRECISION / 5,
+        "Rate should be a product of the scaled providers rates"
+    );
+    assert_eq!(
+        rate_info.last_updated,
+        now - 1_000,
+        "Rate should be updated at the oldest provider rate"
+    );
+
+    _ = set_terms(
+        &mut session,
+        composite_rate,
+        BOB,
+        vec![
+            Term::Mul(Factor::Provider(lst_rate)),
+            Term::Mul(Factor::Constant(2 * RATE_PRECISION)),
+        ],
+        Rounding::Down,
+    )
+    .expect("Owner should set terms");
+    assert_eq!(
+        get_rate_info(&mut session, composite_rate)
+            .expect("Should return rate")
+            .last_updated,
+        now,
+        "Rate without V2 providers should be updated at the current block"
+    );
+}
+
+#[drink::test]
+fn test_failing_provider(mut session: Session) {
+    upload_all(&mut session);
+    let rate_provider = deploy_rate_provider(&mut session, vec![0], RATE_PRECISION);
+    set_available(&mut session, rate_provider, false);
+    let composite_rate = setup_composite_rate(
+        &mut session,
+        vec![Term::Mul(Factor::ProviderV2(rate_provider))],
+        Rounding::Down,
+    );
+    assert_eq!(
+        get_rate_info(&mut session, composite_rate),
+        Err(RateProviderError::Unavailable),
+        "Provider error should be returned"
+    );
+    assert!(
+        session
+            .query(composite_rate_contract::RateProvider::get_rate(
+                &composite_rate_contract::Instance::from(composite_rate),
+            ))
+            .is_err(),
+        "RateProvider::get_rate should revert"
+    );
+
+    // no contract is deployed at this account
+    let missing_provider = AccountId::from([7; 32]);
+    _ = set_terms(
+        &mut session,
+        composite_rate,
+        BOB,
+        vec![Term::Mul(Factor::ProviderV2(missing_provider))],
+        Rounding::Down,
+    )
+    .expect("Owner should set terms");
+    assert!(
+        matches!(
+            get_rate_info(&mut session, composite_rate),
+            Err(RateProviderError::Custom(_))
+        ),
+        "Failed call should return an error"
+    );
+}
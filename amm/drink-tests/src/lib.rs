@@ -1,11 +1,31 @@
 #[cfg(test)]
+mod composite_rate_contract;
+#[cfg(test)]
+mod composite_rate_tests;
+#[cfg(test)]
+#[allow(unused_imports)]
+mod mock_reentrant_psp22_contract;
+#[cfg(test)]
 #[allow(unused_imports)]
 mod mock_sazero_rate_contract;
 #[cfg(test)]
+#[allow(unused_imports)]
+mod mock_taxed_psp22_contract;
+#[cfg(test)]
 mod psp22;
 #[cfg(test)]
+mod rate_aggregator_contract;
+#[cfg(test)]
+mod rate_aggregator_tests;
+#[cfg(test)]
+mod stable_farm_contract;
+#[cfg(test)]
+mod stable_farm_tests;
+#[cfg(test)]
 mod stable_pool_contract;
 #[cfg(test)]
 mod stable_swap_tests;
 #[cfg(test)]
 mod utils;
+#[cfg(test)]
+mod wrapped_azero;
@@ -0,0 +1,163 @@
+mod tests_aggregator;
+
+use crate::mock_sazero_rate_contract;
+use crate::rate_aggregator_contract::{self, RateAggregatorError, Source};
+pub use crate::utils::*;
+
+use drink::{self, runtime::MinimalRuntime, session::Session};
+use ink_primitives::AccountId;
+use ink_wrapper_types::{Connection, ToAccountId};
+
+pub const RATE_PRECISION: u128 = 10u128.pow(12);
+
+/// 1% with `DEVIATION_PRECISION`.
+pub const ONE_PERCENT: u32 = 10_000_000;
+
+/// Deploys a mock rate provider returning `rate`.
+pub fn deploy_rate_provider(
+    session: &mut Session<MinimalRuntime>,
+    salt: Vec<u8>,
+    rate: u128,
+) -> AccountId {
+    let rate_provider: AccountId = session
+        .instantiate(mock_sazero_rate_contract::Instance::new().with_salt(salt))
+        .unwrap()
+        .result
+        .to_account_id()
+        .into();
+    set_provider_rate(session, rate_provider, rate);
+    rate_provider
+}
+
+pub fn set_provider_rate(
+    session: &mut Session<MinimalRuntime>,
+    rate_provider: AccountId,
+    rate: u128,
+) {
+    _ = handle_ink_error(
+        session
+            .execute(mock_sazero_rate_contract::Instance::from(rate_provider).set_rate(rate))
+            .unwrap(),
+    );
+}
+
+/// Sets the precision and update time of the rate reported by a mock `RateProviderV2`.
+pub fn set_rate_info(
+    session: &mut Session<MinimalRuntime>,
+    rate_provider: AccountId,
+    decimals: u8,
+    last_updated: Option<u64>,
+) {
+    _ = handle_ink_error(
+        session
+            .execute(
+                mock_sazero_rate_contract::Instance::from(rate_provider)
+                    .set_rate_info(decimals, last_updated),
+            )
+            .unwrap(),
+    );
+}
+
+pub fn set_available(
+    session: &mut Session<MinimalRuntime>,
+    rate_provider: AccountId,
+    available: bool,
+) {
+    _ = handle_ink_error(
+        session
+            .execute(
+                mock_sazero_rate_contract::Instance::from(rate_provider).set_available(available),
+            )
+            .unwrap(),
+    );
+}
+
+/// Deploys a rate aggregator owned by BOB.
+pub fn setup_rate_aggregator(
+    session: &mut Session<MinimalRuntime>,
+    sources: Vec<Source>,
+    quorum: u8,
+    max_deviation: u32,
+    fallback_max_age: Option<u64>,
+) -> AccountId {
+    let _ = session.set_actor(BOB);
+    session
+        .instantiate(rate_aggregator_contract::Instance::new(
+            bob(),
+            sources,
+            quorum,
+            max_deviation,
+            fallback_max_age,
+            None,
+        ))
+        .unwrap()
+        .result
+        .to_account_id()
+        .into()
+}
+
+/// Queries the rate without updating the last accepted rate.
+pub fn get_rate_info(
+    session: &mut Session<MinimalRuntime>,
+    rate_aggregator: AccountId,
+) -> Result<rate_aggregator_contract::RateInfo, rate_aggregator_contract::RateProviderError> {
+    handle_ink_error(
+        session
+            .query(rate_aggregator_contract::RateProviderV2::get_rate(
+                &rate_aggregator_contract::Instance::from(rate_aggregator),
+            ))
+            .unwrap(),
+    )
+}
+
+/// Gets the rate, updating the last accepted rate.
+pub fn update_rate(
+    session: &mut Session<MinimalRuntime>,
+    rate_aggregator: AccountId,
+) -> Result<rate_aggregator_contract::RateInfo, rate_aggregator_contract::RateProviderError> {
+    handle_ink_error(
+        session
+            .execute(rate_aggregator_contract::RateProviderV2::get_rate(
+                &rate_aggregator_contract::Instance::from(rate_aggregator),
+            ))
+            .unwrap(),
+    )
+}
+
+pub fn report(
+    session: &mut Session<MinimalRuntime>,
+    rate_aggregator: AccountId,
+    caller: drink::AccountId32,
+    rate: u128,
+) -> Result<(), RateAggregatorError> {
+    _ = session.set_actor(caller);
+    handle_ink_error(
+        session
+            .execute(rate_aggregator_contract::Instance::from(rate_aggregator).report(rate))
+            .unwrap(),
+    )
+}
+
+pub fn set_config(
+    session: &mut Session<MinimalRuntime>,
+    rate_aggregator: AccountId,
+    caller: drink::AccountId32,
+    sources: Vec<Source>,
+    quorum: u8,
+    max_deviation: u32,
+    fallback_max_age: Option<u64>,
+) -> Result<(), RateAggregatorError> {
+    _ = session.set_actor(caller);
+    handle_ink_error(
+        session
+            .execute(
+                rate_aggregator_contract::Instance::from(rate_aggregator).set_config(
+                    sources,
+                    quorum,
+                    max_deviation,
+                    fallback_max_age,
+                ),
+            )
+            .unwrap(),
+    )
+}
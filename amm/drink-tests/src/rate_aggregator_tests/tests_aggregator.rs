@@ -0,0 +1,286 @@
+use super::*;
+
+use drink::session::Session;
+use rate_aggregator_contract::RateProviderError;
+
+const MAX_AGE: u64 = 60_000;
+
+fn deviation_error() -> RateProviderError {
+    RateProviderError::Custom("DeviationTooLarge".into())
+}
+
+fn quorum_error() -> RateProviderError {
+    RateProviderError::Custom("QuorumNotReached".into())
+}
+
+#[drink::test]
+fn test_median_of_providers(mut session: Session) {
+    upload_all(&mut session);
+    let providers: Vec<AccountId> = [100, 102, 101, 103]
+        .into_iter()
+        .enumerate()
+        .map(|(i, rate)| {
+            deploy_rate_provider(&mut session, vec![i as u8], rate * RATE_PRECISION / 100)
+        })
+        .collect();
+    let sources: Vec<Source> = providers.iter().map(|&p| Source::Provider(p)).collect();
+    let rate_aggregator = setup_rate_aggregator(
+        &mut session,
+        sources[..3].to_vec(),
+        2,
+        5 * ONE_PERCENT,
+        None,
+    );
+    assert_eq!(
+        get_rate_info(&mut session, rate_aggregator).unwrap().rate,
+        101 * RATE_PRECISION / 100,
+        "Rate should be the median of odd number of sources"
+    );
+
+    _ = set_config(
+        &mut session,
+        rate_aggregator,
+        BOB,
+        sources.clone(),
+        2,
+        5 * ONE_PERCENT,
+        None,
+    )
+    .expect("Owner should set config");
+    assert_eq!(
+        get_rate_info(&mut session, rate_aggregator).unwrap().rate,
+        1015 * RATE_PRECISION / 1000,
+        "Rate should be the mean of middle rates of even number of sources"
+    );
+
+    // A failing provider is skipped.
+    set_provider_rate(&mut session, providers[3], 0);
+    assert_eq!(
+        get_rate_info(&mut session, rate_aggregator).unwrap().rate,
+        101 * RATE_PRECISION / 100,
+        "Provider with zero rate should be skipped"
+    );
+}
+
+#[drink::test]
+fn test_provider_rate_info(mut session: Session) {
+    upload_all(&mut session);
+    let now = get_timestamp(&mut session) + 10 * MAX_AGE;
+    set_timestamp(&mut session, now);
+    let providers: Vec<AccountId> = (0..2)
+        .map(|i| deploy_rate_provider(&mut session, vec![i], RATE_PRECISION))
+        .collect();
+    // 1.0 with 18 decimals
+    set_provider_rate(&mut session, providers[1], 10u128.pow(18));
+    set_rate_info(&mut session, providers[1], 18, Some(now - MAX_AGE));
+    let rate_aggregator = setup_rate_aggregator(
+        &mut session,
+        // a `RateProvider` rate is updated in the current block
+        vec![
+            Source::Provider(providers[0]),
+            Source::ProviderV2(providers[1], MAX_AGE),
+        ],
+        2,
+        ONE_PERCENT,
+        None,
+    );
+    let rate_info = get_rate_info(&mut session, rate_aggregator).unwrap();
+    assert_eq!(
+        rate_info.rate, RATE_PRECISION,
+        "Provider rate should be scaled to RATE_DECIMALS"
+    );
+    assert_eq!(
+        rate_info.last_updated,
+        now - MAX_AGE,
+        "Rate should be updated at the oldest provider rate"
+    );
+
+    set_rate_info(&mut session, providers[1], 18, Some(now - MAX_AGE - 1));
+    assert_eq!(
+        get_rate_info(&mut session, rate_aggregator),
+        Err(quorum_error()),
+        "Stale provider rate should be skipped"
+    );
+
+    set_rate_info(&mut session, providers[1], 18, None);
+    set_available(&mut session, providers[1], false);
+    assert_eq!(
+        get_rate_info(&mut session, rate_aggregator),
+        Err(quorum_error()),
+        "Failing provider should be skipped"
+    );
+}
+
+#[drink::test]
+fn test_reporters(mut session: Session) {
+    upload_all(&mut session);
+    let rate_aggregator = setup_rate_aggregator(
+        &mut session,
+        vec![
+            Source::Reporter(charlie(), MAX_AGE),
+            Source::Reporter(dave(), 2 * MAX_AGE),
+        ],
+        2,
+        ONE_PERCENT,
+        None,
+    );
+    assert_eq!(
+        report(&mut session, rate_aggregator, EVA, RATE_PRECISION),
+        Err(RateAggregatorError::NotReporter),
+        "Only reporters should report"
+    );
+    assert_eq!(
+        report(&mut session, rate_aggregator, CHARLIE, 0),
+        Err(RateAggregatorError::ZeroRate),
+        "Zero rate should be rejected"
+    );
+    _ = report(&mut session, rate_aggregator, CHARLIE, RATE_PRECISION)
+        .expect("Reporter should report");
+    assert_eq!(
+        get_rate_info(&mut session, rate_aggregator),
+        Err(quorum_error()),
+        "Rate should require quorum"
+    );
+
+    _ = report(
+        &mut session,
+        rate_aggregator,
+        DAVE,
+        2 * RATE_PRECISION / 1000 + RATE_PRECISION,
+    )
+    .expect("Reporter should report");
+    let rate_info = get_rate_info(&mut session, rate_aggregator).unwrap();
+    assert_eq!(rate_info.rate, 1001 * RATE_PRECISION / 1000);
+    assert_eq!(rate_info.last_updated, get_timestamp(&mut session));
+
+    // CHARLIE's report becomes stale, while DAVE's is still fresh.
+    let now = get_timestamp(&mut session);
+    set_timestamp(&mut session, now + MAX_AGE + 1);
+    assert_eq!(
+        get_rate_info(&mut session, rate_aggregator),
+        Err(quorum_error()),
+        "Stale reports should be skipped"
+    );
+    _ = report(&mut session, rate_aggregator, CHARLIE, RATE_PRECISION)
+        .expect("Reporter should report");
+    assert_eq!(
+        get_rate_info(&mut session, rate_aggregator)
+            .unwrap()
+            .last_updated,
+        now,
+        "Rate should be updated at the oldest source rate"
+    );
+}
+
+#[drink::test]
+fn test_deviation_and_fallback(mut session: Session) {
+    upload_all(&mut session);
+    let providers: Vec<AccountId> = (0..3)
+        .map(|i| deploy_rate_provider(&mut session, vec![i], RATE_PRECISION))
+        .collect();
+    let rate_aggregator = setup_rate_aggregator(
+        &mut session,
+        providers.iter().map(|&p| Source::Provider(p)).collect(),
+        2,
+        ONE_PERCENT,
+        Some(MAX_AGE),
+    );
+    set_provider_rate(&mut session, providers[2], 102 * RATE_PRECISION / 100);
+    assert_eq!(
+        get_rate_info(&mut session, rate_aggregator),
+        Err(deviation_error()),
+        "Deviating rate should be rejected without a last rate"
+    );
+
+    set_provider_rate(&mut session, providers[2], 101 * RATE_PRECISION / 100);
+    let accepted_at = get_timestamp(&mut session);
+    assert_eq!(
+        update_rate(&mut session, rate_aggregator).unwrap().rate,
+        RATE_PRECISION,
+        "Rate within max deviation should be accepted"
+    );
+
+    set_provider_rate(&mut session, providers[2], 2 * RATE_PRECISION);
+    let rate_info = get_rate_info(&mut session, rate_aggregator).unwrap();
+    assert_eq!(
+        (rate_info.rate, rate_info.last_updated),
+        (RATE_PRECISION, accepted_at),
+        "Last accepted rate should be returned instead of a rejected one"
+    );
+
+    set_timestamp(&mut session, accepted_at + MAX_AGE + 1);
+    assert_eq!(
+        get_rate_info(&mut session, rate_aggregator),
+        Err(deviation_error()),
+        "Stale last rate should not be returned"
+    );
+}
+
+#[drink::test]
+fn test_set_config_restrictions(mut session: Session) {
+    upload_all(&mut session);
+    let rate_provider = deploy_rate_provider(&mut session, vec![0], RATE_PRECISION);
+    let source = Source::Provider(rate_provider);
+    let rate_aggregator = setup_rate_aggregator(&mut session, vec![source], 1, ONE_PERCENT, None);
+
+    assert!(
+        matches!(
+            set_config(
+                &mut session,
+                rate_aggregator,
+                CHARLIE,
+                vec![source],
+                1,
+                ONE_PERCENT,
+                None
+            ),
+            Err(RateAggregatorError::Ownable2StepError(_))
+        ),
+        "Only owner should set config"
+    );
+    for (sources, quorum, max_deviation, error) in [
+        (
+            vec![source],
+            2,
+            ONE_PERCENT,
+            RateAggregatorError::InvalidQuorum,
+        ),
+        (
+            vec![source],
+            0,
+            ONE_PERCENT,
+            RateAggregatorError::InvalidQuorum,
+        ),
+        (
+            vec![source],
+            1,
+            100 * ONE_PERCENT + 1,
+            RateAggregatorError::InvalidMaxDeviation,
+        ),
+        (
+            vec![source, Source::Reporter(rate_provider, MAX_AGE)],
+            1,
+            ONE_PERCENT,
+            RateAggregatorError::DuplicateSource,
+        ),
+        (
+            vec![Source::Reporter(bob(), MAX_AGE); 17],
+            1,
+            ONE_PERCENT,
+            RateAggregatorError::TooManySources,
+        ),
+    ] {
+        assert_eq!(
+            set_config(
+                &mut session,
+                rate_aggregator,
+                BOB,
+                sources,
+                quorum,
+                max_deviation,
+                None
+            ),
+            Err(error)
+        );
+    }
+}
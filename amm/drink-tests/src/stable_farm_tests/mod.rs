@@ -0,0 +1,104 @@
+mod tests_rewards;
+
+use crate::stable_farm_contract;
+use crate::stable_swap_tests::{setup_stable_swap_with_tokens, ONE_DAI, ONE_USDT};
+pub use crate::utils::*;
+
+pub use stable_farm_contract::StableFarm as _;
+pub use stable_farm_contract::StableFarmError;
+
+use drink::{self, runtime::MinimalRuntime, session::Session, AccountId32};
+
+use ink_primitives::AccountId;
+use ink_wrapper_types::ToAccountId;
+
+/// Reward period duration, in milliseconds.
+pub const REWARD_DURATION: u64 = 1_000_000;
+
+/// Rewards distributed during a single reward period.
+pub const REWARDS: u128 = 1_000_000 * TOKEN;
+
+pub struct FarmSetup {
+    pub stable_swap: AccountId,
+    pub farm: AccountId,
+    pub reward_token: AccountId,
+}
+
+/// Deploys a DAI/USDT stable pool, a reward token and a farm owned by BOB.
+/// BOB provides initial liquidity to the pool and approves the farm to spend his LP and reward tokens.
+pub fn setup_farm(session: &mut Session<MinimalRuntime>) -> FarmSetup {
+    let initial_reserves = vec![100_000 * ONE_DAI, 100_000 * ONE_USDT];
+    let initial_supply = initial_reserves
+        .iter()
+        .map(|amount| amount * 100_000)
+        .collect::<Vec<u128>>();
+    let (stable_swap, _) = setup_stable_swap_with_tokens(
+        session,
+        vec![18, 6],
+        initial_supply,
+        10_000,
+        2_500_000,
+        200_000_000,
+        BOB,
+        vec![],
+    );
+    _ = stable_swap::add_liquidity(session, stable_swap, BOB, 1, initial_reserves, bob())
+        .expect("Should successfully add liquidity");
+
+    let reward_token: AccountId = psp22_utils::setup(session, "Reward".to_string(), BOB).into();
+    let farm = stable_farm::setup(session, stable_swap, vec![reward_token], BOB);
+
+    for token in [stable_swap, reward_token] {
+        psp22_utils::increase_allowance(session, token, farm, u128::MAX, BOB).unwrap();
+    }
+
+    FarmSetup {
+        stable_swap,
+        farm,
+        reward_token,
+    }
+}
+
+/// Transfers `amount` of BOB's LP tokens to `account` and approves the farm to spend them.
+pub fn fund_staker(
+    session: &mut Session<MinimalRuntime>,
+    setup: &FarmSetup,
+    account: AccountId32,
+    amount: u128,
+) {
+    psp22_utils::transfer(
+        session,
+        setup.stable_swap,
+        account.to_account_id(),
+        amount,
+        BOB,
+    )
+    .unwrap();
+    psp22_utils::increase_allowance(session, setup.stable_swap, setup.farm, u128::MAX, account)
+        .unwrap();
+}
+
+/// Starts a reward period of `REWARD_DURATION` beginning at the current time.
+/// Returns the start time.
+pub fn start_rewards_now(session: &mut Session<MinimalRuntime>, setup: &FarmSetup) -> u64 {
+    let now = get_timestamp(session);
+    set_timestamp(session, now);
+    stable_farm::start_rewards(
+        session,
+        setup.farm,
+        BOB,
+        now,
+        now + REWARD_DURATION,
+        vec![REWARDS],
+    )
+    .expect("Should start rewards");
+    now
+}
+
+/// Asserts that `actual` is not greater than `expected` and differs from it by at most 1e-9 relative.
+pub fn assert_approx(actual: u128, expected: u128, msg: &str) {
+    assert!(
+        actual <= expected && expected - actual <= expected / 1_000_000_000,
+        "{msg}: expected {expected}, got {actual}"
+    );
+}
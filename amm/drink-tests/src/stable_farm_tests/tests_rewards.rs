@@ -0,0 +1,258 @@
+use drink::{self, session::Session};
+use stable_farm_contract::Ownable2StepError;
+
+use super::*;
+
+#[drink::test]
+fn test_single_staker_earns_all_rewards(mut session: Session) {
+    let setup = setup_farm(&mut session);
+    let shares = psp22_utils::balance_of(&mut session, setup.stable_swap, bob());
+    stable_farm::deposit(&mut session, setup.farm, BOB, shares).expect("Should deposit");
+    assert_eq!(
+        stable_farm::total_shares(&mut session, setup.farm),
+        shares,
+        "Incorrect total shares"
+    );
+
+    let start = start_rewards_now(&mut session, &setup);
+
+    set_timestamp(&mut session, start + REWARD_DURATION / 2);
+    let claimable = stable_farm::claimable(&mut session, setup.farm, bob()).unwrap();
+    assert_approx(
+        claimable[0],
+        REWARDS / 2,
+        "Half of rewards should be accrued",
+    );
+
+    // no rewards accrue after the reward period
+    set_timestamp(&mut session, start + 2 * REWARD_DURATION);
+    let reward_balance = psp22_utils::balance_of(&mut session, setup.reward_token, bob());
+    let claimed = stable_farm::claim(&mut session, setup.farm, BOB).expect("Should claim");
+    assert_approx(claimed[0], REWARDS, "All rewards should be claimed");
+    assert_eq!(
+        psp22_utils::balance_of(&mut session, setup.reward_token, bob()),
+        reward_balance + claimed[0],
+        "Rewards should be transferred"
+    );
+    assert_eq!(
+        stable_farm::claimable(&mut session, setup.farm, bob()).unwrap(),
+        vec![0],
+        "Nothing more should be claimable"
+    );
+}
+
+#[drink::test]
+fn test_rewards_split_proportionally(mut session: Session) {
+    let setup = setup_farm(&mut session);
+    let shares = psp22_utils::balance_of(&mut session, setup.stable_swap, bob()) / 4;
+    fund_staker(&mut session, &setup, CHARLIE, shares);
+
+    stable_farm::deposit(&mut session, setup.farm, BOB, shares).expect("Should deposit");
+    let start = start_rewards_now(&mut session, &setup);
+
+    // Charlie joins in the middle of the reward period with the same stake
+    set_timestamp(&mut session, start + REWARD_DURATION / 2);
+    stable_farm::deposit(&mut session, setup.farm, CHARLIE, shares).expect("Should deposit");
+
+    set_timestamp(&mut session, start + REWARD_DURATION);
+    let bob_claimed = stable_farm::claim(&mut session, setup.farm, BOB).expect("Should claim");
+    let charlie_claimed =
+        stable_farm::claim(&mut session, setup.farm, CHARLIE).expect("Should claim");
+    assert_approx(bob_claimed[0], REWARDS * 3 / 4, "Incorrect Bob's rewards");
+    assert_approx(
+        charlie_claimed[0],
+        REWARDS / 4,
+        "Incorrect Charlie's rewards",
+    );
+}
+
+#[drink::test]
+fn test_withdraw_keeps_rewards(mut session: Session) {
+    let setup = setup_farm(&mut session);
+    let shares = psp22_utils::balance_of(&mut session, setup.stable_swap, bob());
+    stable_farm::deposit(&mut session, setup.farm, BOB, shares).expect("Should deposit");
+    let start = start_rewards_now(&mut session, &setup);
+
+    set_timestamp(&mut session, start + REWARD_DURATION / 2);
+    stable_farm::withdraw(&mut session, setup.farm, BOB, shares).expect("Should withdraw");
+    assert_eq!(
+        psp22_utils::balance_of(&mut session, setup.stable_swap, bob()),
+        shares,
+        "LP tokens should be returned"
+    );
+    assert_eq!(
+        stable_farm::shares_of(&mut session, setup.farm, bob()),
+        0,
+        "No shares should be staked"
+    );
+
+    set_timestamp(&mut session, start + REWARD_DURATION);
+    let claimed = stable_farm::claim(&mut session, setup.farm, BOB).expect("Should claim");
+    assert_approx(
+        claimed[0],
+        REWARDS / 2,
+        "Rewards accrued before withdrawal should be kept",
+    );
+
+    let err = stable_farm::withdraw(&mut session, setup.farm, BOB, 1)
+        .expect_err("Should not withdraw more than staked");
+    assert_eq!(
+        err,
+        StableFarmError::InsufficientShares(),
+        "Should return appropriate error"
+    );
+}
+
+#[drink::test]
+fn test_emergency_withdraw_forfeits_rewards(mut session: Session) {
+    let setup = setup_farm(&mut session);
+    let shares = psp22_utils::balance_of(&mut session, setup.stable_swap, bob());
+    stable_farm::deposit(&mut session, setup.farm, BOB, shares).expect("Should deposit");
+    let start = start_rewards_now(&mut session, &setup);
+
+    set_timestamp(&mut session, start + REWARD_DURATION / 2);
+    let withdrawn =
+        stable_farm::emergency_withdraw(&mut session, setup.farm, BOB).expect("Should withdraw");
+    assert_eq!(withdrawn, shares, "All shares should be withdrawn");
+    assert_eq!(
+        psp22_utils::balance_of(&mut session, setup.stable_swap, bob()),
+        shares,
+        "LP tokens should be returned"
+    );
+    assert_eq!(
+        stable_farm::claimable(&mut session, setup.farm, bob()).unwrap(),
+        vec![0],
+        "Rewards should be forfeited"
+    );
+    assert_eq!(
+        stable_farm::total_shares(&mut session, setup.farm),
+        0,
+        "Incorrect total shares"
+    );
+}
+
+#[drink::test]
+fn test_stop_rewards(mut session: Session) {
+    let setup = setup_farm(&mut session);
+    let shares = psp22_utils::balance_of(&mut session, setup.stable_swap, bob());
+    stable_farm::deposit(&mut session, setup.farm, BOB, shares).expect("Should deposit");
+    let start = start_rewards_now(&mut session, &setup);
+
+    let err = stable_farm::start_rewards(
+        &mut session,
+        setup.farm,
+        BOB,
+        start + REWARD_DURATION,
+        start + 2 * REWARD_DURATION,
+        vec![REWARDS],
+    )
+    .expect_err("Should not start rewards during an active period");
+    assert_eq!(
+        err,
+        StableFarmError::RewardPeriodActive(),
+        "Should return appropriate error"
+    );
+
+    set_timestamp(&mut session, start + REWARD_DURATION / 4);
+    let returned = stable_farm::stop_rewards(&mut session, setup.farm, BOB).expect("Should stop");
+    assert_approx(
+        returned[0],
+        REWARDS * 3 / 4,
+        "Undistributed rewards should be returned",
+    );
+
+    set_timestamp(&mut session, start + REWARD_DURATION);
+    let claimed = stable_farm::claim(&mut session, setup.farm, BOB).expect("Should claim");
+    assert_approx(
+        claimed[0],
+        REWARDS / 4,
+        "Only rewards before the stop should accrue",
+    );
+    assert!(
+        psp22_utils::balance_of(&mut session, setup.reward_token, setup.farm) < TOKEN,
+        "Only dust should be left in the farm"
+    );
+}
+
+#[drink::test]
+fn test_only_owner_manages_rewards(mut session: Session) {
+    let setup = setup_farm(&mut session);
+    let now = get_timestamp(&mut session);
+
+    let err = stable_farm::start_rewards(
+        &mut session,
+        setup.farm,
+        CHARLIE,
+        now,
+        now + REWARD_DURATION,
+        vec![REWARDS],
+    )
+    .expect_err("Should not start rewards");
+    assert_eq!(
+        err,
+        StableFarmError::Ownable2StepError(Ownable2StepError::CallerNotOwner(charlie())),
+        "Should return appropriate error"
+    );
+
+    let err = stable_farm::stop_rewards(&mut session, setup.farm, CHARLIE)
+        .expect_err("Should not stop rewards");
+    assert_eq!(
+        err,
+        StableFarmError::Ownable2StepError(Ownable2StepError::CallerNotOwner(charlie())),
+        "Should return appropriate error"
+    );
+}
+
+#[drink::test]
+fn test_pool_token_is_not_a_reward(mut session: Session) {
+    let setup = setup_farm(&mut session);
+    let _ = session.set_actor(BOB);
+    let result = session.instantiate(stable_farm_contract::Instance::new(
+        setup.stable_swap,
+        vec![setup.reward_token, setup.stable_swap],
+        bob(),
+    ));
+    assert!(
+        result.is_err(),
+        "Farm paying rewards in the staked LP token should not be created"
+    );
+}
+
+#[drink::test]
+fn test_emergency_withdraw_on_rewards_overflow(mut session: Session) {
+    let setup = setup_farm(&mut session);
+    let reward_token: AccountId =
+        psp22_utils::setup_with_amounts(&mut session, "Huge".to_string(), 18, u128::MAX, BOB)
+            .into();
+    let farm = stable_farm::setup(&mut session, setup.stable_swap, vec![reward_token], BOB);
+    for token in [setup.stable_swap, reward_token] {
+        psp22_utils::increase_allowance(&mut session, token, farm, u128::MAX, BOB).unwrap();
+    }
+    stable_farm::deposit(&mut session, farm, BOB, 1).expect("Should deposit");
+    let now = get_timestamp(&mut session);
+    stable_farm::start_rewards(
+        &mut session,
+        farm,
+        BOB,
+        now,
+        now + REWARD_DURATION,
+        vec![u128::MAX],
+    )
+    .expect("Should start rewards");
+
+    // rewards per share of a single share exceed u128
+    set_timestamp(&mut session, now + REWARD_DURATION / 2);
+    assert!(
+        matches!(
+            stable_farm::claim(&mut session, farm, BOB),
+            Err(StableFarmError::MathError(_))
+        ),
+        "Claim should fail on overflow"
+    );
+    let withdrawn =
+        stable_farm::emergency_withdraw(&mut session, farm, BOB).expect("Should withdraw");
+    assert_eq!(
+        withdrawn, 1,
+        "Shares should be withdrawn despite the overflow"
+    );
+}
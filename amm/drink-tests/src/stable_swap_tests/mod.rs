@@ -1,15 +1,23 @@
+mod tests_add_liquidity_received;
 mod tests_add_remove_lp;
+mod tests_decimals;
 mod tests_getters;
+mod tests_inflation;
+mod tests_native;
 mod tests_rated;
+mod tests_reentrancy;
 mod tests_swap_exact_in_received;
 mod tests_swap_exact_out;
+mod tests_transfer_fee;
 
 use crate::stable_pool_contract;
 pub use crate::utils::*;
 use primitive_types::U256;
 
-pub use stable_pool_contract::StablePool as _;
 pub use stable_pool_contract::StablePoolError;
+pub use stable_pool_contract::{
+    StablePoolActions as _, StablePoolAdmin as _, StablePoolQuoter as _, StablePoolView as _,
+};
 
 use drink::{self, runtime::MinimalRuntime, session::Session, AccountId32};
 
@@ -21,6 +29,15 @@ pub const FEE_DENOM: u128 = 1_000_000_000;
 
 pub const RATE_PRECISION: u128 = 10u128.pow(12);
 
+/// Precision of the amplification coefficient.
+pub const A_PRECISION: u128 = 100;
+
+/// LP tokens locked forever on the first deposit.
+pub const MINIMUM_LIQUIDITY: u128 = 1_000_000_000;
+
+/// Address holding the locked `MINIMUM_LIQUIDITY`.
+pub const DEAD_ADDRESS: [u8; 32] = [0u8; 32];
+
 pub const FEE_RECEIVER: AccountId32 = AccountId32::new([42u8; 32]);
 
 pub fn fee_receiver() -> ink_primitives::AccountId {
@@ -108,11 +125,16 @@ pub fn share_price_and_total_shares(
         .iter()
         .zip(reserves.iter())
         .zip(token_rates.iter())
-        .fold(0, |acc, ((&token, reserve), rate)| {
-            acc + reserve
-                * 10u128.pow((18 - psp22_utils::token_decimals(session, token)).into())
-                * rate
-                / RATE_PRECISION
+        .fold(0, |acc, ((&token, &reserve), rate)| {
+            // scaled to 18 decimals as in the pool, rounding down
+            let decimals = psp22_utils::token_decimals(session, token);
+            let scale = 10u128.pow(decimals.abs_diff(18).into());
+            let amount = if decimals <= 18 {
+                reserve * scale
+            } else {
+                reserve / scale
+            };
+            acc + amount * rate / RATE_PRECISION
         });
 
     (
@@ -0,0 +1,191 @@
+use drink::{self, runtime::MinimalRuntime, session::Session};
+
+use super::*;
+
+/// Tests two ways of depositing liquidity: add_liquidity and add_liquidity_received
+fn test_add_liquidity_received(
+    session: &mut Session<MinimalRuntime>,
+    token_decimals: Vec<u8>,
+    initial_reserves: Vec<u128>,
+    amp_coef: u128,
+    trade_fee: u32,
+    protocol_fee: u32,
+    deposit_amounts: Vec<u128>,
+) {
+    let initial_supply = initial_reserves
+        .iter()
+        .zip(deposit_amounts.iter())
+        .map(|(reserve, amount)| reserve + amount)
+        .collect::<Vec<u128>>();
+    // setup two identical pools
+    let (stable_swap_1, _) = setup_stable_swap_with_tokens(
+        session,
+        token_decimals.clone(),
+        initial_supply.clone(),
+        amp_coef,
+        trade_fee,
+        protocol_fee,
+        BOB,
+        "foo".as_bytes().to_vec(),
+    );
+    let (stable_swap_2, tokens_2) = setup_stable_swap_with_tokens(
+        session,
+        token_decimals.clone(),
+        initial_supply,
+        amp_coef,
+        trade_fee,
+        protocol_fee,
+        BOB,
+        "bar".as_bytes().to_vec(),
+    );
+    _ = stable_swap::add_liquidity(
+        session,
+        stable_swap_1,
+        BOB,
+        1,
+        initial_reserves.clone(),
+        bob(),
+    );
+    _ = stable_swap::add_liquidity(
+        session,
+        stable_swap_2,
+        BOB,
+        1,
+        initial_reserves.clone(),
+        bob(),
+    );
+
+    let result_1 = stable_swap::add_liquidity(
+        session,
+        stable_swap_1,
+        BOB,
+        1,
+        deposit_amounts.clone(),
+        charlie(),
+    )
+    .expect("add_liquidity: Should successfully add liquidity");
+
+    for (&token, &amount) in tokens_2.iter().zip(deposit_amounts.iter()) {
+        if amount > 0 {
+            _ = psp22_utils::transfer(session, token, stable_swap_2, amount, BOB);
+        }
+    }
+
+    let result_2 = stable_swap::add_liquidity_received(session, stable_swap_2, BOB, 1, charlie())
+        .expect("add_liquidity_received: Should successfully add liquidity");
+
+    assert_eq!(result_1, result_2, "Minted shares and fee mismatch");
+
+    let expected_reserves = initial_reserves
+        .iter()
+        .zip(deposit_amounts.iter())
+        .map(|(reserve, amount)| reserve + amount)
+        .collect::<Vec<u128>>();
+    let reserves_1 = stable_swap::reserves(session, stable_swap_1);
+    let reserves_2 = stable_swap::reserves(session, stable_swap_2);
+    assert_eq!(
+        reserves_1, expected_reserves,
+        "add_liquidity: Reserves not updated properly"
+    );
+    assert_eq!(
+        reserves_2, expected_reserves,
+        "add_liquidity_received: Reserves not updated properly"
+    );
+
+    // check if reserves are equal the actual balances
+    let balances_2 = tokens_2
+        .iter()
+        .map(|&token| psp22_utils::balance_of(session, token, stable_swap_2))
+        .collect::<Vec<u128>>();
+    assert_eq!(
+        reserves_2, balances_2,
+        "add_liquidity_received: Balances - reserves mismatch"
+    );
+
+    // check charlie's shares and protocol fee
+    assert_eq!(
+        psp22_utils::balance_of(session, stable_swap_1, charlie()),
+        psp22_utils::balance_of(session, stable_swap_2, charlie()),
+        "Incorrect Charlie's shares"
+    );
+    assert_eq!(
+        psp22_utils::balance_of(session, stable_swap_1, fee_receiver()),
+        psp22_utils::balance_of(session, stable_swap_2, fee_receiver()),
+        "Incorrect protocol fee"
+    );
+    assert_eq!(
+        psp22_utils::total_supply(session, stable_swap_1),
+        psp22_utils::total_supply(session, stable_swap_2),
+        "Incorrect total shares"
+    );
+}
+
+#[drink::test]
+fn test_01(mut session: Session) {
+    test_add_liquidity_received(
+        &mut session,
+        vec![18, 6, 6],
+        vec![100000 * ONE_DAI, 100000 * ONE_USDT, 100000 * ONE_USDC],
+        10_000,
+        2_500_000,
+        200_000_000,
+        vec![500 * ONE_DAI, 500 * ONE_USDT, 500 * ONE_USDC],
+    );
+}
+
+#[drink::test]
+fn test_02(mut session: Session) {
+    test_add_liquidity_received(
+        &mut session,
+        vec![18, 6, 6],
+        vec![100000 * ONE_DAI, 100000 * ONE_USDT, 100000 * ONE_USDC],
+        10_000,
+        2_500_000,
+        200_000_000,
+        vec![100 * ONE_DAI, 200 * ONE_USDT, 400 * ONE_USDC],
+    );
+}
+
+#[drink::test]
+fn test_03(mut session: Session) {
+    test_add_liquidity_received(
+        &mut session,
+        vec![6, 6],
+        vec![100000 * ONE_USDT, 100000 * ONE_USDC],
+        1000,
+        600_000,
+        200_000_000,
+        vec![0, 5000 * ONE_USDC],
+    );
+}
+
+#[drink::test]
+fn test_for_zero_received(mut session: Session) {
+    let initial_reserves = vec![100000 * ONE_DAI, 100000 * ONE_USDT, 100000 * ONE_USDC];
+    let initial_supply = initial_reserves
+        .iter()
+        .map(|amount| amount * 100_000_000_000)
+        .collect::<Vec<u128>>();
+    let (stable_swap, _) = setup_stable_swap_with_tokens(
+        &mut session,
+        vec![18, 6, 6],
+        initial_supply,
+        10_000,
+        2_500_000,
+        200_000_000,
+        BOB,
+        vec![],
+    );
+
+    _ = stable_swap::add_liquidity(&mut session, stable_swap, BOB, 1, initial_reserves, bob())
+        .expect("Should successfully add liquidity");
+
+    let err = stable_swap::add_liquidity_received(&mut session, stable_swap, BOB, 0, bob())
+        .expect_err("Should return an error");
+
+    assert_eq!(
+        err,
+        StablePoolError::ZeroAmounts(),
+        "Should return appropriate error"
+    );
+}
@@ -0,0 +1,204 @@
+use crate::mock_sazero_rate_contract;
+use crate::stable_pool_contract;
+use crate::utils::*;
+
+use super::*;
+
+use drink::{self, runtime::MinimalRuntime, session::Session};
+use ink_primitives::AccountId;
+use ink_wrapper_types::{Connection, ToAccountId};
+
+fn setup_tokens(session: &mut Session<MinimalRuntime>, decimals: Vec<u8>) -> Vec<AccountId> {
+    upload_all(session);
+    decimals
+        .iter()
+        .enumerate()
+        .map(|(id, &decimals)| {
+            psp22_utils::setup_with_amounts(
+                session,
+                format!("Token {id}"),
+                decimals,
+                1_000_000_000 * 10u128.pow(decimals as u32),
+                BOB,
+            )
+            .into()
+        })
+        .collect()
+}
+
+fn new_stable_discover_decimals(
+    tokens: Vec<AccountId>,
+    expected_decimals: Option<Vec<u8>>,
+) -> stable_pool_contract::Instance {
+    stable_pool_contract::Instance::new_stable_discover_decimals(
+        tokens,
+        expected_decimals,
+        10_000,
+        bob(),
+        2_500_000,
+        200_000_000,
+        Some(fee_receiver()),
+    )
+}
+
+#[drink::test]
+fn test_discover_decimals(mut session: Session) {
+    let tokens = setup_tokens(&mut session, vec![18, 6, 12]);
+    let _ = session.set_actor(BOB);
+
+    let stable_swap: AccountId = session
+        .instantiate(new_stable_discover_decimals(tokens.clone(), None))
+        .unwrap()
+        .result
+        .to_account_id()
+        .into();
+    assert_eq!(
+        stable_swap::tokens_decimals(&mut session, stable_swap),
+        vec![18, 6, 12],
+        "Incorrect tokens decimals"
+    );
+
+    let stable_swap: AccountId = session
+        .instantiate(new_stable_discover_decimals(tokens, Some(vec![18, 6, 12])).with_salt(vec![1]))
+        .unwrap()
+        .result
+        .to_account_id()
+        .into();
+    assert_eq!(
+        stable_swap::tokens_decimals(&mut session, stable_swap),
+        vec![18, 6, 12],
+        "Incorrect tokens decimals"
+    );
+}
+
+#[drink::test]
+fn test_discover_decimals_mismatch(mut session: Session) {
+    let tokens = setup_tokens(&mut session, vec![18, 6]);
+    let _ = session.set_actor(BOB);
+
+    assert!(
+        session
+            .instantiate(new_stable_discover_decimals(tokens, Some(vec![18, 18])))
+            .is_err(),
+        "Should fail on decimals mismatch"
+    );
+}
+
+#[drink::test]
+fn test_discover_decimals_unavailable(mut session: Session) {
+    let mut tokens = setup_tokens(&mut session, vec![18]);
+    // the rate provider does not implement PSP22Metadata
+    let not_a_token: AccountId = session
+        .instantiate(mock_sazero_rate_contract::Instance::new())
+        .unwrap()
+        .result
+        .to_account_id()
+        .into();
+    tokens.push(not_a_token);
+    let _ = session.set_actor(BOB);
+
+    assert!(
+        session
+            .instantiate(new_stable_discover_decimals(tokens, None))
+            .is_err(),
+        "Should fail if token decimals are unavailable"
+    );
+}
+
+#[drink::test]
+fn test_too_large_decimals(mut session: Session) {
+    let tokens = setup_tokens(&mut session, vec![18, 18]);
+    let _ = session.set_actor(BOB);
+
+    assert!(
+        session
+            .instantiate(stable_pool_contract::Instance::new_stable(
+                tokens,
+                vec![37, 18],
+                10_000,
+                bob(),
+                2_500_000,
+                200_000_000,
+                Some(fee_receiver()),
+            ))
+            .is_err(),
+        "Should fail on too large decimals"
+    );
+}
+
+#[drink::test]
+fn test_token_with_more_than_18_decimals(mut session: Session) {
+    let tokens = setup_tokens(&mut session, vec![24, 18]);
+    let _ = session.set_actor(BOB);
+
+    let stable_swap: AccountId = session
+        .instantiate(new_stable_discover_decimals(tokens.clone(), None))
+        .unwrap()
+        .result
+        .to_account_id()
+        .into();
+    for &token in tokens.iter() {
+        _ = psp22_utils::increase_allowance(&mut session, token, stable_swap, u128::MAX, BOB);
+    }
+    let one_token_0 = 10u128.pow(24);
+    let one_token_1 = 10u128.pow(18);
+
+    let (shares, _) = stable_swap::add_liquidity(
+        &mut session,
+        stable_swap,
+        BOB,
+        1,
+        vec![100_000 * one_token_0, 100_000 * one_token_1],
+        bob(),
+    )
+    .expect("Should successfully add liquidity");
+    assert_eq!(
+        shares,
+        200_000 * ONE_LPT - MINIMUM_LIQUIDITY,
+        "Amounts should be scaled down to 18 decimals"
+    );
+
+    let (amount_out, _) = stable_swap::swap_exact_in(
+        &mut session,
+        stable_swap,
+        BOB,
+        tokens[0],
+        tokens[1],
+        one_token_0,
+        1,
+        bob(),
+    )
+    .expect("Should successfully swap");
+    assert!(
+        amount_out < one_token_1 && amount_out > one_token_1 * 99 / 100,
+        "Incorrect swap amount"
+    );
+
+    let (amount_in, _) = stable_swap::get_swap_amount_in(
+        &mut session,
+        stable_swap,
+        tokens[1],
+        tokens[0],
+        one_token_0 - 1,
+    )
+    .expect("Should compute swap amount");
+    let (rounded_amount_in, _) = stable_swap::get_swap_amount_in(
+        &mut session,
+        stable_swap,
+        tokens[1],
+        tokens[0],
+        one_token_0,
+    )
+    .expect("Should compute swap amount");
+    assert_eq!(
+        amount_in, rounded_amount_in,
+        "Output amount should be rounded up"
+    );
+
+    let reserves = stable_swap::reserves(&mut session, stable_swap);
+    let balances: Vec<u128> = tokens
+        .iter()
+        .map(|&token| psp22_utils::balance_of(&mut session, token, stable_swap))
+        .collect();
+    assert_eq!(reserves, balances, "Balances - reserves mismatch");
+}
@@ -34,6 +34,11 @@ fn test_01(mut session: Session) {
         tokens,
         "Incorrect token accounts"
     );
+    assert_eq!(
+        stable_swap::tokens_decimals(&mut session, stable_swap),
+        vec![18, 6, 6],
+        "Incorrect tokens decimals"
+    );
     assert_eq!(
         stable_swap::reserves(&mut session, stable_swap),
         initial_reserves,
@@ -44,6 +49,11 @@ fn test_01(mut session: Session) {
         Ok(amp_coef),
         "Incorrect A"
     );
+    assert_eq!(
+        stable_swap::amp_coef_precise(&mut session, stable_swap),
+        Ok(amp_coef * A_PRECISION),
+        "Incorrect precise A"
+    );
     assert_eq!(
         stable_swap::fees(&mut session, stable_swap),
         (trade_fee, protocol_fee),
@@ -56,7 +66,7 @@ fn test_01(mut session: Session) {
     );
     assert_eq!(
         psp22_utils::balance_of(&mut session, stable_swap, bob()),
-        300_000 * ONE_LPT,
+        300_000 * ONE_LPT - MINIMUM_LIQUIDITY,
         "Incorrect Users LP token balance"
     );
 
@@ -133,3 +143,80 @@ fn test_01(mut session: Session) {
         "Incorrect LP token supply"
     );
 }
+
+#[drink::test]
+fn test_02(mut session: Session) {
+    let initial_reserves = vec![100000 * ONE_DAI, 100000 * ONE_USDT, 100000 * ONE_USDC];
+    let initial_supply: Vec<u128> = initial_reserves.iter().map(|amount| amount * 10).collect();
+    let (stable_swap, tokens) = setup_stable_swap_with_tokens(
+        &mut session,
+        vec![18, 6, 6],
+        initial_supply,
+        10_000,
+        2_500_000,
+        200_000_000,
+        BOB,
+        vec![],
+    );
+
+    _ = stable_swap::add_liquidity(
+        &mut session,
+        stable_swap,
+        BOB,
+        1,
+        initial_reserves.clone(),
+        bob(),
+    )
+    .expect("Should successfully add liquidity");
+
+    let state =
+        stable_swap::get_pool_state(&mut session, stable_swap).expect("Should return pool state");
+    assert_eq!(state.tokens, tokens, "Incorrect token accounts");
+    assert_eq!(
+        state.tokens_decimals,
+        vec![18, 6, 6],
+        "Incorrect tokens decimals"
+    );
+    assert_eq!(
+        state.tokens_precisions,
+        vec![1, ONE_DAI / ONE_USDT, ONE_DAI / ONE_USDC],
+        "Incorrect tokens precisions"
+    );
+    assert_eq!(state.reserves, initial_reserves, "Incorrect reserves");
+    assert_eq!(
+        state.token_rates,
+        stable_swap::token_rates(&mut session, stable_swap),
+        "Incorrect token rates"
+    );
+    assert_eq!(
+        state.token_rates_updated_at,
+        vec![None; 3],
+        "Constant rates should not be cached"
+    );
+    assert_eq!(
+        (
+            state.amp_coef,
+            state.amp_coef_precise,
+            state.future_amp_coef
+        ),
+        (10_000, 10_000 * A_PRECISION, None),
+        "Incorrect A"
+    );
+    assert_eq!(
+        (state.trade_fee, state.protocol_fee, state.future_fees),
+        (2_500_000, 200_000_000, None),
+        "Incorrect fees"
+    );
+    assert_eq!(
+        state.total_supply,
+        psp22_utils::total_supply(&mut session, stable_swap),
+        "Incorrect LP token supply"
+    );
+    // balanced pool
+    assert_eq!(state.invariant, 300_000 * ONE_LPT, "Incorrect invariant");
+    assert_eq!(
+        state.timestamp,
+        get_timestamp(&mut session),
+        "Incorrect timestamp"
+    );
+}
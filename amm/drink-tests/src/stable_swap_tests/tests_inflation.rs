@@ -0,0 +1,181 @@
+use drink::{self, runtime::MinimalRuntime, session::Session};
+
+use super::*;
+
+const ONE_TOKEN: u128 = 10u128.pow(18);
+
+/// Minimum invariant of the first deposit.
+const MIN_INITIAL_D: u128 = 1_000 * MINIMUM_LIQUIDITY;
+
+fn setup_empty_pool(session: &mut Session<MinimalRuntime>) -> (AccountId, Vec<AccountId>) {
+    seed_account(session, CHARLIE);
+    let (stable_swap, tokens) = setup_stable_swap_with_tokens(
+        session,
+        vec![18, 18],
+        vec![1_000_000_000 * ONE_TOKEN, 1_000_000_000 * ONE_TOKEN],
+        10_000,
+        2_500_000,
+        200_000_000,
+        BOB,
+        vec![],
+    );
+    // CHARLIE is the attacker
+    transfer_and_increase_allowance(
+        session,
+        stable_swap,
+        tokens.clone(),
+        CHARLIE,
+        vec![1_000 * ONE_TOKEN, 1_000 * ONE_TOKEN],
+        BOB,
+    );
+    (stable_swap, tokens)
+}
+
+#[drink::test]
+fn test_tiny_first_deposit_fails(mut session: Session) {
+    let (stable_swap, _) = setup_empty_pool(&mut session);
+
+    for amounts in [vec![1, 1], vec![MIN_INITIAL_D / 2 - 1, MIN_INITIAL_D / 2]] {
+        let err =
+            stable_swap::get_mint_liquidity_for_amounts(&mut session, stable_swap, amounts.clone())
+                .expect_err("Quote should fail");
+        assert_eq!(
+            err,
+            StablePoolError::InsufficientInitialLiquidity(),
+            "Should return appropriate error"
+        );
+
+        let err =
+            stable_swap::add_liquidity(&mut session, stable_swap, CHARLIE, 1, amounts, charlie())
+                .expect_err("First deposit should fail");
+        assert_eq!(
+            err,
+            StablePoolError::InsufficientInitialLiquidity(),
+            "Should return appropriate error"
+        );
+    }
+    assert_eq!(psp22_utils::total_supply(&mut session, stable_swap), 0);
+}
+
+#[drink::test]
+fn test_first_deposit_locks_minimum_liquidity(mut session: Session) {
+    let (stable_swap, _) = setup_empty_pool(&mut session);
+    let amounts = vec![MIN_INITIAL_D / 2, MIN_INITIAL_D / 2];
+
+    let (expected_shares, _) =
+        stable_swap::get_mint_liquidity_for_amounts(&mut session, stable_swap, amounts.clone())
+            .expect("Should compute shares");
+    let (shares, _) =
+        stable_swap::add_liquidity(&mut session, stable_swap, CHARLIE, 1, amounts, charlie())
+            .expect("Should successfully add liquidity");
+
+    assert_eq!(shares, MIN_INITIAL_D - MINIMUM_LIQUIDITY);
+    assert_eq!(
+        shares, expected_shares,
+        "Quote should exclude locked shares"
+    );
+    assert_eq!(
+        psp22_utils::balance_of(&mut session, stable_swap, charlie()),
+        shares
+    );
+    assert_eq!(
+        psp22_utils::balance_of(&mut session, stable_swap, DEAD_ADDRESS.into()),
+        MINIMUM_LIQUIDITY,
+        "Minimum liquidity should be locked"
+    );
+    assert_eq!(
+        psp22_utils::total_supply(&mut session, stable_swap),
+        MIN_INITIAL_D
+    );
+
+    // subsequent deposits do not lock anything
+    let (shares, _) = stable_swap::add_liquidity(
+        &mut session,
+        stable_swap,
+        CHARLIE,
+        1,
+        vec![ONE_TOKEN, ONE_TOKEN],
+        charlie(),
+    )
+    .expect("Should successfully add liquidity");
+    assert_eq!(
+        psp22_utils::balance_of(&mut session, stable_swap, DEAD_ADDRESS.into()),
+        MINIMUM_LIQUIDITY
+    );
+    assert_eq!(
+        psp22_utils::total_supply(&mut session, stable_swap),
+        MIN_INITIAL_D + shares
+    );
+}
+
+/// Attacker makes the smallest possible first deposit, withdraws all but one share
+/// and inflates the share price by trading fees. The victim depositing afterwards
+/// should still receive a fair amount of shares.
+#[drink::test]
+fn test_share_price_inflation_attack(mut session: Session) {
+    let (stable_swap, tokens) = setup_empty_pool(&mut session);
+
+    let (attacker_shares, _) = stable_swap::add_liquidity(
+        &mut session,
+        stable_swap,
+        CHARLIE,
+        1,
+        vec![MIN_INITIAL_D / 2, MIN_INITIAL_D / 2],
+        charlie(),
+    )
+    .expect("Should successfully add liquidity");
+    _ = stable_swap::remove_liquidity_by_shares(
+        &mut session,
+        stable_swap,
+        CHARLIE,
+        attacker_shares - 1,
+        vec![0, 0],
+        charlie(),
+    )
+    .expect("Should successfully remove liquidity");
+    assert_eq!(
+        psp22_utils::total_supply(&mut session, stable_swap),
+        MINIMUM_LIQUIDITY + 1,
+        "Locked liquidity cannot be withdrawn"
+    );
+
+    // pump reserves with trading fees
+    for i in 0..20 {
+        let (token_in, token_out) = (tokens[i % 2], tokens[(i + 1) % 2]);
+        let reserves = stable_swap::reserves(&mut session, stable_swap);
+        _ = stable_swap::swap_exact_in(
+            &mut session,
+            stable_swap,
+            CHARLIE,
+            token_in,
+            token_out,
+            reserves[i % 2] / 2,
+            1,
+            charlie(),
+        )
+        .expect("Should successfully swap");
+    }
+
+    // victim deposit
+    let deposit = vec![1_000 * ONE_TOKEN, 1_000 * ONE_TOKEN];
+    let (victim_shares, _) =
+        stable_swap::add_liquidity(&mut session, stable_swap, BOB, 1, deposit.clone(), bob())
+            .expect("Should successfully add liquidity");
+    assert!(victim_shares > 0, "Victim should receive shares");
+
+    let withdrawn = stable_swap::remove_liquidity_by_shares(
+        &mut session,
+        stable_swap,
+        BOB,
+        victim_shares,
+        vec![0, 0],
+        bob(),
+    )
+    .expect("Should successfully remove liquidity");
+    for (withdrawn, deposited) in withdrawn.iter().zip(deposit.iter()) {
+        assert!(
+            *withdrawn >= deposited * 9_999 / 10_000,
+            "Victim should not lose the deposit to the attacker"
+        );
+    }
+}
@@ -0,0 +1,343 @@
+use crate::utils::*;
+
+use super::*;
+
+use drink::{self, runtime::MinimalRuntime, session::Session};
+use ink_primitives::AccountId;
+use ink_wrapper_types::ToAccountId;
+
+const WAZERO_DEC: u8 = 12;
+
+const ONE_WAZERO: u128 = 10u128.pow(WAZERO_DEC as u32);
+
+/// Deploys wAZERO, a PSP22 token and a stable pool of the two, with wAZERO set.
+/// Adds initial liquidity on behalf of the caller.
+fn setup_native_pool(
+    session: &mut Session<MinimalRuntime>,
+    caller: drink::AccountId32,
+    initial_reserve: u128,
+) -> (AccountId, AccountId, AccountId) {
+    session
+        .sandbox()
+        .mint_into(caller.clone(), 1_000_000_000 * ONE_WAZERO)
+        .unwrap();
+    upload_all(session);
+
+    let wazero = wazero_utils::setup(session, caller.clone());
+    let token: AccountId = psp22_utils::setup_with_amounts(
+        session,
+        "sAZERO".to_string(),
+        WAZERO_DEC,
+        1_000_000_000 * ONE_WAZERO,
+        caller.clone(),
+    )
+    .into();
+
+    let stable_swap: AccountId = stable_swap::setup(
+        session,
+        vec![wazero, token],
+        vec![WAZERO_DEC, WAZERO_DEC],
+        10_000,
+        caller.clone(),
+        2_500_000,
+        200_000_000,
+        Some(fee_receiver()),
+    )
+    .into();
+
+    stable_swap::set_wazero(session, stable_swap, caller.clone(), Some(wazero))
+        .expect("Should set wAZERO");
+
+    psp22_utils::increase_allowance(session, token, stable_swap, u128::MAX, caller.clone())
+        .unwrap();
+
+    _ = stable_swap::add_liquidity_native(
+        session,
+        stable_swap,
+        caller.clone(),
+        1,
+        vec![initial_reserve, initial_reserve],
+        initial_reserve,
+        caller.to_account_id(),
+    )
+    .expect("Should successfully add liquidity");
+
+    (stable_swap, wazero, token)
+}
+
+#[drink::test]
+fn test_add_liquidity_native(mut session: Session) {
+    let initial_reserve = 100_000 * ONE_WAZERO;
+    let (stable_swap, wazero, _) = setup_native_pool(&mut session, BOB, initial_reserve);
+
+    assert_eq!(
+        stable_swap::reserves(&mut session, stable_swap),
+        vec![initial_reserve, initial_reserve],
+        "Incorrect reserves"
+    );
+    assert_eq!(
+        psp22_utils::balance_of(&mut session, wazero, stable_swap),
+        initial_reserve,
+        "Native value should be wrapped"
+    );
+    assert_eq!(
+        psp22_utils::total_supply(&mut session, stable_swap),
+        psp22_utils::balance_of(&mut session, stable_swap, bob()) + MINIMUM_LIQUIDITY,
+        "Bob should own all but locked shares"
+    );
+
+    let err = stable_swap::add_liquidity_native(
+        &mut session,
+        stable_swap,
+        BOB,
+        1,
+        vec![ONE_WAZERO, ONE_WAZERO],
+        ONE_WAZERO - 1,
+        bob(),
+    )
+    .expect_err("Should not add liquidity");
+    assert_eq!(
+        err,
+        StablePoolError::IncorrectNativeAmount(),
+        "Should return appropriate error"
+    );
+}
+
+#[drink::test]
+fn test_swap_exact_in_native(mut session: Session) {
+    let initial_reserve = 100_000 * ONE_WAZERO;
+    let (stable_swap, wazero, token) = setup_native_pool(&mut session, BOB, initial_reserve);
+    let swap_amount = 1_000 * ONE_WAZERO;
+
+    let (expected_amount_out, expected_fee) =
+        stable_swap::get_swap_amount_out(&mut session, stable_swap, wazero, token, swap_amount)
+            .expect("Should compute swap amount");
+
+    let (amount_out, fee) = stable_swap::swap_exact_in_native(
+        &mut session,
+        stable_swap,
+        BOB,
+        token,
+        swap_amount,
+        expected_amount_out,
+        charlie(),
+    )
+    .expect("Should swap");
+    assert_eq!(
+        (amount_out, fee),
+        (expected_amount_out, expected_fee),
+        "Incorrect swap result"
+    );
+    assert_eq!(
+        psp22_utils::balance_of(&mut session, token, charlie()),
+        amount_out,
+        "Incorrect Charlie's balance"
+    );
+    assert_eq!(
+        stable_swap::reserves(&mut session, stable_swap),
+        vec![initial_reserve + swap_amount, initial_reserve - amount_out],
+        "Incorrect reserves"
+    );
+    assert_eq!(
+        psp22_utils::balance_of(&mut session, wazero, stable_swap),
+        initial_reserve + swap_amount,
+        "Balances - reserves mismatch"
+    );
+}
+
+#[drink::test]
+fn test_swap_exact_in_to_native(mut session: Session) {
+    let initial_reserve = 100_000 * ONE_WAZERO;
+    let (stable_swap, wazero, token) = setup_native_pool(&mut session, BOB, initial_reserve);
+    let swap_amount = 1_000 * ONE_WAZERO;
+
+    let (expected_amount_out, expected_fee) =
+        stable_swap::get_swap_amount_out(&mut session, stable_swap, token, wazero, swap_amount)
+            .expect("Should compute swap amount");
+    let dave_native_balance = native_balance_of(&mut session, DAVE);
+
+    let (amount_out, fee) = stable_swap::swap_exact_in_to_native(
+        &mut session,
+        stable_swap,
+        BOB,
+        token,
+        swap_amount,
+        expected_amount_out,
+        dave(),
+    )
+    .expect("Should swap");
+    assert_eq!(
+        (amount_out, fee),
+        (expected_amount_out, expected_fee),
+        "Incorrect swap result"
+    );
+    assert_eq!(
+        native_balance_of(&mut session, DAVE),
+        dave_native_balance + amount_out,
+        "Dave should receive native tokens"
+    );
+    assert_eq!(
+        psp22_utils::balance_of(&mut session, wazero, dave()),
+        0,
+        "Dave should not receive wAZERO"
+    );
+    assert_eq!(
+        stable_swap::reserves(&mut session, stable_swap),
+        vec![initial_reserve - amount_out, initial_reserve + swap_amount],
+        "Incorrect reserves"
+    );
+    assert_eq!(
+        psp22_utils::balance_of(&mut session, wazero, stable_swap),
+        initial_reserve - amount_out,
+        "Balances - reserves mismatch"
+    );
+}
+
+#[drink::test]
+fn test_remove_liquidity_by_shares_to_native(mut session: Session) {
+    let initial_reserve = 100_000 * ONE_WAZERO;
+    let (stable_swap, wazero, token) = setup_native_pool(&mut session, BOB, initial_reserve);
+    let shares = psp22_utils::balance_of(&mut session, stable_swap, bob()) / 10;
+    let dave_native_balance = native_balance_of(&mut session, DAVE);
+
+    let amounts = stable_swap::remove_liquidity_by_shares_to_native(
+        &mut session,
+        stable_swap,
+        BOB,
+        shares,
+        vec![1, 1],
+        dave(),
+    )
+    .expect("Should remove liquidity");
+
+    assert_eq!(
+        native_balance_of(&mut session, DAVE),
+        dave_native_balance + amounts[0],
+        "Dave should receive native tokens"
+    );
+    assert_eq!(
+        psp22_utils::balance_of(&mut session, wazero, dave()),
+        0,
+        "Dave should not receive wAZERO"
+    );
+    assert_eq!(
+        psp22_utils::balance_of(&mut session, token, dave()),
+        amounts[1],
+        "Dave should receive PSP22 tokens"
+    );
+}
+
+#[drink::test]
+fn test_native_without_wazero(mut session: Session) {
+    let (stable_swap, tokens) = setup_stable_swap_with_tokens(
+        &mut session,
+        vec![12, 12],
+        vec![1_000_000 * ONE_WAZERO, 1_000_000 * ONE_WAZERO],
+        10_000,
+        2_500_000,
+        200_000_000,
+        BOB,
+        vec![],
+    );
+    _ = stable_swap::add_liquidity(
+        &mut session,
+        stable_swap,
+        BOB,
+        1,
+        vec![100_000 * ONE_WAZERO, 100_000 * ONE_WAZERO],
+        bob(),
+    )
+    .expect("Should successfully add liquidity");
+
+    let err = stable_swap::swap_exact_in_to_native(
+        &mut session,
+        stable_swap,
+        BOB,
+        tokens[0],
+        ONE_WAZERO,
+        0,
+        bob(),
+    )
+    .expect_err("Should not swap");
+    assert_eq!(
+        err,
+        StablePoolError::NativeTokenNotSupported(),
+        "Should return appropriate error"
+    );
+
+    let not_pool_token = wazero_utils::setup(&mut session, BOB);
+    let err = stable_swap::set_wazero(&mut session, stable_swap, BOB, Some(not_pool_token))
+        .expect_err("Should not set wAZERO");
+    assert_eq!(
+        err,
+        StablePoolError::InvalidTokenId(not_pool_token),
+        "Should return appropriate error"
+    );
+}
+
+#[drink::test]
+fn test_native_ignores_excess_balance(mut session: Session) {
+    let initial_reserve = 100_000 * ONE_WAZERO;
+    let (stable_swap, wazero, token) = setup_native_pool(&mut session, BOB, initial_reserve);
+    let donation = 1_000 * ONE_WAZERO;
+
+    // donate wAZERO withdrawn from the pool back to it
+    let shares = psp22_utils::balance_of(&mut session, stable_swap, bob()) / 10;
+    _ = stable_swap::remove_liquidity_by_shares(
+        &mut session,
+        stable_swap,
+        BOB,
+        shares,
+        vec![1, 1],
+        bob(),
+    )
+    .expect("Should remove liquidity");
+    psp22_utils::transfer(&mut session, wazero, stable_swap, donation, BOB)
+        .expect("Should donate wAZERO");
+    let reserves = stable_swap::reserves(&mut session, stable_swap);
+
+    let swap_amount = ONE_WAZERO;
+    let (expected_amount_out, _) =
+        stable_swap::get_swap_amount_out(&mut session, stable_swap, wazero, token, swap_amount)
+            .expect("Should compute swap amount");
+    let (amount_out, _) = stable_swap::swap_exact_in_native(
+        &mut session,
+        stable_swap,
+        BOB,
+        token,
+        swap_amount,
+        expected_amount_out,
+        bob(),
+    )
+    .expect("Should swap");
+    assert_eq!(
+        amount_out, expected_amount_out,
+        "Only the native value should be swapped"
+    );
+    assert_eq!(
+        stable_swap::reserves(&mut session, stable_swap)[0],
+        reserves[0] + swap_amount,
+        "Donation should not be credited to the swap"
+    );
+
+    _ = stable_swap::add_liquidity_native(
+        &mut session,
+        stable_swap,
+        BOB,
+        1,
+        vec![ONE_WAZERO, 0],
+        ONE_WAZERO,
+        bob(),
+    )
+    .expect("Should add liquidity");
+    assert_eq!(
+        stable_swap::reserves(&mut session, stable_swap)[0],
+        reserves[0] + swap_amount + ONE_WAZERO,
+        "Donation should not be credited to the deposit"
+    );
+    assert_eq!(
+        psp22_utils::balance_of(&mut session, wazero, stable_swap),
+        reserves[0] + swap_amount + ONE_WAZERO + donation,
+        "Donation should stay in the pool balance"
+    );
+}
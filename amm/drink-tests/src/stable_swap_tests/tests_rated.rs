@@ -129,7 +129,7 @@ fn test_01(mut session: Session) {
     .expect("Should successfully add liquidity");
     assert_eq!(
         psp22_utils::balance_of(&mut session, rated_swap, bob()),
-        200000 * ONE_LPT,
+        200000 * ONE_LPT - MINIMUM_LIQUIDITY,
         "Incorrect user share"
     );
     let (last_share_price, last_total_shares) =
@@ -196,13 +196,13 @@ fn test_01(mut session: Session) {
     assert_eq!(last_share_price, 100000000, "Incorrect share price");
 
     // --- DIFF ----
-    // Allow withdrawing all liquidity from the pool
+    // Allow withdrawing all but the locked liquidity from the pool
 
     _ = stable_swap::remove_liquidity_by_shares(
         &mut session,
         rated_swap.into(),
         BOB,
-        200000 * ONE_LPT,
+        200000 * ONE_LPT - MINIMUM_LIQUIDITY,
         vec![1 * ONE_SAZERO, 1 * ONE_WAZERO],
         bob(),
     )
@@ -217,9 +217,12 @@ fn test_01(mut session: Session) {
     let (last_share_price, last_total_shares) =
         share_price_and_total_shares(&mut session, rated_swap);
 
-    // no shares left
-    assert_eq!(last_total_shares, 0, "Incorrect total shares");
-    assert_eq!(last_share_price, 0, "Incorrect share price");
+    // only locked shares left
+    assert_eq!(
+        last_total_shares, MINIMUM_LIQUIDITY,
+        "Incorrect total shares"
+    );
+    assert!(last_share_price >= 100000000, "Incorrect share price");
 }
 
 // ref https://github.com/ref-finance/ref-contracts/blob/d241d7aeaa6250937b160d56e5c4b5b48d9d97f7/ref-exchange/tests/test_rated_pool.rs#L116
@@ -260,7 +263,7 @@ fn test_02(mut session: Session) {
     .expect("Should successfully add liquidity");
     assert_eq!(
         psp22_utils::balance_of(&mut session, rated_swap, bob()),
-        300000 * ONE_LPT,
+        300000 * ONE_LPT - MINIMUM_LIQUIDITY,
         "Incorrect user share"
     );
     let (last_share_price, last_total_shares) =
@@ -371,7 +374,7 @@ fn test_03(mut session: Session) {
     .expect("Should successfully add liquidity");
     assert_eq!(
         psp22_utils::balance_of(&mut session, rated_swap, bob()),
-        300000 * ONE_LPT,
+        300000 * ONE_LPT - MINIMUM_LIQUIDITY,
         "Incorrect user share"
     );
     let (last_share_price, last_total_shares) =
@@ -474,7 +477,7 @@ fn test_04(mut session: Session) {
     .expect("Should successfully add liquidity");
     assert_eq!(
         psp22_utils::balance_of(&mut session, rated_swap, bob()),
-        200000 * ONE_LPT,
+        200000 * ONE_LPT - MINIMUM_LIQUIDITY,
         "Incorrect user share"
     );
     let (_, last_total_shares) = share_price_and_total_shares(&mut session, rated_swap);
@@ -547,3 +550,507 @@ fn test_04(mut session: Session) {
         "Incorrect reserves"
     );
 }
+
+#[drink::test]
+fn test_05(mut session: Session) {
+    upload_all(&mut session);
+
+    let initial_token_supply: u128 = 1_000_000_000;
+    let mock_sazero_rate = deploy_rate_provider(&mut session, vec![0]);
+    let (rated_swap, tokens) = setup_rated_swap_with_tokens(
+        &mut session,
+        BOB,
+        vec![Some(mock_sazero_rate), None],
+        initial_token_supply,
+        10000,
+        2_500_000,
+        200_000_000,
+    );
+    let [sazero, wazero]: [AccountId; 2] = tokens.try_into().unwrap();
+
+    // no rate cached yet
+    assert_eq!(
+        stable_swap::cached_token_rates(&mut session, rated_swap),
+        vec![(0, Some(0)), (RATE_PRECISION, None)],
+        "Incorrect cached rates"
+    );
+
+    set_mock_rate(&mut session, mock_sazero_rate, 2 * RATE_PRECISION);
+    _ = stable_swap::add_liquidity(
+        &mut session,
+        rated_swap.into(),
+        BOB,
+        1,
+        vec![50000 * ONE_SAZERO, 100000 * ONE_WAZERO],
+        bob(),
+    )
+    .expect("Should successfully add liquidity");
+
+    let cached_rates = stable_swap::cached_token_rates(&mut session, rated_swap);
+    assert_eq!(
+        cached_rates[0].0,
+        2 * RATE_PRECISION,
+        "Incorrect cached rate"
+    );
+    assert!(cached_rates[0].1.is_some_and(|block| block > 0));
+    assert_eq!(cached_rates[1], (RATE_PRECISION, None));
+
+    assert_eq!(
+        stable_swap::quote_swap_amount_out(&mut session, rated_swap, sazero, wazero, ONE_SAZERO),
+        stable_swap::get_swap_amount_out(&mut session, rated_swap, sazero, wazero, ONE_SAZERO),
+        "Quote should match swap amount out"
+    );
+    assert_eq!(
+        stable_swap::quote_swap_amount_in(&mut session, rated_swap, sazero, wazero, ONE_WAZERO),
+        stable_swap::get_swap_amount_in(&mut session, rated_swap, sazero, wazero, ONE_WAZERO),
+        "Quote should match swap amount in"
+    );
+}
+
+fn set_mock_rate_info(
+    session: &mut Session<MinimalRuntime>,
+    mock_rate_contract: AccountId,
+    decimals: u8,
+    last_updated: Option<u64>,
+    available: bool,
+) {
+    let mock = mock_sazero_rate_contract::Instance::from(mock_rate_contract);
+    _ = handle_ink_error(
+        session
+            .execute(mock.set_rate_info(decimals, last_updated))
+            .unwrap(),
+    );
+    _ = handle_ink_error(session.execute(mock.set_available(available)).unwrap());
+}
+
+#[drink::test]
+fn test_06(mut session: Session) {
+    upload_all(&mut session);
+
+    let now = get_timestamp(&mut session);
+    let max_age = 60_000;
+    let mock_sazero_rate = deploy_rate_provider(&mut session, vec![0]);
+    let tokens: Vec<AccountId> = (0..2)
+        .map(|i| {
+            psp22_utils::setup_with_amounts(
+                &mut session,
+                format!("Token{i}"),
+                WAZERO_DEC,
+                1_000_000 * ONE_WAZERO,
+                BOB,
+            )
+            .into()
+        })
+        .collect();
+    let instance = stable_pool_contract::Instance::new_rated_v2(
+        tokens,
+        vec![WAZERO_DEC; 2],
+        vec![Some((mock_sazero_rate, max_age)), None],
+        10000,
+        bob(),
+        2_500_000,
+        200_000_000,
+        Some(fee_receiver()),
+    );
+    let rated_swap: AccountId = session
+        .instantiate(instance)
+        .unwrap()
+        .result
+        .to_account_id()
+        .into();
+
+    // rate with 18 decimals is scaled to RATE_DECIMALS
+    set_mock_rate(&mut session, mock_sazero_rate, 2 * 10u128.pow(18));
+    set_mock_rate_info(&mut session, mock_sazero_rate, 18, None, true);
+    assert_eq!(
+        stable_swap::try_token_rates(&mut session, rated_swap),
+        Ok(vec![2 * RATE_PRECISION, RATE_PRECISION]),
+        "Incorrect token rates"
+    );
+
+    set_mock_rate_info(&mut session, mock_sazero_rate, 18, Some(now), true);
+    set_timestamp(&mut session, now + max_age);
+    assert_eq!(
+        stable_swap::try_token_rates(&mut session, rated_swap),
+        Ok(vec![2 * RATE_PRECISION, RATE_PRECISION]),
+        "Rate at max age should be accepted"
+    );
+    set_timestamp(&mut session, now + max_age + 1);
+    assert_eq!(
+        stable_swap::try_token_rates(&mut session, rated_swap),
+        Err(StablePoolError::StaleRate(mock_sazero_rate)),
+        "Stale rate should be rejected"
+    );
+    assert!(
+        session
+            .query(stable_pool_contract::Instance::from(rated_swap).token_rates())
+            .is_err(),
+        "Infallible token rates should revert on stale rate"
+    );
+
+    set_mock_rate_info(&mut session, mock_sazero_rate, 18, None, false);
+    assert_eq!(
+        stable_swap::try_token_rates(&mut session, rated_swap),
+        Err(StablePoolError::RateProviderError(
+            stable_pool_contract::RateProviderError::Unavailable
+        )),
+        "Provider error should be returned"
+    );
+}
+
+// withdrawing by shares with auto amp enabled does not depend on the rate provider
+#[drink::test]
+fn test_08(mut session: Session) {
+    upload_all(&mut session);
+
+    let now = get_timestamp(&mut session);
+    let max_age = 60_000;
+    let mock_sazero_rate = deploy_rate_provider(&mut session, vec![0]);
+    let tokens: Vec<AccountId> = (0..2)
+        .map(|i| {
+            psp22_utils::setup_with_amounts(
+                &mut session,
+                format!("Token{i}"),
+                WAZERO_DEC,
+                1_000_000 * ONE_WAZERO,
+                BOB,
+            )
+            .into()
+        })
+        .collect();
+    let instance = stable_pool_contract::Instance::new_rated_v2(
+        tokens.clone(),
+        vec![WAZERO_DEC; 2],
+        vec![Some((mock_sazero_rate, max_age)), None],
+        10000,
+        bob(),
+        2_500_000,
+        200_000_000,
+        Some(fee_receiver()),
+    );
+    let rated_swap: AccountId = session
+        .instantiate(instance)
+        .unwrap()
+        .result
+        .to_account_id()
+        .into();
+    for token in tokens {
+        psp22_utils::increase_allowance(&mut session, token, rated_swap, u128::MAX, BOB).unwrap();
+    }
+
+    set_mock_rate_info(&mut session, mock_sazero_rate, SAZERO_DEC, Some(now), true);
+    let (shares, _) = stable_swap::add_liquidity(
+        &mut session,
+        rated_swap,
+        BOB,
+        1,
+        vec![100_000 * ONE_SAZERO, 100_000 * ONE_WAZERO],
+        bob(),
+    )
+    .expect("Should successfully add liquidity");
+    stable_swap::set_auto_amp(
+        &mut session,
+        rated_swap,
+        BOB,
+        Some(stable_pool_contract::AutoAmpConfig {
+            min_amp_coef: 100,
+            max_amp_coef: 10000,
+            lower_threshold: 100_000_000,
+            raise_threshold: 20_000_000,
+            ema_period: 3_600_000,
+        }),
+    )
+    .expect("Should enable auto amp");
+
+    // stale rate
+    set_timestamp(&mut session, now + max_age + 1);
+    assert_eq!(
+        stable_swap::add_liquidity(
+            &mut session,
+            rated_swap,
+            BOB,
+            1,
+            vec![ONE_SAZERO, ONE_WAZERO],
+            bob(),
+        ),
+        Err(StablePoolError::StaleRate(mock_sazero_rate)),
+        "Stale rate should be rejected"
+    );
+    stable_swap::remove_liquidity_by_shares(
+        &mut session,
+        rated_swap,
+        BOB,
+        shares / 4,
+        vec![1, 1],
+        bob(),
+    )
+    .expect("Should withdraw with a stale rate");
+
+    // failing provider
+    set_mock_rate_info(&mut session, mock_sazero_rate, SAZERO_DEC, None, false);
+    stable_swap::remove_liquidity_by_shares(
+        &mut session,
+        rated_swap,
+        BOB,
+        shares / 4,
+        vec![1, 1],
+        bob(),
+    )
+    .expect("Should withdraw with a failing rate provider");
+    assert_eq!(
+        psp22_utils::balance_of(&mut session, rated_swap, bob()),
+        shares - 2 * (shares / 4),
+        "Incorrect LP token balance"
+    );
+}
+
+fn new_with_rates(
+    tokens: Vec<AccountId>,
+    rates: Vec<stable_pool_contract::RateSpec>,
+) -> stable_pool_contract::Instance {
+    stable_pool_contract::Instance::new_with_rates(
+        tokens.clone(),
+        vec![WAZERO_DEC; tokens.len()],
+        rates,
+        vec![None; tokens.len()],
+        10000,
+        bob(),
+        2_500_000,
+        200_000_000,
+        Some(fee_receiver()),
+    )
+}
+
+// pool of a token redenominated 1:1000 and the original token
+#[drink::test]
+fn test_07(mut session: Session) {
+    use stable_pool_contract::RateSpec;
+
+    upload_all(&mut session);
+
+    let tokens: Vec<AccountId> = (0..2)
+        .map(|i| {
+            psp22_utils::setup_with_amounts(
+                &mut session,
+                format!("Token{i}"),
+                WAZERO_DEC,
+                10_000_000 * ONE_WAZERO,
+                BOB,
+            )
+            .into()
+        })
+        .collect();
+    let _ = session.set_actor(BOB);
+
+    for invalid_rate in [0, u128::MAX] {
+        assert!(
+            session
+                .instantiate(new_with_rates(
+                    tokens.clone(),
+                    vec![
+                        RateSpec::Constant(invalid_rate),
+                        RateSpec::Constant(RATE_PRECISION)
+                    ],
+                ))
+                .is_err(),
+            "Should fail on invalid rate {invalid_rate}"
+        );
+    }
+
+    let rated_swap: AccountId = session
+        .instantiate(new_with_rates(
+            tokens.clone(),
+            vec![
+                RateSpec::Constant(1000 * RATE_PRECISION),
+                RateSpec::Constant(RATE_PRECISION),
+            ],
+        ))
+        .unwrap()
+        .result
+        .to_account_id()
+        .into();
+    for &token in tokens.iter() {
+        psp22_utils::increase_allowance(&mut session, token, rated_swap, u128::MAX, BOB).unwrap();
+    }
+    _ = stable_swap::add_liquidity(
+        &mut session,
+        rated_swap,
+        BOB,
+        1,
+        vec![1000 * ONE_WAZERO, 1_000_000 * ONE_WAZERO],
+        bob(),
+    )
+    .expect("Should successfully add liquidity");
+
+    let (amount_out, _) = stable_swap::get_swap_amount_out(
+        &mut session,
+        rated_swap,
+        tokens[0],
+        tokens[1],
+        ONE_WAZERO,
+    )
+    .expect("Should quote swap");
+    assert!(
+        amount_out > 997 * ONE_WAZERO && amount_out < 1000 * ONE_WAZERO,
+        "Swap should follow the fixed ratio, got {amount_out}"
+    );
+}
+
+// fractional amplification coefficient, set via the precise entry points
+#[drink::test]
+fn test_09(mut session: Session) {
+    use stable_pool_contract::RateSpec;
+
+    let day = 86_400_000;
+    upload_all(&mut session);
+
+    let tokens: Vec<AccountId> = (0..2)
+        .map(|i| {
+            psp22_utils::setup_with_amounts(
+                &mut session,
+                format!("Token{i}"),
+                WAZERO_DEC,
+                10_000_000 * ONE_WAZERO,
+                BOB,
+            )
+            .into()
+        })
+        .collect();
+    let _ = session.set_actor(BOB);
+
+    let rated_swap: AccountId = session
+        .instantiate(stable_pool_contract::Instance::new_with_rates_precise(
+            tokens.clone(),
+            vec![WAZERO_DEC; 2],
+            vec![RateSpec::Constant(RATE_PRECISION); 2],
+            vec![None; 2],
+            150,
+            bob(),
+            2_500_000,
+            200_000_000,
+            Some(fee_receiver()),
+        ))
+        .unwrap()
+        .result
+        .to_account_id()
+        .into();
+    assert_eq!(
+        stable_swap::amp_coef(&mut session, rated_swap),
+        Ok(1),
+        "A should be rounded down"
+    );
+    assert_eq!(
+        stable_swap::amp_coef_precise(&mut session, rated_swap),
+        Ok(150),
+        "Incorrect precise A"
+    );
+
+    let now = get_timestamp(&mut session);
+    assert_eq!(
+        stable_swap::ramp_amp_coef_precise(&mut session, rated_swap, BOB, 99, now + day),
+        Err(StablePoolError::AmpCoefTooLow),
+        "A below 1 should be rejected"
+    );
+    assert_eq!(
+        stable_swap::ramp_amp_coef_precise(&mut session, rated_swap, BOB, 275, now + day),
+        Ok(()),
+        "Should ramp to a fractional A"
+    );
+    assert_eq!(
+        stable_swap::future_amp_coef_precise(&mut session, rated_swap),
+        Some((275, now + day)),
+        "Incorrect precise future A"
+    );
+    assert_eq!(
+        stable_swap::future_amp_coef(&mut session, rated_swap),
+        Some((2, now + day)),
+        "Future A should be rounded down"
+    );
+    set_timestamp(&mut session, now + day / 2);
+    assert_eq!(
+        stable_swap::amp_coef_precise(&mut session, rated_swap),
+        Ok(212),
+        "Incorrect precise A during the ramp"
+    );
+    set_timestamp(&mut session, now + day);
+    assert_eq!(
+        stable_swap::amp_coef_precise(&mut session, rated_swap),
+        Ok(275),
+        "Incorrect precise A after the ramp"
+    );
+}
+
+// infallible token rates revert while a `RateProviderV2` is stale or failing
+#[drink::test]
+fn test_10(mut session: Session) {
+    upload_all(&mut session);
+
+    let now = get_timestamp(&mut session);
+    let max_age = 60_000;
+    let mock_sazero_rate = deploy_rate_provider(&mut session, vec![0]);
+    let tokens: Vec<AccountId> = (0..2)
+        .map(|i| {
+            psp22_utils::setup_with_amounts(
+                &mut session,
+                format!("Token{i}"),
+                WAZERO_DEC,
+                1_000_000 * ONE_WAZERO,
+                BOB,
+            )
+            .into()
+        })
+        .collect();
+    let rated_swap: AccountId = session
+        .instantiate(stable_pool_contract::Instance::new_rated_v2(
+            tokens,
+            vec![WAZERO_DEC; 2],
+            vec![Some((mock_sazero_rate, max_age)), None],
+            10000,
+            bob(),
+            2_500_000,
+            200_000_000,
+            Some(fee_receiver()),
+        ))
+        .unwrap()
+        .result
+        .to_account_id()
+        .into();
+
+    set_mock_rate(&mut session, mock_sazero_rate, 2 * RATE_PRECISION);
+    set_mock_rate_info(&mut session, mock_sazero_rate, 12, Some(now), true);
+    assert_eq!(
+        stable_swap::token_rates(&mut session, rated_swap),
+        vec![2 * RATE_PRECISION, RATE_PRECISION],
+        "Incorrect token rates"
+    );
+
+    set_timestamp(&mut session, now + max_age + 1);
+    assert!(
+        session
+            .query(stable_pool_contract::Instance::from(rated_swap).token_rates())
+            .is_err(),
+        "Token rates should revert on stale rate"
+    );
+
+    // the provider updates its rate
+    set_mock_rate_info(
+        &mut session,
+        mock_sazero_rate,
+        12,
+        Some(now + max_age),
+        true,
+    );
+    assert_eq!(
+        stable_swap::token_rates(&mut session, rated_swap),
+        vec![2 * RATE_PRECISION, RATE_PRECISION],
+        "Token rates should recover with a fresh rate"
+    );
+
+    set_mock_rate_info(&mut session, mock_sazero_rate, 12, None, false);
+    assert!(
+        session
+            .query(stable_pool_contract::Instance::from(rated_swap).token_rates())
+            .is_err(),
+        "Token rates should revert on failing provider"
+    );
+}
@@ -0,0 +1,213 @@
+use crate::utils::*;
+
+use super::*;
+
+use drink::{self, runtime::MinimalRuntime, session::Session};
+use ink_primitives::AccountId;
+
+const TOKEN_DEC: u8 = 12;
+
+const ONE_TOKEN: u128 = 10u128.pow(TOKEN_DEC as u32);
+
+/// Deploys a reentrant token, a regular PSP22 token and a stable pool of the two.
+/// Adds initial liquidity on behalf of the caller and only then arms the reentrant token.
+fn setup_reentrant_pool(
+    session: &mut Session<MinimalRuntime>,
+    caller: drink::AccountId32,
+    initial_reserve: u128,
+) -> (AccountId, AccountId, AccountId) {
+    upload_all(session);
+
+    let reentrant_token = reentrant_psp22_utils::setup(
+        session,
+        TOKEN_DEC,
+        1_000_000_000 * ONE_TOKEN,
+        caller.clone(),
+    );
+    let token: AccountId = psp22_utils::setup_with_amounts(
+        session,
+        "USDT".to_string(),
+        TOKEN_DEC,
+        1_000_000_000 * ONE_TOKEN,
+        caller.clone(),
+    )
+    .into();
+
+    let stable_swap: AccountId = stable_swap::setup(
+        session,
+        vec![reentrant_token, token],
+        vec![TOKEN_DEC, TOKEN_DEC],
+        10_000,
+        caller.clone(),
+        2_500_000,
+        200_000_000,
+        Some(fee_receiver()),
+    )
+    .into();
+
+    for t in [reentrant_token, token] {
+        psp22_utils::increase_allowance(session, t, stable_swap, u128::MAX, caller.clone())
+            .unwrap();
+    }
+
+    _ = stable_swap::add_liquidity(
+        session,
+        stable_swap,
+        caller.clone(),
+        1,
+        vec![initial_reserve, initial_reserve],
+        bob(),
+    )
+    .expect("Should successfully add liquidity");
+
+    reentrant_psp22_utils::set_pool(session, reentrant_token, Some(stable_swap), caller);
+
+    (stable_swap, reentrant_token, token)
+}
+
+fn assert_reentry_blocked(
+    session: &mut Session<MinimalRuntime>,
+    stable_swap: AccountId,
+    reentrant_token: AccountId,
+    token: AccountId,
+    expected_attempts: u32,
+) {
+    let (attempts, blocked) = reentrant_psp22_utils::reentry_stats(session, reentrant_token);
+    assert_eq!(attempts, expected_attempts, "Token should reenter the pool");
+    assert_eq!(blocked, attempts, "All reentrant calls should be blocked");
+    let reserves = stable_swap::reserves(session, stable_swap);
+    assert_eq!(
+        reserves,
+        vec![
+            psp22_utils::balance_of(session, reentrant_token, stable_swap),
+            psp22_utils::balance_of(session, token, stable_swap),
+        ],
+        "Balances - reserves mismatch"
+    );
+}
+
+#[drink::test]
+fn test_reentrancy_on_swap(mut session: Session) {
+    let initial_reserve = 100_000 * ONE_TOKEN;
+    let (stable_swap, reentrant_token, token) =
+        setup_reentrant_pool(&mut session, BOB, initial_reserve);
+
+    // token in is reentrant - reenters on `transfer_from`
+    _ = stable_swap::swap_exact_in(
+        &mut session,
+        stable_swap,
+        BOB,
+        reentrant_token,
+        token,
+        ONE_TOKEN,
+        1,
+        bob(),
+    )
+    .expect("Should swap");
+    assert_reentry_blocked(&mut session, stable_swap, reentrant_token, token, 4);
+
+    // token out is reentrant - reenters on `transfer`
+    _ = stable_swap::swap_exact_in(
+        &mut session,
+        stable_swap,
+        BOB,
+        token,
+        reentrant_token,
+        ONE_TOKEN,
+        1,
+        bob(),
+    )
+    .expect("Should swap");
+    assert_reentry_blocked(&mut session, stable_swap, reentrant_token, token, 8);
+}
+
+#[drink::test]
+fn test_reentrancy_on_add_and_remove_liquidity(mut session: Session) {
+    let initial_reserve = 100_000 * ONE_TOKEN;
+    let (stable_swap, reentrant_token, token) =
+        setup_reentrant_pool(&mut session, BOB, initial_reserve);
+
+    _ = stable_swap::add_liquidity(
+        &mut session,
+        stable_swap,
+        BOB,
+        1,
+        vec![ONE_TOKEN, ONE_TOKEN],
+        bob(),
+    )
+    .expect("Should add liquidity");
+    assert_reentry_blocked(&mut session, stable_swap, reentrant_token, token, 4);
+
+    let shares = psp22_utils::balance_of(&mut session, stable_swap, bob()) / 10;
+    _ = stable_swap::remove_liquidity_by_shares(
+        &mut session,
+        stable_swap,
+        BOB,
+        shares,
+        vec![1, 1],
+        bob(),
+    )
+    .expect("Should remove liquidity");
+    assert_reentry_blocked(&mut session, stable_swap, reentrant_token, token, 8);
+}
+
+#[drink::test]
+fn test_lock_released_after_call(mut session: Session) {
+    let initial_reserve = 100_000 * ONE_TOKEN;
+    let (stable_swap, reentrant_token, token) =
+        setup_reentrant_pool(&mut session, BOB, initial_reserve);
+
+    _ = stable_swap::swap_exact_in(
+        &mut session,
+        stable_swap,
+        BOB,
+        reentrant_token,
+        token,
+        ONE_TOKEN,
+        1,
+        bob(),
+    )
+    .expect("Should swap");
+
+    stable_swap::get_swap_amount_out(&mut session, stable_swap, token, reentrant_token, ONE_TOKEN)
+        .expect("Quote should succeed outside of a pool call");
+    reentrant_psp22_utils::set_pool(&mut session, reentrant_token, None, BOB);
+    _ = stable_swap::swap_exact_in(
+        &mut session,
+        stable_swap,
+        BOB,
+        token,
+        reentrant_token,
+        ONE_TOKEN,
+        1,
+        bob(),
+    )
+    .expect("Should swap after a guarded call");
+    assert_reentry_blocked(&mut session, stable_swap, reentrant_token, token, 4);
+}
+
+#[drink::test]
+fn test_reentrancy_into_lp_token(mut session: Session) {
+    let initial_reserve = 100_000 * ONE_TOKEN;
+    let (stable_swap, reentrant_token, token) =
+        setup_reentrant_pool(&mut session, BOB, initial_reserve);
+
+    // the token reenters LP token `transfer` and `approve` in the middle of the swap
+    _ = stable_swap::swap_exact_in(
+        &mut session,
+        stable_swap,
+        BOB,
+        reentrant_token,
+        token,
+        ONE_TOKEN,
+        1,
+        bob(),
+    )
+    .expect("Should swap");
+    assert_reentry_blocked(&mut session, stable_swap, reentrant_token, token, 4);
+    assert_eq!(
+        psp22_utils::allowance(&mut session, stable_swap, reentrant_token, reentrant_token),
+        0,
+        "Reentrant approve should be reverted"
+    );
+}
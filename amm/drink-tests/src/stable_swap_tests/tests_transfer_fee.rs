@@ -0,0 +1,204 @@
+use crate::utils::*;
+
+use super::*;
+
+use drink::{self, runtime::MinimalRuntime, session::Session};
+use ink_primitives::AccountId;
+
+const TOKEN_DEC: u8 = 12;
+
+const ONE_TOKEN: u128 = 10u128.pow(TOKEN_DEC as u32);
+
+/// 3% fee on transfer.
+const TAX: u32 = 30_000_000;
+
+/// Deploys a taxed token, a regular PSP22 token and a stable pool of the two
+/// with the taxed token flagged as charging a fee on transfer.
+fn setup_taxed_pool(
+    session: &mut Session<MinimalRuntime>,
+    caller: drink::AccountId32,
+) -> (AccountId, AccountId, AccountId) {
+    upload_all(session);
+
+    let taxed_token = taxed_psp22_utils::setup(
+        session,
+        TOKEN_DEC,
+        1_000_000_000 * ONE_TOKEN,
+        TAX,
+        caller.clone(),
+    );
+    let token: AccountId = psp22_utils::setup_with_amounts(
+        session,
+        "USDT".to_string(),
+        TOKEN_DEC,
+        1_000_000_000 * ONE_TOKEN,
+        caller.clone(),
+    )
+    .into();
+
+    let stable_swap: AccountId = stable_swap::setup_with_transfer_fees(
+        session,
+        vec![taxed_token, token],
+        vec![TOKEN_DEC, TOKEN_DEC],
+        vec![Some(TAX), None],
+        10_000,
+        caller.clone(),
+        2_500_000,
+        200_000_000,
+        Some(fee_receiver()),
+    )
+    .into();
+
+    for t in [taxed_token, token] {
+        psp22_utils::increase_allowance(session, t, stable_swap, u128::MAX, caller.clone())
+            .unwrap();
+    }
+
+    (stable_swap, taxed_token, token)
+}
+
+fn assert_reserves_match_balances(
+    session: &mut Session<MinimalRuntime>,
+    stable_swap: AccountId,
+    tokens: [AccountId; 2],
+) {
+    let balances: Vec<u128> = tokens
+        .iter()
+        .map(|&token| psp22_utils::balance_of(session, token, stable_swap))
+        .collect();
+    assert_eq!(
+        stable_swap::reserves(session, stable_swap),
+        balances,
+        "Balances - reserves mismatch"
+    );
+}
+
+#[drink::test]
+fn test_add_liquidity_with_transfer_fee(mut session: Session) {
+    let (stable_swap, taxed_token, token) = setup_taxed_pool(&mut session, BOB);
+    let amounts = vec![100_000 * ONE_TOKEN, 100_000 * ONE_TOKEN];
+
+    let (expected_shares, _) =
+        stable_swap::get_mint_liquidity_for_amounts(&mut session, stable_swap, amounts.clone())
+            .expect("Should compute shares");
+    let (shares, _) =
+        stable_swap::add_liquidity(&mut session, stable_swap, BOB, 1, amounts.clone(), bob())
+            .expect("Should successfully add liquidity");
+    assert_eq!(
+        shares, expected_shares,
+        "Quote should account for the transfer fee"
+    );
+    assert_eq!(
+        stable_swap::reserves(&mut session, stable_swap),
+        vec![amounts[0] * 97 / 100, amounts[1]],
+        "Only credited amounts should be added to reserves"
+    );
+    assert_reserves_match_balances(&mut session, stable_swap, [taxed_token, token]);
+}
+
+#[drink::test]
+fn test_swap_exact_in_with_transfer_fee(mut session: Session) {
+    let (stable_swap, taxed_token, token) = setup_taxed_pool(&mut session, BOB);
+    _ = stable_swap::add_liquidity(
+        &mut session,
+        stable_swap,
+        BOB,
+        1,
+        vec![100_000 * ONE_TOKEN, 100_000 * ONE_TOKEN],
+        bob(),
+    )
+    .expect("Should successfully add liquidity");
+    let swap_amount = 1_000 * ONE_TOKEN;
+
+    let (expected_amount_out, expected_fee) = stable_swap::get_swap_amount_out(
+        &mut session,
+        stable_swap,
+        taxed_token,
+        token,
+        swap_amount,
+    )
+    .expect("Should compute swap amount");
+    let result = stable_swap::swap_exact_in(
+        &mut session,
+        stable_swap,
+        BOB,
+        taxed_token,
+        token,
+        swap_amount,
+        expected_amount_out,
+        charlie(),
+    )
+    .expect("Should swap");
+    assert_eq!(
+        result,
+        (expected_amount_out, expected_fee),
+        "Quote should account for the transfer fee"
+    );
+    assert_reserves_match_balances(&mut session, stable_swap, [taxed_token, token]);
+
+    // the taxed token as token out - the pool pays the full amount, the recipient gets it taxed
+    let (amount_out, _) = stable_swap::swap_exact_in(
+        &mut session,
+        stable_swap,
+        BOB,
+        token,
+        taxed_token,
+        swap_amount,
+        1,
+        dave(),
+    )
+    .expect("Should swap");
+    assert_eq!(
+        psp22_utils::balance_of(&mut session, taxed_token, dave()),
+        amount_out - amount_out * TAX as u128 / FEE_DENOM,
+        "Recipient should be taxed"
+    );
+    assert_reserves_match_balances(&mut session, stable_swap, [taxed_token, token]);
+}
+
+#[drink::test]
+fn test_swap_exact_out_with_transfer_fee(mut session: Session) {
+    let (stable_swap, taxed_token, token) = setup_taxed_pool(&mut session, BOB);
+    _ = stable_swap::add_liquidity(
+        &mut session,
+        stable_swap,
+        BOB,
+        1,
+        vec![100_000 * ONE_TOKEN, 100_000 * ONE_TOKEN],
+        bob(),
+    )
+    .expect("Should successfully add liquidity");
+    let amount_out = 1_000 * ONE_TOKEN;
+
+    let (expected_amount_in, expected_fee) =
+        stable_swap::get_swap_amount_in(&mut session, stable_swap, taxed_token, token, amount_out)
+            .expect("Should compute swap amount");
+    let bob_balance = psp22_utils::balance_of(&mut session, taxed_token, bob());
+    let result = stable_swap::swap_exact_out(
+        &mut session,
+        stable_swap,
+        BOB,
+        taxed_token,
+        token,
+        amount_out,
+        expected_amount_in,
+        charlie(),
+    )
+    .expect("Should swap");
+    assert_eq!(
+        result,
+        (expected_amount_in, expected_fee),
+        "Quote should account for the transfer fee"
+    );
+    assert_eq!(
+        psp22_utils::balance_of(&mut session, taxed_token, bob()),
+        bob_balance - expected_amount_in,
+        "Bob should pay the grossed up amount"
+    );
+    assert_eq!(
+        psp22_utils::balance_of(&mut session, token, charlie()),
+        amount_out,
+        "Charlie should receive exact amount"
+    );
+    assert_reserves_match_balances(&mut session, stable_swap, [taxed_token, token]);
+}
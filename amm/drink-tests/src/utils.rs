@@ -40,17 +40,38 @@ pub fn upload_all(session: &mut Session<MinimalRuntime>) {
     session
         .upload_code(stable_pool_contract::upload())
         .expect("Upload stable_pool_contract code");
+    session
+        .upload_code(stable_farm_contract::upload())
+        .expect("Upload stable_farm_contract code");
     session
         .upload_code(mock_sazero_rate_contract::upload())
         .expect("Upload mock_rate_contract code");
     session
         .upload_code(psp22::upload())
         .expect("Upload psp22 code");
+    session
+        .upload_code(wrapped_azero::upload())
+        .expect("Upload wrapped_azero code");
+    session
+        .upload_code(composite_rate_contract::upload())
+        .expect("Upload composite_rate_contract code");
+    session
+        .upload_code(rate_aggregator_contract::upload())
+        .expect("Upload rate_aggregator_contract code");
+    session
+        .upload_code(mock_reentrant_psp22_contract::upload())
+        .expect("Upload mock_reentrant_psp22_contract code");
+    session
+        .upload_code(mock_taxed_psp22_contract::upload())
+        .expect("Upload mock_taxed_psp22_contract code");
 }
 
 pub mod stable_swap {
     use super::*;
-    use stable_pool_contract::{StablePool as _, StablePoolError};
+    use stable_pool_contract::{
+        StablePoolActions as _, StablePoolAdmin as _, StablePoolError, StablePoolQuoter as _,
+        StablePoolView as _,
+    };
 
     pub fn setup(
         session: &mut Session<MinimalRuntime>,
@@ -81,6 +102,39 @@ pub mod stable_swap {
             .into()
     }
 
+    pub fn setup_with_transfer_fees(
+        session: &mut Session<MinimalRuntime>,
+        tokens: Vec<AccountId>,
+        tokens_decimals: Vec<u8>,
+        transfer_fees: Vec<Option<u32>>,
+        init_amp_coef: u128,
+        caller: drink::AccountId32,
+        trade_fee: u32,
+        protocol_fee: u32,
+        fee_receiver: Option<AccountId>,
+    ) -> stable_pool_contract::Instance {
+        let _ = session.set_actor(caller.clone());
+        let external_rates = vec![None; tokens.len()];
+        let instance = stable_pool_contract::Instance::new_with_transfer_fees(
+            tokens,
+            tokens_decimals,
+            external_rates,
+            transfer_fees,
+            init_amp_coef,
+            caller.to_account_id(),
+            trade_fee,
+            protocol_fee,
+            fee_receiver,
+        );
+
+        session
+            .instantiate(instance)
+            .unwrap()
+            .result
+            .to_account_id()
+            .into()
+    }
+
     pub fn add_liquidity(
         session: &mut Session<MinimalRuntime>,
         stable_pool: AccountId,
@@ -103,6 +157,24 @@ pub mod stable_swap {
         )
     }
 
+    pub fn add_liquidity_received(
+        session: &mut Session<MinimalRuntime>,
+        stable_pool: AccountId,
+        caller: drink::AccountId32,
+        min_share_amount: u128,
+        to: AccountId,
+    ) -> Result<(u128, u128), StablePoolError> {
+        _ = session.set_actor(caller);
+        handle_ink_error(
+            session
+                .execute(
+                    stable_pool_contract::Instance::from(stable_pool)
+                        .add_liquidity_received(min_share_amount, to),
+                )
+                .unwrap(),
+        )
+    }
+
     pub fn remove_liquidity_by_amounts(
         session: &mut Session<MinimalRuntime>,
         stable_pool: AccountId,
@@ -223,6 +295,155 @@ pub mod stable_swap {
         )
     }
 
+    pub fn swap_exact_in_native(
+        session: &mut Session<MinimalRuntime>,
+        stable_pool: AccountId,
+        caller: drink::AccountId32,
+        token_out: AccountId,
+        token_in_amount: u128,
+        min_token_out_amount: u128,
+        to: AccountId,
+    ) -> Result<(u128, u128), StablePoolError> {
+        _ = session.set_actor(caller);
+        handle_ink_error(
+            session
+                .execute(
+                    stable_pool_contract::Instance::from(stable_pool)
+                        .swap_exact_in_native(token_out, min_token_out_amount, to)
+                        .with_value(token_in_amount),
+                )
+                .unwrap(),
+        )
+    }
+
+    pub fn swap_exact_in_to_native(
+        session: &mut Session<MinimalRuntime>,
+        stable_pool: AccountId,
+        caller: drink::AccountId32,
+        token_in: AccountId,
+        token_in_amount: u128,
+        min_token_out_amount: u128,
+        to: AccountId,
+    ) -> Result<(u128, u128), StablePoolError> {
+        _ = session.set_actor(caller);
+        handle_ink_error(
+            session
+                .execute(
+                    stable_pool_contract::Instance::from(stable_pool).swap_exact_in_to_native(
+                        token_in,
+                        token_in_amount,
+                        min_token_out_amount,
+                        to,
+                    ),
+                )
+                .unwrap(),
+        )
+    }
+
+    pub fn add_liquidity_native(
+        session: &mut Session<MinimalRuntime>,
+        stable_pool: AccountId,
+        caller: drink::AccountId32,
+        min_share_amount: u128,
+        amounts: Vec<u128>,
+        native_amount: u128,
+        to: AccountId,
+    ) -> Result<(u128, u128), StablePoolError> {
+        _ = session.set_actor(caller);
+        handle_ink_error(
+            session
+                .execute(
+                    stable_pool_contract::Instance::from(stable_pool)
+                        .add_liquidity_native(min_share_amount, amounts, to)
+                        .with_value(native_amount),
+                )
+                .unwrap(),
+        )
+    }
+
+    pub fn remove_liquidity_by_shares_to_native(
+        session: &mut Session<MinimalRuntime>,
+        stable_pool: AccountId,
+        caller: drink::AccountId32,
+        shares_amount: u128,
+        min_amounts: Vec<u128>,
+        to: AccountId,
+    ) -> Result<Vec<u128>, StablePoolError> {
+        _ = session.set_actor(caller);
+        handle_ink_error(
+            session
+                .execute(
+                    stable_pool_contract::Instance::from(stable_pool)
+                        .remove_liquidity_by_shares_to_native(shares_amount, min_amounts, to),
+                )
+                .unwrap(),
+        )
+    }
+
+    pub fn set_wazero(
+        session: &mut Session<MinimalRuntime>,
+        stable_pool: AccountId,
+        caller: drink::AccountId32,
+        wazero: Option<AccountId>,
+    ) -> Result<(), StablePoolError> {
+        _ = session.set_actor(caller);
+        handle_ink_error(
+            session
+                .execute(stable_pool_contract::Instance::from(stable_pool).set_wazero(wazero))
+                .unwrap(),
+        )
+    }
+
+    pub fn ramp_amp_coef(
+        session: &mut Session<MinimalRuntime>,
+        stable_pool: AccountId,
+        caller: drink::AccountId32,
+        future_amp_coef: u128,
+        future_time: u64,
+    ) -> Result<(), StablePoolError> {
+        _ = session.set_actor(caller);
+        handle_ink_error(
+            session
+                .execute(
+                    stable_pool_contract::Instance::from(stable_pool)
+                        .ramp_amp_coef(future_amp_coef, future_time),
+                )
+                .unwrap(),
+        )
+    }
+
+    pub fn ramp_amp_coef_precise(
+        session: &mut Session<MinimalRuntime>,
+        stable_pool: AccountId,
+        caller: drink::AccountId32,
+        future_amp_coef: u128,
+        future_time: u64,
+    ) -> Result<(), StablePoolError> {
+        _ = session.set_actor(caller);
+        handle_ink_error(
+            session
+                .execute(
+                    stable_pool_contract::Instance::from(stable_pool)
+                        .ramp_amp_coef_precise(future_amp_coef, future_time),
+                )
+                .unwrap(),
+        )
+    }
+
+    pub fn set_auto_amp(
+        session: &mut Session<MinimalRuntime>,
+        stable_pool: AccountId,
+        caller: drink::AccountId32,
+        config: Option<stable_pool_contract::AutoAmpConfig>,
+    ) -> Result<(), StablePoolError> {
+        _ = session.set_actor(caller);
+        handle_ink_error(
+            session
+                .execute(stable_pool_contract::Instance::from(stable_pool).set_auto_amp(config))
+                .unwrap(),
+        )
+    }
+
     pub fn reserves(session: &mut Session<MinimalRuntime>, stable_pool: AccountId) -> Vec<u128> {
         handle_ink_error(
             session
@@ -231,6 +452,14 @@ pub mod stable_swap {
         )
     }
 
+    pub fn tokens_decimals(session: &mut Session<MinimalRuntime>, stable_pool: AccountId) -> Vec<u8> {
+        handle_ink_error(
+            session
+                .query(stable_pool_contract::Instance::from(stable_pool).tokens_decimals())
+                .unwrap(),
+        )
+    }
+
     pub fn amp_coef(session: &mut Session<MinimalRuntime>, stable_pool: AccountId) -> Result<u128, StablePoolError> {
         handle_ink_error(
             session
@@ -239,6 +468,39 @@ pub mod stable_swap {
         )
     }
 
+    pub fn amp_coef_precise(
+        session: &mut Session<MinimalRuntime>,
+        stable_pool: AccountId,
+    ) -> Result<u128, StablePoolError> {
+        handle_ink_error(
+            session
+                .query(stable_pool_contract::Instance::from(stable_pool).amp_coef_precise())
+                .unwrap(),
+        )
+    }
+
+    pub fn future_amp_coef(
+        session: &mut Session<MinimalRuntime>,
+        stable_pool: AccountId,
+    ) -> Option<(u128, u64)> {
+        handle_ink_error(
+            session
+                .query(stable_pool_contract::Instance::from(stable_pool).future_amp_coef())
+                .unwrap(),
+        )
+    }
+
+    pub fn future_amp_coef_precise(
+        session: &mut Session<MinimalRuntime>,
+        stable_pool: AccountId,
+    ) -> Option<(u128, u64)> {
+        handle_ink_error(
+            session
+                .query(stable_pool_contract::Instance::from(stable_pool).future_amp_coef_precise())
+                .unwrap(),
+        )
+    }
+
     pub fn fees(session: &mut Session<MinimalRuntime>, stable_pool: AccountId) -> (u32, u32) {
         handle_ink_error(
             session
@@ -255,6 +517,39 @@ pub mod stable_swap {
         )
     }
 
+    pub fn try_token_rates(
+        session: &mut Session<MinimalRuntime>,
+        stable_pool: AccountId,
+    ) -> Result<Vec<u128>, StablePoolError> {
+        handle_ink_error(
+            session
+                .query(stable_pool_contract::Instance::from(stable_pool).try_token_rates())
+                .unwrap(),
+        )
+    }
+
+    pub fn cached_token_rates(
+        session: &mut Session<MinimalRuntime>,
+        stable_pool: AccountId,
+    ) -> Vec<(u128, Option<u32>)> {
+        handle_ink_error(
+            session
+                .query(stable_pool_contract::Instance::from(stable_pool).cached_token_rates())
+                .unwrap(),
+        )
+    }
+
+    pub fn get_pool_state(
+        session: &mut Session<MinimalRuntime>,
+        stable_pool: AccountId,
+    ) -> Result<stable_pool_contract::PoolState, StablePoolError> {
+        handle_ink_error(
+            session
+                .query(stable_pool_contract::Instance::from(stable_pool).get_pool_state())
+                .unwrap(),
+        )
+    }
+
     pub fn tokens(session: &mut Session<MinimalRuntime>, stable_pool: AccountId) -> Vec<AccountId> {
         handle_ink_error(
             session
@@ -263,6 +558,86 @@ pub mod stable_swap {
         )
     }
 
+    pub fn get_swap_amount_out(
+        session: &mut Session<MinimalRuntime>,
+        stable_pool: AccountId,
+        token_in: AccountId,
+        token_out: AccountId,
+        token_in_amount: u128,
+    ) -> Result<(u128, u128), StablePoolError> {
+        handle_ink_error(
+            session
+                .query(
+                    stable_pool_contract::Instance::from(stable_pool).get_swap_amount_out(
+                        token_in,
+                        token_out,
+                        token_in_amount,
+                    ),
+                )
+                .unwrap(),
+        )
+    }
+
+    pub fn get_swap_amount_in(
+        session: &mut Session<MinimalRuntime>,
+        stable_pool: AccountId,
+        token_in: AccountId,
+        token_out: AccountId,
+        token_out_amount: u128,
+    ) -> Result<(u128, u128), StablePoolError> {
+        handle_ink_error(
+            session
+                .query(
+                    stable_pool_contract::Instance::from(stable_pool).get_swap_amount_in(
+                        token_in,
+                        token_out,
+                        token_out_amount,
+                    ),
+                )
+                .unwrap(),
+        )
+    }
+
+    pub fn quote_swap_amount_out(
+        session: &mut Session<MinimalRuntime>,
+        stable_pool: AccountId,
+        token_in: AccountId,
+        token_out: AccountId,
+        token_in_amount: u128,
+    ) -> Result<(u128, u128), StablePoolError> {
+        handle_ink_error(
+            session
+                .query(
+                    stable_pool_contract::Instance::from(stable_pool).quote_swap_amount_out(
+                        token_in,
+                        token_out,
+                        token_in_amount,
+                    ),
+                )
+                .unwrap(),
+        )
+    }
+
+    pub fn quote_swap_amount_in(
+        session: &mut Session<MinimalRuntime>,
+        stable_pool: AccountId,
+        token_in: AccountId,
+        token_out: AccountId,
+        token_out_amount: u128,
+    ) -> Result<(u128, u128), StablePoolError> {
+        handle_ink_error(
+            session
+                .query(
+                    stable_pool_contract::Instance::from(stable_pool).quote_swap_amount_in(
+                        token_in,
+                        token_out,
+                        token_out_amount,
+                    ),
+                )
+                .unwrap(),
+        )
+    }
+
     pub fn get_amounts_for_liquidity_burn(
         session: &mut Session<MinimalRuntime>,
         stable_pool: AccountId,
@@ -324,6 +699,146 @@ pub mod stable_swap {
     }
 }
 
+pub mod stable_farm {
+    use super::*;
+    use stable_farm_contract::{StableFarm as _, StableFarmError};
+
+    pub fn setup(
+        session: &mut Session<MinimalRuntime>,
+        pool_id: AccountId,
+        reward_tokens: Vec<AccountId>,
+        caller: drink::AccountId32,
+    ) -> AccountId {
+        let _ = session.set_actor(caller.clone());
+        let instance =
+            stable_farm_contract::Instance::new(pool_id, reward_tokens, caller.to_account_id());
+
+        session
+            .instantiate(instance)
+            .unwrap()
+            .result
+            .to_account_id()
+            .into()
+    }
+
+    pub fn deposit(
+        session: &mut Session<MinimalRuntime>,
+        farm: AccountId,
+        caller: drink::AccountId32,
+        amount: u128,
+    ) -> Result<(), StableFarmError> {
+        _ = session.set_actor(caller);
+        handle_ink_error(
+            session
+                .execute(stable_farm_contract::Instance::from(farm).deposit(amount))
+                .unwrap(),
+        )
+    }
+
+    pub fn withdraw(
+        session: &mut Session<MinimalRuntime>,
+        farm: AccountId,
+        caller: drink::AccountId32,
+        amount: u128,
+    ) -> Result<(), StableFarmError> {
+        _ = session.set_actor(caller);
+        handle_ink_error(
+            session
+                .execute(stable_farm_contract::Instance::from(farm).withdraw(amount))
+                .unwrap(),
+        )
+    }
+
+    pub fn claim(
+        session: &mut Session<MinimalRuntime>,
+        farm: AccountId,
+        caller: drink::AccountId32,
+    ) -> Result<Vec<u128>, StableFarmError> {
+        _ = session.set_actor(caller);
+        handle_ink_error(
+            session
+                .execute(stable_farm_contract::Instance::from(farm).claim())
+                .unwrap(),
+        )
+    }
+
+    pub fn emergency_withdraw(
+        session: &mut Session<MinimalRuntime>,
+        farm: AccountId,
+        caller: drink::AccountId32,
+    ) -> Result<u128, StableFarmError> {
+        _ = session.set_actor(caller);
+        handle_ink_error(
+            session
+                .execute(stable_farm_contract::Instance::from(farm).emergency_withdraw())
+                .unwrap(),
+        )
+    }
+
+    pub fn start_rewards(
+        session: &mut Session<MinimalRuntime>,
+        farm: AccountId,
+        caller: drink::AccountId32,
+        start: u64,
+        end: u64,
+        rewards: Vec<u128>,
+    ) -> Result<(), StableFarmError> {
+        _ = session.set_actor(caller);
+        handle_ink_error(
+            session
+                .execute(
+                    stable_farm_contract::Instance::from(farm).start_rewards(start, end, rewards),
+                )
+                .unwrap(),
+        )
+    }
+
+    pub fn stop_rewards(
+        session: &mut Session<MinimalRuntime>,
+        farm: AccountId,
+        caller: drink::AccountId32,
+    ) -> Result<Vec<u128>, StableFarmError> {
+        _ = session.set_actor(caller);
+        handle_ink_error(
+            session
+                .execute(stable_farm_contract::Instance::from(farm).stop_rewards())
+                .unwrap(),
+        )
+    }
+
+    pub fn claimable(
+        session: &mut Session<MinimalRuntime>,
+        farm: AccountId,
+        account: AccountId,
+    ) -> Result<Vec<u128>, StableFarmError> {
+        handle_ink_error(
+            session
+                .query(stable_farm_contract::Instance::from(farm).claimable(account))
+                .unwrap(),
+        )
+    }
+
+    pub fn shares_of(
+        session: &mut Session<MinimalRuntime>,
+        farm: AccountId,
+        account: AccountId,
+    ) -> u128 {
+        handle_ink_error(
+            session
+                .query(stable_farm_contract::Instance::from(farm).shares_of(account))
+                .unwrap(),
+        )
+    }
+
+    pub fn total_shares(session: &mut Session<MinimalRuntime>, farm: AccountId) -> u128 {
+        handle_ink_error(
+            session
+                .query(stable_farm_contract::Instance::from(farm).total_shares())
+                .unwrap(),
+        )
+    }
+}
+
 pub mod psp22_utils {
     use super::*;
     use psp22::{Instance as PSP22, PSP22Metadata as _, PSP22 as _};
@@ -423,6 +938,19 @@ pub mod psp22_utils {
         )
     }
 
+    pub fn allowance(
+        session: &mut Session<MinimalRuntime>,
+        token: AccountId,
+        owner: AccountId,
+        spender: AccountId,
+    ) -> u128 {
+        handle_ink_error(
+            session
+                .query(PSP22::allowance(&token.into(), owner, spender))
+                .unwrap(),
+        )
+    }
+
     pub fn total_supply(session: &mut Session<MinimalRuntime>, token: AccountId) -> u128 {
         handle_ink_error(session.query(PSP22::total_supply(&token.into())).unwrap())
     }
@@ -432,6 +960,100 @@ pub mod psp22_utils {
     }
 }
 
+pub mod wazero_utils {
+    use super::*;
+
+    /// Uploads and creates a wAZERO instance.
+    pub fn setup(session: &mut Session<MinimalRuntime>, caller: drink::AccountId32) -> AccountId {
+        let _code_hash = session.upload_code(wrapped_azero::upload()).unwrap();
+
+        let _ = session.set_actor(caller);
+
+        session
+            .instantiate(wrapped_azero::Instance::new())
+            .unwrap()
+            .result
+            .to_account_id()
+            .into()
+    }
+}
+
+pub mod reentrant_psp22_utils {
+    use super::*;
+
+    pub fn setup(
+        session: &mut Session<MinimalRuntime>,
+        decimals: u8,
+        init_supply: u128,
+        caller: drink::AccountId32,
+    ) -> AccountId {
+        let _ = session.set_actor(caller);
+
+        session
+            .instantiate(mock_reentrant_psp22_contract::Instance::new(
+                init_supply,
+                decimals,
+            ))
+            .unwrap()
+            .result
+            .to_account_id()
+            .into()
+    }
+
+    pub fn set_pool(
+        session: &mut Session<MinimalRuntime>,
+        token: AccountId,
+        pool: Option<AccountId>,
+        caller: drink::AccountId32,
+    ) {
+        let _ = session.set_actor(caller);
+
+        handle_ink_error(
+            session
+                .execute(mock_reentrant_psp22_contract::Instance::from(token).set_pool(pool))
+                .unwrap(),
+        )
+    }
+
+    /// Returns a tuple of (attempted, blocked) reentrant calls.
+    pub fn reentry_stats(session: &mut Session<MinimalRuntime>, token: AccountId) -> (u32, u32) {
+        handle_ink_error(
+            session
+                .query(mock_reentrant_psp22_contract::Instance::from(token).reentry_stats())
+                .unwrap(),
+        )
+    }
+}
+
+pub mod taxed_psp22_utils {
+    use super::*;
+
+    pub fn setup(
+        session: &mut Session<MinimalRuntime>,
+        decimals: u8,
+        init_supply: u128,
+        tax: u32,
+        caller: drink::AccountId32,
+    ) -> AccountId {
+        let _ = session.set_actor(caller);
+
+        session
+            .instantiate(mock_taxed_psp22_contract::Instance::new(
+                init_supply,
+                decimals,
+                tax,
+            ))
+            .unwrap()
+            .result
+            .to_account_id()
+            .into()
+    }
+}
+
+pub fn native_balance_of(session: &mut Session<MinimalRuntime>, account: AccountId32) -> u128 {
+    session.sandbox().free_balance(&account)
+}
+
 pub fn get_timestamp(session: &mut Session<MinimalRuntime>) -> u64 {
     session.sandbox().get_timestamp()
 }
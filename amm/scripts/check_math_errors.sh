@@ -0,0 +1,87 @@
+#!/bin/bash
+
+# Checks that each `MathError` tag created in the sources is registered and used only once.
+# A tag is registered in `MathError::describe` (helpers/math.rs) or in the tags documented
+# by the file creating it, which is how contracts register their own tags.
+# Off-chain mirrors of the contracts must reuse the tags of the mirrored operations.
+
+set -euo pipefail
+
+readonly ROOT_DIR=$( cd -- "$( dirname -- "${BASH_SOURCE[0]}" )/../.." &> /dev/null && pwd )
+
+declare -a SOURCES=(
+    "helpers/math.rs"
+    "helpers/stable_swap_math/mod.rs"
+    "helpers/stable_swap_math/fees.rs"
+    "helpers/stable_swap_math/amp_coef.rs"
+    "helpers/stable_swap_math/auto_amp.rs"
+    "amm/contracts/stable_pool/lib.rs"
+    "amm/contracts/stable_pool/token_rate.rs"
+    "amm/contracts/stable_farm/lib.rs"
+    "amm/contracts/composite_rate/lib.rs"
+    "amm/contracts/rate_aggregator/lib.rs"
+)
+
+declare -a MIRRORS=(
+    "sim/pool.rs"
+)
+
+readonly ERROR_PATTERN='(AddOverflow|CastOverflow|DivByZero|MulOverflow|SubUnderflow|Precision|PowOverflow)\([0-9]+\)'
+
+# Prints `<error> <file>:<line>` for each `MathError` created in the file, outside of its tests.
+function usages() {
+    awk -v path="$1" '
+        /^[ \t]*mod tests/ && previous ~ /^[ \t]*#\[cfg\(test\)\]/ { exit }
+        {
+            previous = $0
+            line = $0
+            while (match(line, /MathError::[A-Za-z]+\([0-9]+\)/)) {
+                print substr(line, RSTART + 11, RLENGTH - 11), path ":" NR
+                line = substr(line, RSTART + RLENGTH)
+            }
+        }' "$ROOT_DIR/$1"
+}
+
+# Prints errors registered in `MathError::describe`.
+function described() {
+    sed -n '/pub fn describe/,/unknown operation/p' "$ROOT_DIR/helpers/math.rs" \
+        | grep -oE "$ERROR_PATTERN"
+}
+
+# Prints errors documented in the doc comments of the file.
+function documented() {
+    grep -E '^[ \t]*///' "$ROOT_DIR/$1" | grep -oE "\`$ERROR_PATTERN\`" | tr -d '`' || true
+}
+
+status=0
+registered=$(described)
+declare -A sites=()
+
+for source in "${SOURCES[@]}"; do
+    source_registered="$registered"$'\n'"$(documented "$source")"
+    while read -r error site; do
+        if ! grep -qxF "$error" <<< "$source_registered"; then
+            echo "$error at $site is not registered"
+            status=1
+        fi
+        if [[ -n "${sites[$error]:-}" ]]; then
+            echo "$error is used at both ${sites[$error]} and $site"
+            status=1
+        fi
+        sites[$error]="$site"
+    done < <(usages "$source")
+done
+
+for mirror in "${MIRRORS[@]}"; do
+    while read -r error site; do
+        if [[ -z "${sites[$error]:-}" ]]; then
+            echo "$error at $site does not mirror a contract operation"
+            status=1
+        fi
+    done < <(usages "$mirror")
+done
+
+if [[ $status -eq 0 ]]; then
+    echo "MathError tags are registered and unique"
+fi
+exit $status
@@ -8,8 +8,14 @@ readonly SCRIPT_DIR=$( cd -- "$( dirname -- "${BASH_SOURCE[0]}" )" &> /dev/null
 
 declare -a CONTRACTS=(
     "stable_pool_contract" 
+    "stable_farm_contract"
+    "composite_rate_contract"
+    "rate_aggregator_contract"
     "psp22"
     "mock_sazero_rate_contract"
+    "mock_reentrant_psp22_contract"
+    "mock_taxed_psp22_contract"
+    "wrapped_azero"
 )
 
 function wrap_contracts() {
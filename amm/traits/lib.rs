@@ -2,11 +2,20 @@
 
 mod ownable2step;
 mod rate_provider;
+mod stable_farm;
 mod stable_pool;
+mod wrapped_azero;
 
 pub type Balance = <ink::env::DefaultEnvironment as ink::env::Environment>::Balance;
 
 pub use amm_helpers::math::MathError;
 pub use ownable2step::{Ownable2Step, Ownable2StepData, Ownable2StepError, Ownable2StepResult};
-pub use rate_provider::RateProvider;
-pub use stable_pool::{StablePool, StablePoolError};
+pub use rate_provider::{RateInfo, RateProvider, RateProviderError, RateProviderV2};
+pub use stable_farm::{StableFarm, StableFarmError};
+#[cfg(feature = "std")]
+pub use stable_pool::decode_dry_run_error;
+pub use stable_pool::{
+    PoolState, StablePoolActions, StablePoolAdmin, StablePoolError, StablePoolQuoter,
+    StablePoolView,
+};
+pub use wrapped_azero::WrappedAZERO;
@@ -53,6 +53,8 @@ pub enum Ownable2StepError {
     ContractNotPendingOwner(AccountId),
     /// The caller tried to accept ownership but the process hasn't been started
     NoPendingOwner,
+    /// The contract is in the middle of another call, e.g. a reentrant one
+    Reentrancy,
 }
 
 pub type Ownable2StepResult<T> = Result<T, Ownable2StepError>;
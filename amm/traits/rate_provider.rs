@@ -1,3 +1,6 @@
+use ink::prelude::string::String;
+use ink::primitives::AccountId;
+
 #[ink::trait_definition]
 pub trait RateProvider {
     // Get "rate" of a particular token with respect to a given base token.
@@ -9,3 +12,36 @@ pub trait RateProvider {
     #[ink(message)]
     fn get_rate(&mut self) -> u128;
 }
+
+/// Rate reported by a `RateProviderV2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct RateInfo {
+    /// Rate with `decimals` decimal places.
+    pub rate: u128,
+    pub decimals: u8,
+    /// Timestamp (in milliseconds) at which the rate was last updated by its source.
+    pub last_updated: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum RateProviderError {
+    /// The rate is not available, e.g. its source has not reported it yet.
+    Unavailable,
+    Custom(String),
+}
+
+#[ink::trait_definition]
+pub trait RateProviderV2 {
+    /// Returns the rate of a token with respect to the base token (see `rate_base_token`),
+    /// with the precision and update time of the rate.
+    ///
+    /// Unlike `RateProvider::get_rate`, it may fail, e.g. if the rate is not available.
+    #[ink(message)]
+    fn get_rate(&mut self) -> Result<RateInfo, RateProviderError>;
+
+    /// Returns the token in which the rate is denominated (`None` for the native token).
+    #[ink(message)]
+    fn rate_base_token(&self) -> Option<AccountId>;
+}
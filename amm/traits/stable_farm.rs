@@ -0,0 +1,137 @@
+use ink::prelude::vec::Vec;
+use ink::primitives::AccountId;
+use ink::LangError;
+use psp22::PSP22Error;
+
+use crate::{MathError, Ownable2StepError};
+
+/// Farm in which users stake LP tokens of a stable pool and earn rewards
+/// in up to `MAX_REWARD_TOKENS` PSP22 tokens.
+///
+/// Rewards are distributed at a constant rate during reward periods scheduled by the owner,
+/// proportionally to the amount of staked LP tokens.
+#[ink::trait_definition]
+pub trait StableFarm {
+    /// Returns address of the staked LP token (the stable pool).
+    #[ink(message)]
+    fn pool_id(&self) -> AccountId;
+
+    /// Returns list of reward tokens.
+    #[ink(message)]
+    fn reward_tokens(&self) -> Vec<AccountId>;
+
+    /// Returns total amount of staked LP tokens.
+    #[ink(message)]
+    fn total_shares(&self) -> u128;
+
+    /// Returns amount of LP tokens staked by `account`.
+    #[ink(message)]
+    fn shares_of(&self, account: AccountId) -> u128;
+
+    /// Returns a tuple of the current (or last) reward period start and end time.
+    /// Returns `None` if no reward period was ever scheduled.
+    #[ink(message)]
+    fn reward_period(&self) -> Option<(u64, u64)>;
+
+    /// Returns rewards distributed per millisecond for each reward token
+    /// during the current (or last) reward period.
+    #[ink(message)]
+    fn reward_rates(&self) -> Vec<u128>;
+
+    /// Returns amounts of reward tokens claimable by `account`.
+    #[ink(message)]
+    fn claimable(&self, account: AccountId) -> Result<Vec<u128>, StableFarmError>;
+
+    /// Stakes `amount` of LP tokens on behalf of the caller.
+    ///
+    /// Caller has to approve the farm to spend `amount` of LP tokens beforehand.
+    #[ink(message)]
+    fn deposit(&mut self, amount: u128) -> Result<(), StableFarmError>;
+
+    /// Unstakes `amount` of caller's LP tokens and transfers them to the caller.
+    ///
+    /// Rewards accrued so far are kept and can be claimed later.
+    #[ink(message)]
+    fn withdraw(&mut self, amount: u128) -> Result<(), StableFarmError>;
+
+    /// Transfers all rewards accrued by the caller.
+    ///
+    /// Returns claimed amounts of each reward token.
+    #[ink(message)]
+    fn claim(&mut self) -> Result<Vec<u128>, StableFarmError>;
+
+    /// Unstakes all of caller's LP tokens without claiming rewards.
+    /// Unclaimed rewards are forfeited.
+    ///
+    /// Meant to be used when claiming is not possible, e.g. a reward token is blocking transfers
+    /// or the rewards overflow.
+    ///
+    /// Returns amount of unstaked LP tokens.
+    #[ink(message)]
+    fn emergency_withdraw(&mut self) -> Result<u128, StableFarmError>;
+
+    // --- OWNER RESTRICTED FUNCTIONS --- //
+
+    /// Schedules a reward period from `start` to `end` distributing `rewards` of each reward token.
+    /// Transfers the rewards from the caller, who has to approve the farm beforehand.
+    ///
+    /// Reward rates are rounded down, so the actually transferred amounts can be slightly lower than `rewards`.
+    /// Can only be called when there is no active or scheduled reward period.
+    #[ink(message)]
+    fn start_rewards(
+        &mut self,
+        start: u64,
+        end: u64,
+        rewards: Vec<u128>,
+    ) -> Result<(), StableFarmError>;
+
+    /// Stops the current reward period (or cancels a scheduled one)
+    /// and transfers undistributed rewards to the caller.
+    ///
+    /// Returns amounts of returned reward tokens.
+    #[ink(message)]
+    fn stop_rewards(&mut self) -> Result<Vec<u128>, StableFarmError>;
+}
+
+#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum StableFarmError {
+    Ownable2StepError(Ownable2StepError),
+    MathError(MathError),
+    PSP22Error(PSP22Error),
+    LangError(LangError),
+    TooManyRewardTokens,
+    DuplicateRewardToken,
+    IncorrectAmountsCount,
+    InvalidRewardPeriod,
+    RewardPeriodActive,
+    NoActiveRewardPeriod,
+    ZeroRewardRate,
+    ZeroAmount,
+    InsufficientShares,
+    PoolTokenAsReward,
+}
+
+impl From<PSP22Error> for StableFarmError {
+    fn from(error: PSP22Error) -> Self {
+        StableFarmError::PSP22Error(error)
+    }
+}
+
+impl From<LangError> for StableFarmError {
+    fn from(error: LangError) -> Self {
+        StableFarmError::LangError(error)
+    }
+}
+
+impl From<MathError> for StableFarmError {
+    fn from(error: MathError) -> Self {
+        StableFarmError::MathError(error)
+    }
+}
+
+impl From<Ownable2StepError> for StableFarmError {
+    fn from(error: Ownable2StepError) -> Self {
+        StableFarmError::Ownable2StepError(error)
+    }
+}
@@ -1,48 +1,117 @@
+use amm_helpers::stable_swap_math::{
+    amp_coef::{AmpCoefError, RampShape},
+    auto_amp::AutoAmpConfig,
+    fees::FeeScheduleError,
+};
+use core::fmt;
 use ink::prelude::vec::Vec;
 use ink::primitives::AccountId;
 use ink::LangError;
 use psp22::PSP22Error;
 
-use crate::{MathError, Ownable2StepError};
+use crate::{MathError, Ownable2StepError, RateProviderError};
 
+/// Getters and quotes of a stable pool.
+///
+/// Messages of `StablePoolView`, `StablePoolActions` and `StablePoolAdmin` keep the selectors
+/// they had in the former `StablePool` trait, so the pool ABI is unchanged.
 #[ink::trait_definition]
-pub trait StablePool {
+pub trait StablePoolView {
     /// Returns list of tokens in the pool.
-    #[ink(message)]
+    #[ink(message, selector = 0xA3262FF8)]
     fn tokens(&self) -> Vec<AccountId>;
 
     /// Returns list of tokens reserves.
-    #[ink(message)]
+    #[ink(message, selector = 0xD936841F)]
     fn reserves(&self) -> Vec<u128>;
 
-    /// Returns current value of amplification coefficient.
-    #[ink(message)]
+    /// Returns list of tokens decimals.
+    #[ink(message, selector = 0x69930E6B)]
+    fn tokens_decimals(&self) -> Vec<u8>;
+
+    /// Returns current value of amplification coefficient rounded down to an integer.
+    #[ink(message, selector = 0x39A70F1C)]
     fn amp_coef(&self) -> Result<u128, StablePoolError>;
 
+    /// Returns current value of amplification coefficient with `A_PRECISION` (100).
+    #[ink(message, selector = 0x5010B114)]
+    fn amp_coef_precise(&self) -> Result<u128, StablePoolError>;
+
     /// Returns a tuple of the future amplification coefficient and the ramping end time.
     /// Returns `None` if the amplification coefficient is not in ramping period.
-    #[ink(message)]
+    #[ink(message, selector = 0x763629E5)]
     fn future_amp_coef(&self) -> Option<(u128, u64)>;
 
+    /// Same as `future_amp_coef`, with the amplification coefficient with `A_PRECISION` (100).
+    #[ink(message, selector = 0xB36BAE43)]
+    fn future_amp_coef_precise(&self) -> Option<(u128, u64)>;
+
+    /// Returns parameters of the automatic amplification coefficient adjustment and the EMA
+    /// of the imbalance of reserves (in 1e9 precision) as of the last update.
+    /// Returns `None` if the adjustment is disabled.
+    #[ink(message, selector = 0x7C2A8EC9)]
+    fn auto_amp(&self) -> Option<(AutoAmpConfig, u32)>;
+
     /// Returns current trade and protocol fees in 1e9 precision.
-    #[ink(message)]
+    #[ink(message, selector = 0xBCB8FAB0)]
     fn fees(&self) -> (u32, u32);
 
+    /// Returns a tuple of the future trade fee, protocol fee and the ramping end time.
+    /// Returns `None` if the fees are not in ramping period.
+    #[ink(message, selector = 0xC8A3A36B)]
+    fn future_fees(&self) -> Option<(u32, u32, u64)>;
+
     /// Protocol fees receiver (if any)
-    #[ink(message)]
+    #[ink(message, selector = 0xE8BE1DBD)]
     fn fee_receiver(&self) -> Option<AccountId>;
 
+    /// wAZERO token used by native token entry points (if any)
+    #[ink(message, selector = 0x89CA7D7E)]
+    fn wazero(&self) -> Option<AccountId>;
+
     /// Updates cached token rates if there was a new block since the previous update.
     ///
     /// Returns current tokens rates with precision of 12 decimal places.
-    #[ink(message)]
+    /// Panics if a `RateProviderV2` fails or its rate is stale, see `try_token_rates`.
+    #[ink(message, selector = 0x7C0A70B4)]
     fn token_rates(&mut self) -> Vec<u128>;
 
-    /// Returns list of RateProvider address for each token.
+    /// Same as `token_rates`, but returns an error if a `RateProviderV2` fails
+    /// or its rate is stale.
+    #[ink(message, selector = 0x9782CBE9)]
+    fn try_token_rates(&mut self) -> Result<Vec<u128>, StablePoolError>;
+
+    /// Returns list of RateProvider (or RateProviderV2) address for each token.
     /// If the rate is constant, returns None.
-    #[ink(message)]
+    #[ink(message, selector = 0x8CD46D2F)]
     fn token_rates_providers(&self) -> Vec<Option<AccountId>>;
 
+    /// Returns current tokens rates (as `token_rates`) without updating the cached rates.
+    ///
+    /// Queries rate providers unless their rate was cached in the current block.
+    #[ink(message, selector = 0x19CCAF8C)]
+    fn quote_token_rates(&self) -> Result<Vec<u128>, StablePoolError>;
+
+    /// Returns cached tokens rates, each with the block number of its last update.
+    /// The block number is `None` for constant rates.
+    ///
+    /// Does not query rate providers. The rate is 0 if it was never cached.
+    #[ink(message, selector = 0xB0BBDB64)]
+    fn cached_token_rates(&self) -> Vec<(u128, Option<u32>)>;
+
+    /// Returns the state of the pool at the current block, which would otherwise take multiple
+    /// calls to read.
+    ///
+    /// Uses current token rates (as `quote_token_rates`) without updating the cached rates.
+    /// Fails during an operation of the pool, i.e. on reentry.
+    #[ink(message, selector = 0xDEEE6EAF)]
+    fn get_pool_state(&self) -> Result<PoolState, StablePoolError>;
+
+    /// Returns list of expected fees charged by tokens on transfer, in 1e9 precision.
+    /// `None` for tokens which do not charge a fee on transfer.
+    #[ink(message, selector = 0x3E8807D9)]
+    fn transfer_fees(&self) -> Vec<Option<u32>>;
+
     /// Calculate swap amount of `token_out`
     /// given `token_in amount`.
     ///
@@ -50,7 +119,8 @@ pub trait StablePool {
     ///
     /// Returns a tuple of (amount out, fee)
     /// NOTE: fee is applied on `token_out`
-    #[ink(message)]
+    /// NOTE: expected transfer fee of `token_in` (if any) is deducted from `token_in_amount`
+    #[ink(message, selector = 0x7A51B7B0)]
     fn get_swap_amount_out(
         &mut self,
         token_in: AccountId,
@@ -65,7 +135,8 @@ pub trait StablePool {
     ///
     /// Returns a tuple of (amount in, fee)
     /// NOTE: fee is applied on `token_out`
-    #[ink(message)]
+    /// NOTE: amount in includes expected transfer fee of `token_in` (if any)
+    #[ink(message, selector = 0x7183EAA2)]
     fn get_swap_amount_in(
         &mut self,
         token_in: AccountId,
@@ -73,25 +144,78 @@ pub trait StablePool {
         token_out_amount: u128,
     ) -> Result<(u128, u128), StablePoolError>;
 
+    /// Returns the spot price of `token_in` in `token_out` (with `RATE_PRECISION`),
+    /// i.e. the comparable amount of `token_out` paid for an infinitesimal comparable
+    /// amount of `token_in`, without fees.
+    ///
+    /// Updates cached token rates if there was a new block since the previous update.
+    #[ink(message, selector = 0x68368979)]
+    fn get_spot_price(
+        &mut self,
+        token_in: AccountId,
+        token_out: AccountId,
+    ) -> Result<u128, StablePoolError>;
+
+    /// Calculate swap amount of `token_in` which lowers the spot price
+    /// of `token_in` in `token_out` (see `get_spot_price`) to `target_price`.
+    ///
+    /// Updates cached token rates if there was a new block since the previous update.
+    ///
+    /// Returns zero if the price is already at or below `target_price`.
+    /// NOTE: trade fee staying in the pool is accounted for in the resulting price
+    /// NOTE: amount in includes expected transfer fee of `token_in` (if any)
+    #[ink(message, selector = 0x1FC81FFB)]
+    fn get_swap_amount_in_for_price(
+        &mut self,
+        token_in: AccountId,
+        token_out: AccountId,
+        target_price: u128,
+    ) -> Result<u128, StablePoolError>;
+
+    /// Calculate swap amount of `token_in` which raises the ratio of comparable reserves
+    /// of `token_in` to `token_out` to `target_ratio` (with `RATE_PRECISION`).
+    ///
+    /// Updates cached token rates if there was a new block since the previous update.
+    ///
+    /// Returns zero if the ratio is already at or above `target_ratio`.
+    /// NOTE: amount in includes expected transfer fee of `token_in` (if any)
+    #[ink(message, selector = 0x546A5DC1)]
+    fn get_swap_amount_in_for_ratio(
+        &mut self,
+        token_in: AccountId,
+        token_out: AccountId,
+        target_ratio: u128,
+    ) -> Result<u128, StablePoolError>;
+
     /// Calculate how many lp tokens will be minted
     /// given deposit `amounts`.
     ///
     /// Updates cached token rates if there was a new block since the previous update.
     ///
     /// Returns a tuple of (lpt amount, fee)
-    #[ink(message)]
+    /// NOTE: expected transfer fees (if any) are deducted from `amounts`
+    #[ink(message, selector = 0xE6934218)]
     fn get_mint_liquidity_for_amounts(
         &mut self,
         amounts: Vec<u128>,
     ) -> Result<(u128, u128), StablePoolError>;
 
+    /// Read-only version of `get_mint_liquidity_for_amounts`.
+    ///
+    /// Uses current token rates (as `quote_token_rates`) without updating the cached rates.
+    #[ink(message, selector = 0x2C2A0AB1)]
+    fn quote_mint_liquidity_for_amounts(
+        &self,
+        amounts: Vec<u128>,
+    ) -> Result<(u128, u128), StablePoolError>;
+
     /// Calculate ideal deposit amounts required
     /// to mint `liquidity` amount of lp tokens
     ///
     /// Updates cached token rates if there was a new block since the previous update.
     ///
     /// Returns required deposit amounts
-    #[ink(message)]
+    #[ink(message, selector = 0xF3B182F3)]
     fn get_amounts_for_liquidity_mint(
         &mut self,
         liquidity: u128,
@@ -103,7 +227,7 @@ pub trait StablePool {
     /// Updates cached token rates if there was a new block since the previous update.
     ///
     /// Returns a tuple of (lpt amount, fee part)
-    #[ink(message)]
+    #[ink(message, selector = 0x828A2955)]
     fn get_burn_liquidity_for_amounts(
         &mut self,
         amounts: Vec<u128>,
@@ -115,19 +239,25 @@ pub trait StablePool {
     /// Updates cached token rates if there was a new block since the previous update.
     ///
     /// Returns withdraw amounts
-    #[ink(message)]
+    #[ink(message, selector = 0x64A4788C)]
     fn get_amounts_for_liquidity_burn(
         &mut self,
         liquidity: u128,
     ) -> Result<Vec<u128>, StablePoolError>;
+}
 
+/// Liquidity provision and swaps.
+#[ink::trait_definition]
+pub trait StablePoolActions {
     /// Deposit `amounts` of tokens to receive lpt tokens to `to` account.
     /// Caller must allow enough spending allowance of underlying tokens
     /// for this contract.
     /// Returns an error if the minted LP tokens amount is less
     /// than `min_share_amount`.
+    /// On the first deposit `MINIMUM_LIQUIDITY` of lpt tokens is locked forever
+    /// and the deposit's invariant must be at least `MIN_INITIAL_D`.
     /// Returns a tuple of (minted lpt amount, fee)
-    #[ink(message)]
+    #[ink(message, selector = 0xE00CA259)]
     fn add_liquidity(
         &mut self,
         min_share_amount: u128,
@@ -135,10 +265,22 @@ pub trait StablePool {
         to: AccountId,
     ) -> Result<(u128, u128), StablePoolError>;
 
+    /// Deposit excess reserve balances of all tokens
+    /// to receive lpt tokens to `to` account.
+    /// Returns an error if the minted LP tokens amount is less
+    /// than `min_share_amount`.
+    /// Returns a tuple of (minted lpt amount, fee)
+    #[ink(message, selector = 0xDAF403F7)]
+    fn add_liquidity_received(
+        &mut self,
+        min_share_amount: u128,
+        to: AccountId,
+    ) -> Result<(u128, u128), StablePoolError>;
+
     /// Burns LP tokens and withdraws underlying tokens to `to` account
     /// in imbalanced `amounts`.
     /// Returns a tuple of (burned lpt amount, fee part)
-    #[ink(message)]
+    #[ink(message, selector = 0x5B67FA07)]
     fn remove_liquidity_by_amounts(
         &mut self,
         max_share_amount: u128,
@@ -149,7 +291,7 @@ pub trait StablePool {
     /// Burns lp tokens and withdraws underlying tokens in balanced amounts to `to` account.
     /// Fails if any of the amounts received is less than in `min_amounts`.
     /// Returns withdrawal amounts
-    #[ink(message)]
+    #[ink(message, selector = 0x4E80233C)]
     fn remove_liquidity_by_shares(
         &mut self,
         shares: u128,
@@ -165,7 +307,7 @@ pub trait StablePool {
     /// `min_token_out_amount`.
     /// NOTE: Fee is applied on `token_out`.
     /// Returns a tuple of (token out amount, fee amount)
-    #[ink(message)]
+    #[ink(message, selector = 0x0D9AFC46)]
     fn swap_exact_in(
         &mut self,
         token_in: AccountId,
@@ -183,7 +325,7 @@ pub trait StablePool {
     /// `max_token_in_amount`  to get `token_out_amount`.
     /// NOTE: Fee is applied on `token_out`.
     /// Returns a tuple of (token in amount, fee amount)
-    #[ink(message)]
+    #[ink(message, selector = 0xDAEC3E2A)]
     fn swap_exact_out(
         &mut self,
         token_in: AccountId,
@@ -196,7 +338,7 @@ pub trait StablePool {
     /// Swaps excess reserve balance of `token_in` to `token_out`.
     /// Swapped tokens are transferred to the `to` account.
     /// Returns a tuple of (token out amount, fee amount)
-    #[ink(message)]
+    #[ink(message, selector = 0xC57CD9BA)]
     fn swap_received(
         &mut self,
         token_in: AccountId,
@@ -205,31 +347,235 @@ pub trait StablePool {
         to: AccountId,
     ) -> Result<(u128, u128), StablePoolError>;
 
-    // --- OWNER RESTRICTED FUNCTIONS --- //
+    /// Wraps the transferred native value to wAZERO and swaps it to `token_out`.
+    /// Swapped tokens are transferred to the `to` account.
+    /// Returns an error if wAZERO is not one of the pool tokens or
+    /// if swapped `token_out` amount is less than `min_token_out_amount`.
+    /// NOTE: Fee is applied on `token_out`.
+    /// Returns a tuple of (token out amount, fee amount)
+    #[ink(message, payable, selector = 0xF3A6D0C4)]
+    fn swap_exact_in_native(
+        &mut self,
+        token_out: AccountId,
+        min_token_out_amount: u128,
+        to: AccountId,
+    ) -> Result<(u128, u128), StablePoolError>;
 
-    #[ink(message)]
+    /// Swaps token_in to wAZERO and unwraps it.
+    /// Native tokens are transferred to the `to` account.
+    /// Caller account must allow enough spending allowance of `token_in`
+    /// for this contract.
+    /// Returns an error if wAZERO is not one of the pool tokens or
+    /// if swapped amount is less than `min_token_out_amount`.
+    /// NOTE: Fee is applied on wAZERO.
+    /// Returns a tuple of (native amount out, fee amount)
+    #[ink(message, selector = 0x01A10891)]
+    fn swap_exact_in_to_native(
+        &mut self,
+        token_in: AccountId,
+        token_in_amount: u128,
+        min_token_out_amount: u128,
+        to: AccountId,
+    ) -> Result<(u128, u128), StablePoolError>;
+
+    /// Deposit `amounts` of tokens to receive lpt tokens to `to` account.
+    /// The wAZERO amount is taken from the transferred native value, which
+    /// must be equal to the wAZERO entry in `amounts`.
+    /// Caller must allow enough spending allowance of other underlying tokens
+    /// for this contract.
+    /// Returns an error if the minted LP tokens amount is less
+    /// than `min_share_amount`.
+    /// Returns a tuple of (minted lpt amount, fee)
+    #[ink(message, payable, selector = 0xA268966A)]
+    fn add_liquidity_native(
+        &mut self,
+        min_share_amount: u128,
+        amounts: Vec<u128>,
+        to: AccountId,
+    ) -> Result<(u128, u128), StablePoolError>;
+
+    /// Burns lp tokens and withdraws underlying tokens in balanced amounts to `to` account.
+    /// wAZERO is unwrapped and transferred as the native token.
+    /// Fails if any of the amounts received is less than in `min_amounts`.
+    /// Returns withdrawal amounts
+    #[ink(message, selector = 0x919A59BF)]
+    fn remove_liquidity_by_shares_to_native(
+        &mut self,
+        shares: u128,
+        min_amounts: Vec<u128>,
+        to: AccountId,
+    ) -> Result<Vec<u128>, StablePoolError>;
+}
+
+/// Owner restricted messages.
+#[ink::trait_definition]
+pub trait StablePoolAdmin {
+    #[ink(message, selector = 0x58DD0838)]
     fn set_fee_receiver(&mut self, fee_receiver: Option<AccountId>) -> Result<(), StablePoolError>;
 
-    /// Set fees
+    /// Set wAZERO token used by native token entry points.
+    /// Must be one of the pool tokens.
+    #[ink(message, selector = 0x3F500AAC)]
+    fn set_wazero(&mut self, wazero: Option<AccountId>) -> Result<(), StablePoolError>;
+
+    /// Set fees, stopping the fees ramp (if any)
     /// - trade_fee given as an integer with 1e9 precision. The the maximum is 1% (10000000)
     /// - protocol_fee given as an integer with 1e9 precision. The maximum is 50% (500000000)
-    #[ink(message)]
+    #[ink(message, selector = 0xD96D4678)]
     fn set_fees(&mut self, trade_fee: u32, protocol_fee: u32) -> Result<(), StablePoolError>;
 
+    /// Ramp fees linearly to `future_trade_fee` and `future_protocol_fee`. The ramping should finish at `future_time`.
+    /// The fees are restricted as in `set_fees`.
+    #[ink(message, selector = 0x7CB2C0C6)]
+    fn ramp_fees(
+        &mut self,
+        future_trade_fee: u32,
+        future_protocol_fee: u32,
+        future_time: u64,
+    ) -> Result<(), StablePoolError>;
+
+    /// Stop ramping fees.
+    /// If ramping is not in progress, it does not influence the fees.
+    #[ink(message, selector = 0xCBECE956)]
+    fn stop_ramp_fees(&mut self) -> Result<(), StablePoolError>;
+
     /// Ramp amplification coeficient to `future_amp_coef`. The ramping should finish at `future_time`
-    #[ink(message)]
+    #[ink(message, selector = 0x8F940CC7)]
     fn ramp_amp_coef(
         &mut self,
         future_amp_coef: u128,
         future_time: u64,
     ) -> Result<(), StablePoolError>;
 
+    /// Ramp amplification coefficient through `waypoints` of `(amp_coef, time)`, interpolated with `ramp_shape`.
+    /// Each segment between consecutive waypoints is restricted as in `ramp_amp_coef`.
+    #[ink(message, selector = 0x62E9F4FE)]
+    fn ramp_amp_coef_schedule(
+        &mut self,
+        waypoints: Vec<(u128, u64)>,
+        ramp_shape: RampShape,
+    ) -> Result<(), StablePoolError>;
+
+    /// Same as `ramp_amp_coef`, with `future_amp_coef` given with `A_PRECISION` (100).
+    #[ink(message, selector = 0x32016822)]
+    fn ramp_amp_coef_precise(
+        &mut self,
+        future_amp_coef: u128,
+        future_time: u64,
+    ) -> Result<(), StablePoolError>;
+
+    /// Same as `ramp_amp_coef_schedule`, with amplification coefficients of `waypoints`
+    /// given with `A_PRECISION` (100).
+    #[ink(message, selector = 0xEC889108)]
+    fn ramp_amp_coef_schedule_precise(
+        &mut self,
+        waypoints: Vec<(u128, u64)>,
+        ramp_shape: RampShape,
+    ) -> Result<(), StablePoolError>;
+
+    /// Enable (or disable if `None`) the automatic amplification coefficient adjustment.
+    ///
+    /// While enabled, the pool ramps A towards `config.min_amp_coef` when the EMA of the imbalance
+    /// of reserves is above `config.lower_threshold`, and towards `config.max_amp_coef` when it is
+    /// below `config.raise_threshold`, overriding ramps heading the other way.
+    #[ink(message, selector = 0x8AED21C1)]
+    fn set_auto_amp(&mut self, config: Option<AutoAmpConfig>) -> Result<(), StablePoolError>;
+
     /// Stop ramping amplification coefficient.
     /// If ramping is not in progress, it does not influence the A.
-    #[ink(message)]
+    #[ink(message, selector = 0x17E40BBD)]
     fn stop_ramp_amp_coef(&mut self) -> Result<(), StablePoolError>;
 }
 
+/// Read-only swap quotes, which pools of other kinds can implement as well,
+/// so that swaps can be routed through any of them.
+///
+/// Quotes use current token rates (as `StablePoolView::quote_token_rates`) without updating
+/// the cached rates, so they can be called from contexts which do not allow storage writes.
+#[ink::trait_definition]
+pub trait StablePoolQuoter {
+    /// Returns list of tokens which can be swapped in the pool.
+    #[ink(message)]
+    fn pool_tokens(&self) -> Vec<AccountId>;
+
+    /// Calculate swap amount of `token_out` given `token_in_amount`,
+    /// as `StablePoolView::get_swap_amount_out` but without writing to storage.
+    ///
+    /// Returns a tuple of (amount out, fee)
+    /// NOTE: fee is applied on `token_out`
+    #[ink(message, selector = 0x6833D6DF)]
+    fn quote_swap_amount_out(
+        &self,
+        token_in: AccountId,
+        token_out: AccountId,
+        token_in_amount: u128,
+    ) -> Result<(u128, u128), StablePoolError>;
+
+    /// Calculate required swap amount of `token_in` to get `token_out_amount`,
+    /// as `StablePoolView::get_swap_amount_in` but without writing to storage.
+    ///
+    /// Returns a tuple of (amount in, fee)
+    /// NOTE: fee is applied on `token_out`
+    #[ink(message, selector = 0xDE6BA65D)]
+    fn quote_swap_amount_in(
+        &self,
+        token_in: AccountId,
+        token_out: AccountId,
+        token_out_amount: u128,
+    ) -> Result<(u128, u128), StablePoolError>;
+
+    /// Returns the spot price of `token_in` in `token_out` (with `RATE_PRECISION`),
+    /// as `StablePoolView::get_spot_price` but without writing to storage.
+    #[ink(message)]
+    fn quote_spot_price(
+        &self,
+        token_in: AccountId,
+        token_out: AccountId,
+    ) -> Result<u128, StablePoolError>;
+}
+
+/// State of a pool at a block, see `StablePoolView::get_pool_state`.
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct PoolState {
+    pub block_number: u32,
+    /// Block timestamp, in milliseconds.
+    pub timestamp: u64,
+    pub tokens: Vec<AccountId>,
+    pub tokens_decimals: Vec<u8>,
+    /// Precision factor of each token, `10^|TOKEN_TARGET_DECIMALS - decimals|`. Amounts of tokens
+    /// with less than `TOKEN_TARGET_DECIMALS` decimals are scaled up (multiplied) by it,
+    /// and of tokens with more decimals scaled down (divided).
+    pub tokens_precisions: Vec<u128>,
+    pub reserves: Vec<u128>,
+    /// Tokens rates with precision of 12 decimal places.
+    pub token_rates: Vec<u128>,
+    /// RateProvider address of each token (`None` for constant rates).
+    pub token_rates_providers: Vec<Option<AccountId>>,
+    /// Block number at which the cached rate of each token was queried from its RateProvider
+    /// (`None` for constant rates).
+    pub token_rates_updated_at: Vec<Option<u32>>,
+    /// Expected fees charged by tokens on transfer, in 1e9 precision.
+    pub transfer_fees: Vec<Option<u32>>,
+    /// Amplification coefficient rounded down to an integer.
+    pub amp_coef: u128,
+    /// Amplification coefficient with `A_PRECISION` (100).
+    pub amp_coef_precise: u128,
+    /// Future amplification coefficient and the ramping end time (if ramping).
+    pub future_amp_coef: Option<(u128, u64)>,
+    /// Trade fee in 1e9 precision.
+    pub trade_fee: u32,
+    /// Protocol fee in 1e9 precision.
+    pub protocol_fee: u32,
+    /// Future trade fee, protocol fee and the ramping end time (if ramping).
+    pub future_fees: Option<(u32, u32, u64)>,
+    pub fee_receiver: Option<AccountId>,
+    /// Total supply of the LP token.
+    pub total_supply: u128,
+    /// Invariant (D) of the reserves, in comparable amounts with 18 decimal places.
+    pub invariant: u128,
+}
+
 #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub enum StablePoolError {
@@ -241,7 +587,9 @@ pub enum StablePoolError {
     IdenticalTokenId,
     IncorrectAmountsCount,
     ZeroAmounts,
+    /// Superseded by `LiquidityMintedBelowMin`.
     InsufficientLiquidityMinted,
+    /// Superseded by `LiquidityBurnedAboveMax`.
     InsufficientLiquidityBurned,
     InsufficientOutputAmount,
     InsufficientLiquidity,
@@ -253,6 +601,169 @@ pub enum StablePoolError {
     AmpCoefTooHigh,
     AmpCoefRampDurationTooShort,
     AmpCoefChangeTooLarge,
+    NativeTokenNotSupported,
+    IncorrectNativeAmount,
+    NativeTransferFailed,
+    Reentrancy,
+    TokenDecimalsUnavailable(AccountId),
+    TokenDecimalsMismatch(AccountId),
+    InsufficientInitialLiquidity,
+    /// Amount of `token` out is below the minimum `expected` by the caller.
+    OutputAmountBelowMin {
+        token: AccountId,
+        expected: u128,
+        actual: u128,
+    },
+    /// Amount of `token` in exceeds the maximum `expected` by the caller.
+    InputAmountAboveMax {
+        token: AccountId,
+        expected: u128,
+        actual: u128,
+    },
+    /// Amount of `token` credited to the pool is below the `expected` one,
+    /// e.g. the token charges a higher transfer fee than the pool expects.
+    InputAmountCreditedBelowExpected {
+        token: AccountId,
+        expected: u128,
+        actual: u128,
+    },
+    /// Minted liquidity is below the minimum `expected` by the caller.
+    LiquidityMintedBelowMin {
+        expected: u128,
+        actual: u128,
+    },
+    /// Burned liquidity exceeds the maximum `expected` by the caller.
+    LiquidityBurnedAboveMax {
+        expected: u128,
+        actual: u128,
+    },
+    FeeRampDurationTooShort,
+    AmpCoefInvalidSchedule,
+    InvalidAutoAmpConfig,
+    /// Error returned by the `RateProviderV2` of a token.
+    RateProviderError(RateProviderError),
+    /// Rate of the `RateProviderV2` at the given account is older than its max age.
+    StaleRate(AccountId),
+    /// Constant rate (or factor) of a token is zero or too large for the token decimals.
+    InvalidTokenRate,
+}
+
+/// Formats an account as a hex string.
+struct Hex<'a>(&'a AccountId);
+
+impl fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x")?;
+        AsRef::<[u8]>::as_ref(self.0)
+            .iter()
+            .try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
+
+impl fmt::Display for StablePoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use StablePoolError::*;
+        match self {
+            Ownable2StepError(error) => write!(f, "Ownership error: {error:?}"),
+            MathError(error) => write!(f, "Math error {error}"),
+            PSP22Error(error) => write!(f, "Token error: {error:?}"),
+            LangError(error) => write!(f, "Contract call error: {error:?}"),
+            InvalidTokenId(token) => write!(f, "Token {} is not in the pool", Hex(token)),
+            IdenticalTokenId => write!(f, "Tokens of a swap are the same"),
+            IncorrectAmountsCount => write!(f, "Number of amounts differs from number of tokens"),
+            ZeroAmounts => write!(f, "All amounts are zero"),
+            InsufficientLiquidityMinted => write!(f, "Minted liquidity is too low"),
+            InsufficientLiquidityBurned => write!(f, "Burned liquidity is too high"),
+            InsufficientOutputAmount => write!(f, "Output amount is zero"),
+            InsufficientLiquidity => write!(f, "Not enough liquidity"),
+            InsufficientInputAmount => write!(f, "Input amount is zero"),
+            IncorrectTokenCount => write!(f, "Number of tokens is out of the allowed range"),
+            TooLargeTokenDecimal => write!(f, "Token has too many decimal places"),
+            InvalidFee => write!(f, "Fee is out of the allowed range"),
+            AmpCoefTooLow => write!(f, "Amplification coefficient is too low"),
+            AmpCoefTooHigh => write!(f, "Amplification coefficient is too high"),
+            AmpCoefRampDurationTooShort => write!(f, "Amplification coefficient ramp is too short"),
+            AmpCoefChangeTooLarge => write!(f, "Amplification coefficient change is too large"),
+            NativeTokenNotSupported => write!(f, "Pool does not hold the wrapped native token"),
+            IncorrectNativeAmount => write!(f, "Transferred native amount is incorrect"),
+            NativeTransferFailed => write!(f, "Native token transfer failed"),
+            Reentrancy => write!(f, "Pool is locked by another call"),
+            TokenDecimalsUnavailable(token) => {
+                write!(f, "Token {} does not provide its decimals", Hex(token))
+            }
+            TokenDecimalsMismatch(token) => {
+                write!(f, "Token {} has different decimals than given", Hex(token))
+            }
+            InsufficientInitialLiquidity => write!(f, "Initial deposit is too small"),
+            OutputAmountBelowMin {
+                token,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Output amount of token {} is {actual}, below the minimum of {expected}",
+                Hex(token)
+            ),
+            InputAmountAboveMax {
+                token,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Input amount of token {} is {actual}, above the maximum of {expected}",
+                Hex(token)
+            ),
+            InputAmountCreditedBelowExpected {
+                token,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Pool received {actual} of token {}, less than the expected {expected}",
+                Hex(token)
+            ),
+            LiquidityMintedBelowMin { expected, actual } => write!(
+                f,
+                "Minted liquidity is {actual}, below the minimum of {expected}"
+            ),
+            LiquidityBurnedAboveMax { expected, actual } => write!(
+                f,
+                "Burned liquidity is {actual}, above the maximum of {expected}"
+            ),
+            FeeRampDurationTooShort => write!(f, "Fees ramp is too short"),
+            AmpCoefInvalidSchedule => write!(
+                f,
+                "Amplification coefficient ramp has no or too many waypoints"
+            ),
+            InvalidAutoAmpConfig => write!(
+                f,
+                "Automatic amplification coefficient adjustment parameters are invalid"
+            ),
+            RateProviderError(error) => write!(f, "Rate provider error: {error:?}"),
+            StaleRate(rate_provider) => write!(
+                f,
+                "Rate of provider {} is older than its max age",
+                Hex(rate_provider)
+            ),
+            InvalidTokenRate => write!(f, "Token rate is zero or too large"),
+        }
+    }
+}
+
+/// Decodes the error of a stable pool message from the output of its dry-run,
+/// i.e. the SCALE encoded `Result<Result<T, StablePoolError>, LangError>`.
+///
+/// Returns `Ok(None)` if the message succeeded.
+#[cfg(feature = "std")]
+pub fn decode_dry_run_error(output: &[u8]) -> Result<Option<StablePoolError>, scale::Error> {
+    use scale::DecodeAll;
+    match output {
+        [0, 0, ..] => Ok(None),
+        [0, 1, error @ ..] => StablePoolError::decode_all(&mut &error[..]).map(Some),
+        [1, error @ ..] => LangError::decode_all(&mut &error[..])
+            .map(|error| Some(StablePoolError::LangError(error))),
+        _ => Err("Output is not a message result".into()),
+    }
 }
 
 impl From<PSP22Error> for StablePoolError {
@@ -273,8 +784,37 @@ impl From<MathError> for StablePoolError {
     }
 }
 
+impl From<AmpCoefError> for StablePoolError {
+    fn from(error: AmpCoefError) -> Self {
+        match error {
+            AmpCoefError::TooLow => StablePoolError::AmpCoefTooLow,
+            AmpCoefError::TooHigh => StablePoolError::AmpCoefTooHigh,
+            AmpCoefError::RampDurationTooShort => StablePoolError::AmpCoefRampDurationTooShort,
+            AmpCoefError::ChangeTooLarge => StablePoolError::AmpCoefChangeTooLarge,
+            AmpCoefError::InvalidSchedule => StablePoolError::AmpCoefInvalidSchedule,
+            AmpCoefError::InvalidAutoAmpConfig => StablePoolError::InvalidAutoAmpConfig,
+            AmpCoefError::MathError(error) => StablePoolError::MathError(error),
+        }
+    }
+}
+
+impl From<RateProviderError> for StablePoolError {
+    fn from(error: RateProviderError) -> Self {
+        StablePoolError::RateProviderError(error)
+    }
+}
+
 impl From<Ownable2StepError> for StablePoolError {
     fn from(error: Ownable2StepError) -> Self {
         StablePoolError::Ownable2StepError(error)
     }
 }
+
+impl From<FeeScheduleError> for StablePoolError {
+    fn from(error: FeeScheduleError) -> Self {
+        match error {
+            FeeScheduleError::InvalidFee => StablePoolError::InvalidFee,
+            FeeScheduleError::RampDurationTooShort => StablePoolError::FeeRampDurationTooShort,
+        }
+    }
+}
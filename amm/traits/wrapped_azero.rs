@@ -0,0 +1,16 @@
+use psp22::PSP22Error;
+
+/// Interface of the wAZERO contract, a PSP22 token backed 1:1 by the native AZERO.
+///
+/// Selectors are compatible with the `WrappedAZERO` trait of the
+/// [wAZERO](https://github.com/Cardinal-Cryptography/wAZERO) contract.
+#[ink::trait_definition]
+pub trait WrappedAZERO {
+    /// Wraps the transferred native value and mints the same amount of wAZERO to the caller.
+    #[ink(message, payable)]
+    fn deposit(&mut self) -> Result<(), PSP22Error>;
+
+    /// Burns `value` of the caller's wAZERO and transfers the same amount of native AZERO to the caller.
+    #[ink(message)]
+    fn withdraw(&mut self, value: u128) -> Result<(), PSP22Error>;
+}
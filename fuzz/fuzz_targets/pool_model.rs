@@ -0,0 +1,209 @@
+#![no_main]
+
+use amm_fuzz::PoolParams;
+use amm_helpers::{
+    constants::stable_pool::{MAX_COINS, MINIMUM_LIQUIDITY},
+    math::MathError,
+    stable_swap_math as math,
+};
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use primitive_types::U256;
+use stable_swap_sim::{AccountId, AmpCoef, StablePoolError, StablePoolSim};
+
+/// Max error of the computed D, in comparable amounts.
+const D_TOLERANCE: u128 = 10;
+
+/// The (only) liquidity provider, who is also the trader.
+const PROVIDER: [u8; 32] = [1; 32];
+const FEE_RECEIVER: [u8; 32] = [2; 32];
+
+/// Amounts are taken modulo the respective reserve (plus one), so that most operations
+/// fit the pool. The first deposit takes the amounts as they are.
+#[derive(Arbitrary, Debug)]
+enum Op {
+    AddLiquidity {
+        amounts: [u128; MAX_COINS],
+    },
+    RemoveLiquidityByShares {
+        shares: u128,
+    },
+    RemoveLiquidityByAmounts {
+        amounts: [u128; MAX_COINS],
+    },
+    SwapExactIn {
+        token_in: u8,
+        token_out: u8,
+        amount: u128,
+    },
+    SwapExactOut {
+        token_in: u8,
+        token_out: u8,
+        amount: u128,
+    },
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    params: PoolParams,
+    initial_amounts: [u128; MAX_COINS],
+    ops: Vec<Op>,
+}
+
+fn token(id: usize) -> AccountId {
+    AccountId::from([id as u8 + 101; 32])
+}
+
+fn bounded(amount: u128, reserve: u128) -> u128 {
+    match reserve.checked_add(1) {
+        Some(bound) => amount % bound,
+        None => amount,
+    }
+}
+
+fn bounded_amounts(amounts: &[u128], reserves: &[u128]) -> Vec<u128> {
+    reserves
+        .iter()
+        .zip(amounts.iter())
+        .map(|(&reserve, &amount)| bounded(amount, reserve))
+        .collect()
+}
+
+fn apply(pool: &mut StablePoolSim, op: &Op) -> Result<(), StablePoolError> {
+    let n_coins = pool.tokens().len();
+    match *op {
+        Op::AddLiquidity { ref amounts } => {
+            let amounts = bounded_amounts(amounts, &pool.reserves());
+            pool.add_liquidity(0, amounts, PROVIDER.into())?;
+        }
+        Op::RemoveLiquidityByShares { shares } => {
+            let shares = bounded(shares, pool.balance_of(PROVIDER.into()));
+            let (reserves, total_supply) = (pool.reserves(), pool.total_supply());
+            let amounts =
+                pool.remove_liquidity_by_shares(PROVIDER.into(), shares, vec![0; n_coins])?;
+            for (&amount, &reserve) in amounts.iter().zip(reserves.iter()) {
+                assert!(
+                    U256::from(amount) * U256::from(total_supply)
+                        <= U256::from(reserve) * U256::from(shares),
+                    "Withdrawn more than the share of the reserve"
+                );
+            }
+        }
+        Op::RemoveLiquidityByAmounts { ref amounts } => {
+            let amounts = bounded_amounts(amounts, &pool.reserves());
+            let (shares, _) =
+                pool.remove_liquidity_by_amounts(PROVIDER.into(), u128::MAX, amounts)?;
+            assert!(shares > 0, "Tokens out for nothing");
+        }
+        Op::SwapExactIn {
+            token_in,
+            token_out,
+            amount,
+        } => {
+            let (token_in_id, token_out_id) =
+                (token_in as usize % n_coins, token_out as usize % n_coins);
+            let amount = bounded(amount, pool.reserves()[token_in_id]);
+            let (amount_out, _) =
+                pool.swap_exact_in(token(token_in_id), token(token_out_id), amount, 0)?;
+            assert!(amount_out == 0 || amount > 0, "Tokens out for nothing");
+        }
+        Op::SwapExactOut {
+            token_in,
+            token_out,
+            amount,
+        } => {
+            let (token_in_id, token_out_id) =
+                (token_in as usize % n_coins, token_out as usize % n_coins);
+            let amount = bounded(amount, pool.reserves()[token_out_id]);
+            let (amount_in, _) =
+                pool.swap_exact_out(token(token_in_id), token(token_out_id), amount, u128::MAX)?;
+            assert!(amount_in > 0, "Tokens out for nothing");
+        }
+    }
+    Ok(())
+}
+
+/// Change of D caused by rounding the reserves down to whole comparable amounts,
+/// which in extremely imbalanced pools is large for the scarce tokens.
+fn rounding_tolerance(params: &PoolParams, pool: &StablePoolSim, d: U256) -> Option<U256> {
+    let reserves: Vec<u128> = pool
+        .reserves()
+        .iter()
+        .zip(params.units.iter())
+        .map(|(reserve, unit)| reserve.checked_add(*unit))
+        .collect::<Option<_>>()?;
+    let d_rounded_up = math::rated_compute_d(&params.rates, &reserves, params.amp_coef).ok()?;
+    Some(d_rounded_up.saturating_sub(d) + D_TOLERANCE)
+}
+
+/// Checks that the value of a share, `d / total_supply`, did not decrease,
+/// up to the `tolerance` of `d_1`.
+fn assert_virtual_price_not_decreased(
+    d_0: U256,
+    supply_0: u128,
+    d_1: U256,
+    supply_1: u128,
+    tolerance: U256,
+) {
+    assert!(
+        (d_1 + tolerance) * U256::from(supply_0) >= d_0 * U256::from(supply_1),
+        "Virtual price decreased: {d_0}/{supply_0} -> {d_1}/{supply_1}"
+    );
+}
+
+/// Invariant (D) of the current reserves, in comparable amounts.
+fn invariant(params: &PoolParams, pool: &StablePoolSim) -> Result<U256, MathError> {
+    math::rated_compute_d(&params.rates, &pool.reserves(), params.amp_coef)
+}
+
+fuzz_target!(|input: Input| {
+    let Input {
+        params,
+        initial_amounts,
+        ops,
+    } = input;
+    let n_coins = params.n_coins();
+    // as instantiated with a fee receiver set, without transfer fees
+    let Ok(mut pool) = StablePoolSim::new_pool(
+        (0..n_coins).map(token).collect(),
+        params.tokens_decimals.clone(),
+        params.token_rates.clone(),
+        vec![None; n_coins],
+        AmpCoef::new_precise(params.amp_coef).unwrap(),
+        Some(params.fees),
+        Some(FEE_RECEIVER.into()),
+        0,
+    ) else {
+        return;
+    };
+    if pool
+        .add_liquidity(0, initial_amounts[..n_coins].to_vec(), PROVIDER.into())
+        .is_err()
+    {
+        return;
+    }
+    for op in ops.iter() {
+        let (d_0, supply_0) = (invariant(&params, &pool), pool.total_supply());
+        if apply(&mut pool, op).is_err() {
+            continue;
+        }
+        assert_eq!(
+            pool.total_supply(),
+            MINIMUM_LIQUIDITY
+                + pool.balance_of(PROVIDER.into())
+                + pool.balance_of(FEE_RECEIVER.into()),
+            "LP token supply out of sync"
+        );
+        if let (Ok(d_0), Ok(d_1)) = (d_0, invariant(&params, &pool)) {
+            if let Some(tolerance) = rounding_tolerance(&params, &pool, d_1) {
+                assert_virtual_price_not_decreased(
+                    d_0,
+                    supply_0,
+                    d_1,
+                    pool.total_supply(),
+                    tolerance,
+                );
+            }
+        }
+    }
+});
@@ -0,0 +1,58 @@
+#![no_main]
+
+use amm_fuzz::{amounts, Pool};
+use amm_helpers::stable_swap_math::rated_compute_lp_amount_for_deposit;
+use arbitrary::{Arbitrary, Result, Unstructured};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug)]
+struct Input {
+    pool: Pool,
+    deposit_amounts: Vec<u128>,
+    pool_token_supply: u128,
+}
+
+impl<'a> Arbitrary<'a> for Input {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let pool = Pool::arbitrary(u)?;
+        let deposit_amounts = amounts(u, pool.params.n_coins())?;
+        Ok(Self {
+            pool,
+            deposit_amounts,
+            pool_token_supply: u.arbitrary()?,
+        })
+    }
+}
+
+fuzz_target!(|input: Input| {
+    let Input {
+        pool,
+        deposit_amounts,
+        pool_token_supply,
+    } = input;
+    let compute = |with_fees: bool| {
+        rated_compute_lp_amount_for_deposit(
+            &pool.params.rates,
+            &deposit_amounts,
+            &pool.reserves,
+            pool_token_supply,
+            with_fees.then_some(&pool.params.fees),
+            pool.params.amp_coef,
+        )
+    };
+    if let (Ok((shares, fee_part)), Ok((shares_without_fee, _))) = (compute(true), compute(false)) {
+        assert!(
+            shares <= shares_without_fee,
+            "Fee increases minted shares: {shares} > {shares_without_fee}"
+        );
+        assert_eq!(
+            shares + fee_part,
+            shares_without_fee,
+            "Fee part is not the difference"
+        );
+        assert!(
+            shares == 0 || deposit_amounts.iter().any(|&amount| amount > 0),
+            "Shares for nothing"
+        );
+    }
+});
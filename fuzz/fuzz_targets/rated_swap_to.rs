@@ -0,0 +1,38 @@
+#![no_main]
+
+use amm_fuzz::PoolWithPair;
+use amm_helpers::stable_swap_math::rated_swap_to;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (PoolWithPair, u128)| {
+    let (
+        PoolWithPair {
+            pool,
+            token_in_id,
+            token_out_id,
+        },
+        token_in_amount,
+    ) = input;
+    if let Ok((token_out_amount, fee)) = rated_swap_to(
+        &pool.params.rates,
+        token_in_id,
+        token_in_amount,
+        token_out_id,
+        &pool.reserves,
+        &pool.params.fees,
+        pool.params.amp_coef,
+    ) {
+        assert!(
+            token_out_amount == 0 || token_out_amount < pool.reserves[token_out_id],
+            "Swap drains the reserve"
+        );
+        assert!(
+            token_out_amount == 0 || token_in_amount > 0,
+            "Tokens out for nothing"
+        );
+        assert!(
+            fee <= pool.reserves[token_out_id].saturating_sub(token_out_amount),
+            "Fee exceeds the reserve"
+        );
+    }
+});
@@ -0,0 +1,59 @@
+#![no_main]
+
+use amm_fuzz::{amounts, Pool};
+use amm_helpers::stable_swap_math::rated_compute_lp_amount_for_withdraw;
+use arbitrary::{Arbitrary, Result, Unstructured};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug)]
+struct Input {
+    pool: Pool,
+    withdraw_amounts: Vec<u128>,
+    pool_token_supply: u128,
+}
+
+impl<'a> Arbitrary<'a> for Input {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let pool = Pool::arbitrary(u)?;
+        let withdraw_amounts = amounts(u, pool.params.n_coins())?;
+        Ok(Self {
+            pool,
+            withdraw_amounts,
+            pool_token_supply: u.arbitrary()?,
+        })
+    }
+}
+
+fuzz_target!(|input: Input| {
+    let Input {
+        pool,
+        withdraw_amounts,
+        pool_token_supply,
+    } = input;
+    let compute = |with_fees: bool| {
+        rated_compute_lp_amount_for_withdraw(
+            &pool.params.rates,
+            &withdraw_amounts,
+            &pool.reserves,
+            pool_token_supply,
+            with_fees.then_some(&pool.params.fees),
+            pool.params.amp_coef,
+        )
+    };
+    if let (Ok((shares, fee_part)), Ok((shares_without_fee, _))) = (compute(true), compute(false)) {
+        assert!(
+            shares >= shares_without_fee,
+            "Fee decreases burned shares: {shares} < {shares_without_fee}"
+        );
+        // burned shares are rounded up, the fee part is the difference to the rounded down ones
+        assert!(
+            shares_without_fee.abs_diff(shares - fee_part) <= 1,
+            "Fee part is not the difference"
+        );
+        // at least one share is burnt, even if there is none
+        assert!(
+            shares <= pool_token_supply.max(1),
+            "Burns more than the supply: {shares} > {pool_token_supply}"
+        );
+    }
+});
@@ -0,0 +1,118 @@
+//! Shared inputs of the stable pool fuzz targets.
+
+use amm_helpers::{
+    constants::stable_pool::{
+        A_PRECISION, MAX_AMP, MAX_COINS, MAX_PROTOCOL_FEE, MAX_TOKEN_DECIMALS, MAX_TRADE_FEE,
+        MIN_AMP, RATE_PRECISION, TOKEN_TARGET_PRECISION,
+    },
+    stable_swap_math::{fees::Fees, ScaledRate},
+};
+use arbitrary::{Arbitrary, Result, Unstructured};
+use primitive_types::U256;
+
+/// Max token rate, given with `RATE_PRECISION`.
+pub const MAX_RATE: u128 = 1_000 * RATE_PRECISION;
+
+/// Pool parameters accepted by the `StablePoolContract` constructors.
+#[derive(Debug)]
+pub struct PoolParams {
+    pub tokens_decimals: Vec<u8>,
+    /// Token rates given with `RATE_PRECISION`.
+    pub token_rates: Vec<u128>,
+    /// `token_rates` scaled for the tokens decimals.
+    pub rates: Vec<ScaledRate>,
+    /// Token amounts worth a single comparable unit, rounded up.
+    pub units: Vec<u128>,
+    pub fees: Fees,
+    /// Amplification coefficient given with `A_PRECISION`.
+    pub amp_coef: u128,
+}
+
+impl PoolParams {
+    pub fn n_coins(&self) -> usize {
+        self.rates.len()
+    }
+}
+
+impl<'a> Arbitrary<'a> for PoolParams {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let n_coins = u.int_in_range(2..=MAX_COINS)?;
+        let mut tokens_decimals = Vec::with_capacity(n_coins);
+        let mut token_rates = Vec::with_capacity(n_coins);
+        let mut rates = Vec::with_capacity(n_coins);
+        let mut units = Vec::with_capacity(n_coins);
+        for _ in 0..n_coins {
+            let decimals = u.int_in_range(0..=MAX_TOKEN_DECIMALS)?;
+            let rate = u.int_in_range(1..=MAX_RATE)?;
+            tokens_decimals.push(decimals);
+            token_rates.push(rate);
+            rates.push(ScaledRate::new(rate, decimals).expect("Rate within bounds"));
+            // 10^decimals * RATE_PRECISION / (rate * TOKEN_TARGET_PRECISION), rounded up
+            let precision = U256::from(10).pow(decimals.into()) * U256::from(RATE_PRECISION);
+            let rate = U256::from(rate) * U256::from(TOKEN_TARGET_PRECISION);
+            let unit = (precision + rate - 1) / rate;
+            units.push(unit.try_into().unwrap_or(u128::MAX));
+        }
+        let fees = Fees::new(
+            u.int_in_range(0..=MAX_TRADE_FEE)?,
+            u.int_in_range(0..=MAX_PROTOCOL_FEE)?,
+        )
+        .expect("Fees within bounds");
+        let amp_coef = u.int_in_range(MIN_AMP * A_PRECISION..=MAX_AMP * A_PRECISION)?;
+        Ok(Self {
+            tokens_decimals,
+            token_rates,
+            rates,
+            units,
+            fees,
+            amp_coef,
+        })
+    }
+}
+
+/// Pool parameters with arbitrary reserves.
+#[derive(Debug)]
+pub struct Pool {
+    pub params: PoolParams,
+    pub reserves: Vec<u128>,
+}
+
+impl<'a> Arbitrary<'a> for Pool {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let params = PoolParams::arbitrary(u)?;
+        let reserves = amounts(u, params.n_coins())?;
+        Ok(Self { params, reserves })
+    }
+}
+
+/// Pool with a pair of distinct token ids.
+#[derive(Debug)]
+pub struct PoolWithPair {
+    pub pool: Pool,
+    pub token_in_id: usize,
+    pub token_out_id: usize,
+}
+
+impl<'a> Arbitrary<'a> for PoolWithPair {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let pool = Pool::arbitrary(u)?;
+        let (token_in_id, token_out_id) = pair(u, pool.params.n_coins())?;
+        Ok(Self {
+            pool,
+            token_in_id,
+            token_out_id,
+        })
+    }
+}
+
+/// Arbitrary amount of each of `n_coins` tokens.
+pub fn amounts(u: &mut Unstructured, n_coins: usize) -> Result<Vec<u128>> {
+    (0..n_coins).map(|_| u.arbitrary()).collect()
+}
+
+/// Arbitrary pair of distinct token ids.
+pub fn pair(u: &mut Unstructured, n_coins: usize) -> Result<(usize, usize)> {
+    let token_in_id = u.choose_index(n_coins)?;
+    let offset = 1 + u.choose_index(n_coins - 1)?;
+    Ok((token_in_id, (token_in_id + offset) % n_coins))
+}
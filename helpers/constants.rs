@@ -2,6 +2,8 @@ pub mod stable_pool {
     // Token amounts are rescaled so as if they have TOKEN_TARGET_DECIMALS decimal places.
     pub const TOKEN_TARGET_DECIMALS: u8 = 18;
     pub const TOKEN_TARGET_PRECISION: u128 = 10u128.pow(TOKEN_TARGET_DECIMALS as u32);
+    // Tokens with more decimal places than TOKEN_TARGET_DECIMALS are scaled down, up to this limit.
+    pub const MAX_TOKEN_DECIMALS: u8 = 36;
 
     // Precision for rate values. If the rate is 1.2, the rate provider should return 1.2 * RATE_PRECISION.
     pub const RATE_DECIMALS: u8 = 12;
@@ -17,6 +19,12 @@ pub mod stable_pool {
     /// Fee denominator
     pub const FEE_DENOM: u32 = 1_000_000_000;
 
+    /// Amount of LP tokens permanently locked on the first deposit.
+    /// Makes inflating the share price by the first depositor prohibitively expensive.
+    pub const MINIMUM_LIQUIDITY: u128 = 1_000_000_000;
+    /// Minimum invariant (D) of the first deposit, in `TOKEN_TARGET_DECIMALS` precision.
+    pub const MIN_INITIAL_D: u128 = 1_000 * MINIMUM_LIQUIDITY;
+
     /// Maximum number coins (PSP22 token contracts) in the pool.
     pub const MAX_COINS: usize = 8;
 
@@ -28,4 +36,31 @@ pub mod stable_pool {
     pub const MAX_AMP: u128 = 1_000_000;
     /// Max amplification change (how many times it can increase/decrease compared to current value).
     pub const MAX_AMP_CHANGE: u128 = 10;
+    /// Max number of waypoints of an amplification coefficient ramp.
+    pub const MAX_AMP_RAMP_WAYPOINTS: usize = 8;
+    /// Precision of the imbalance of reserves used by the automatic amplification adjustment.
+    pub const IMBALANCE_PRECISION: u32 = 1_000_000_000;
+    /// Precision of the amplification coefficient used in computations.
+    /// If A is 1.5, it is represented as 1.5 * A_PRECISION.
+    pub const A_PRECISION: u128 = 100;
+}
+
+pub mod stable_farm {
+    /// Maximum number of reward tokens in the farm.
+    pub const MAX_REWARD_TOKENS: usize = 5;
+
+    // Precision for the rewards per share accumulators.
+    pub const REWARD_PER_SHARE_PRECISION: u128 = 10u128.pow(12);
+}
+
+pub mod composite_rate {
+    /// Maximum number of terms of a composite rate.
+    pub const MAX_TERMS: usize = 8;
+}
+
+pub mod rate_aggregator {
+    /// Maximum number of sources of an aggregated rate.
+    pub const MAX_SOURCES: usize = 16;
+    /// Precision of the maximum deviation of a source rate from the median (1e9 is 100%).
+    pub const DEVIATION_PRECISION: u32 = 1_000_000_000;
 }
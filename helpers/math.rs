@@ -1,9 +1,36 @@
+use crate::constants::stable_pool::RATE_DECIMALS;
+use core::fmt;
 use primitive_types::U256;
 
 pub fn casted_mul(a: u128, b: u128) -> U256 {
     U256::from(a) * U256::from(b)
 }
 
+/// Scales `rate` with `decimals` decimal places to `RATE_DECIMALS`, rounding down.
+pub fn scale_rate(rate: u128, decimals: u8) -> Result<u128, MathError> {
+    let scale = 10u128
+        .checked_pow(decimals.abs_diff(RATE_DECIMALS).into())
+        .ok_or(MathError::PowOverflow(125))?;
+    if decimals <= RATE_DECIMALS {
+        rate.checked_mul(scale).ok_or(MathError::MulOverflow(125))
+    } else {
+        Ok(rate / scale)
+    }
+}
+
+/// Error of a checked arithmetic operation.
+///
+/// The tag identifies the operation which failed. Tags are unique within each kind of error
+/// and never reused, see [`MathError::describe`] for the registry:
+/// - 1-31: stable swap math
+/// - 51-55: amplification coefficient
+/// - 61-64: fees
+/// - 101-106: stable pool contract
+/// - 120-125: token rates scaling
+/// - 130-138: spot price and swap amount solvers
+///
+/// Tags from 200 up are reserved for other contracts, which document them
+/// (201-204: stable farm, 211: composite rate).
 #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub enum MathError {
@@ -13,4 +40,176 @@ pub enum MathError {
     MulOverflow(u8),
     SubUnderflow(u8),
     Precision(u8),
+    PowOverflow(u8),
+}
+
+impl MathError {
+    /// Describes the operation which failed.
+    pub fn describe(&self) -> &'static str {
+        use MathError::*;
+        match self {
+            AddOverflow(1) => "invariant: sum of reserves overflows",
+            AddOverflow(2) => "invariant: number of coins overflows",
+            AddOverflow(3) | AddOverflow(4) => "invariant: Newton step overflows",
+            AddOverflow(5) => "swap: sum of reserves overflows",
+            AddOverflow(6) => "swap: Newton coefficient overflows",
+            AddOverflow(7) | AddOverflow(8) => "swap: Newton step overflows",
+            AddOverflow(9) => "swap: input reserve overflows",
+            AddOverflow(11) => "swap: output amount with fee overflows",
+            AddOverflow(12) => "swap: input amount overflows",
+            AddOverflow(14) => "deposit: reserve overflows",
+            AddOverflow(51) => "amp coef: ramp overflows",
+            AddOverflow(63) => "transfer fee: gross amount overflows",
+            AddOverflow(101) => "pool: reserve overflows",
+            AddOverflow(120) => "rates: rounded amount overflows",
+            AddOverflow(130) | AddOverflow(131) => "spot price: price term overflows",
+
+            CastOverflow(1) => "deposit: initial invariant exceeds u128",
+            CastOverflow(2) => "deposit: ideal reserve exceeds u128",
+            CastOverflow(3) | CastOverflow(4) | CastOverflow(5) => {
+                "deposit: minted liquidity exceeds u128"
+            }
+            CastOverflow(6) => "withdraw: token amount exceeds u128",
+            CastOverflow(7) => "withdraw: ideal reserve exceeds u128",
+            CastOverflow(8) | CastOverflow(9) | CastOverflow(10) => {
+                "withdraw: burned liquidity exceeds u128"
+            }
+            CastOverflow(11) => "swap: new reserve exceeds u128",
+            CastOverflow(51) => "amp coef: geometric ramp exceeds u128",
+            CastOverflow(61) => "fees: fee amount exceeds u128",
+            CastOverflow(63) => "transfer fee: gross amount exceeds u128",
+            CastOverflow(106) => "pool: invariant exceeds u128",
+            CastOverflow(120) | CastOverflow(122) => "rates: rated amount exceeds u128",
+            CastOverflow(121) => "rates: token amount exceeds u128",
+            CastOverflow(124) => "rates: provider rate multiplied by factor exceeds u128",
+            CastOverflow(130) => "spot price: price exceeds u128",
+
+            DivByZero(1) => "invariant: a reserve is zero",
+            DivByZero(2) => "invariant: Newton step denominator is zero",
+            DivByZero(3) | DivByZero(4) => "swap: a reserve is zero",
+            DivByZero(5) | DivByZero(6) => "swap: amplification coefficient is zero",
+            DivByZero(7) => "swap: Newton step denominator is zero",
+            DivByZero(8) => "deposit: initial deposit must include all tokens",
+            DivByZero(9) | DivByZero(10) | DivByZero(11) | DivByZero(12) => {
+                "deposit: pool invariant is zero"
+            }
+            DivByZero(13) => "withdraw: total supply is zero",
+            DivByZero(14) | DivByZero(15) | DivByZero(16) | DivByZero(17) => {
+                "withdraw: pool invariant is zero"
+            }
+            DivByZero(18) => "invariant: number of coins is zero",
+            DivByZero(51) => "amp coef: ramp duration is zero",
+            DivByZero(52) => "amp coef: geometric ramp starts at zero",
+            DivByZero(53) | DivByZero(54) => "amp coef: growth precision is zero",
+            DivByZero(61) => "fees: fee denominator is zero",
+            DivByZero(62) => "fees: pool has a single coin",
+            DivByZero(63) => "transfer fee: transfer fee is 100%",
+            DivByZero(120) | DivByZero(121) => "rates: token rate is zero",
+            DivByZero(130) | DivByZero(133) => "spot price: a reserve is zero",
+            DivByZero(131) => "spot price: number of coins is zero",
+            DivByZero(132) => "spot price: price term is zero",
+
+            MulOverflow(1) => "invariant: n^n overflows",
+            MulOverflow(2) => "invariant: A * n^n * sum of reserves overflows",
+            MulOverflow(3) | MulOverflow(5) | MulOverflow(6) | MulOverflow(7) | MulOverflow(8) => {
+                "invariant: Newton step overflows"
+            }
+            MulOverflow(9) | MulOverflow(13) => "swap: n^n overflows",
+            MulOverflow(10) | MulOverflow(11) | MulOverflow(12) | MulOverflow(14)
+            | MulOverflow(26) | MulOverflow(27) => "swap: Newton coefficient overflows",
+            MulOverflow(15) | MulOverflow(16) => "swap: Newton step overflows",
+            MulOverflow(17) => "deposit: ideal reserve overflows",
+            MulOverflow(18) | MulOverflow(19) | MulOverflow(20) => {
+                "deposit: minted liquidity overflows"
+            }
+            MulOverflow(22) => "withdraw: ideal reserve overflows",
+            MulOverflow(23) | MulOverflow(24) | MulOverflow(25) => {
+                "withdraw: burned liquidity overflows"
+            }
+            MulOverflow(51) => "amp coef: ramp overflows",
+            MulOverflow(52) | MulOverflow(53) | MulOverflow(54) => {
+                "amp coef: geometric ramp overflows"
+            }
+            MulOverflow(61) | MulOverflow(62) => "fees: normalized trade fee overflows",
+            MulOverflow(120) | MulOverflow(122) => "rates: token decimals precision overflows",
+            MulOverflow(121) | MulOverflow(123) => "rates: scaled token rate overflows",
+            MulOverflow(125) => "rates: provider rate overflows with RATE_DECIMALS",
+            MulOverflow(130) => "spot price: n^n overflows",
+            MulOverflow(131) | MulOverflow(132) | MulOverflow(133) | MulOverflow(134)
+            | MulOverflow(135) | MulOverflow(136) => "spot price: price term overflows",
+            MulOverflow(137) => "solver: swap amount overflows",
+            MulOverflow(138) => "solver: target ratio overflows",
+
+            SubUnderflow(1) => "invariant: amplification coefficient is too low",
+            SubUnderflow(6) => "swap: Newton step underflows",
+            SubUnderflow(7) | SubUnderflow(8) => "swap: output amount exceeds the reserve",
+            SubUnderflow(9) => "swap: fee exceeds the output amount",
+            SubUnderflow(12) => "swap: output amount exceeds the reserve",
+            SubUnderflow(13) => "swap: input reserve decreases",
+            SubUnderflow(14) => "withdraw: amount exceeds the reserve",
+            SubUnderflow(18) => "deposit: fee exceeds the reserve",
+            SubUnderflow(19) | SubUnderflow(20) | SubUnderflow(22) => {
+                "deposit: pool invariant decreases"
+            }
+            SubUnderflow(21) => "deposit: fee part underflows",
+            SubUnderflow(27) => "withdraw: fee exceeds the reserve",
+            SubUnderflow(28) | SubUnderflow(29) | SubUnderflow(31) => {
+                "withdraw: pool invariant increases"
+            }
+            SubUnderflow(30) => "withdraw: fee part underflows",
+            SubUnderflow(51) | SubUnderflow(52) => "amp coef: ramp starts in the future",
+            SubUnderflow(55) => "amp coef: ramp underflows",
+            SubUnderflow(61) => "fees: trade fee exceeds 100%",
+            SubUnderflow(62) => "fees: pool has no coins",
+            SubUnderflow(63) => "transfer fee: fee exceeds the amount",
+            SubUnderflow(64) => "transfer fee: transfer fee exceeds 100%",
+            SubUnderflow(101) => "pool: amount exceeds the reserve",
+            SubUnderflow(102) => "pool: protocol fee exceeds the fee",
+            SubUnderflow(103) => "pool: balance is below the reserve",
+            SubUnderflow(104) => "pool: balance is below the reserve",
+            SubUnderflow(105) => "pool: balance decreased on transfer in",
+            SubUnderflow(130) | SubUnderflow(132) | SubUnderflow(134) => {
+                "solver: input reserve decreases"
+            }
+            SubUnderflow(131) => "solver: output amount exceeds the reserve",
+            SubUnderflow(133) => "solver: trade fee exceeds 100%",
+
+            Precision(1) => "invariant: Newton method does not converge",
+            Precision(2) => "swap: Newton method does not converge",
+            Precision(3) => "solver: Newton method does not converge",
+
+            PowOverflow(125) => "rates: provider rate precision exceeds u128",
+
+            _ => "unknown operation",
+        }
+    }
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {}", self, self.describe())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn scales_rate_to_rate_decimals() {
+        assert_eq!(scale_rate(15 * 10u128.pow(17), 18), Ok(15 * 10u128.pow(11)));
+        assert_eq!(scale_rate(3, 0), Ok(3 * 10u128.pow(12)));
+        assert_eq!(scale_rate(u128::MAX, 0), Err(MathError::MulOverflow(125)));
+        assert_eq!(scale_rate(1, u8::MAX), Err(MathError::PowOverflow(125)));
+    }
+
+    #[test]
+    fn displays_description() {
+        assert_eq!(
+            format!("{}", MathError::DivByZero(8)),
+            "DivByZero(8): deposit: initial deposit must include all tokens"
+        );
+        assert_eq!(MathError::SubUnderflow(200).describe(), "unknown operation");
+    }
 }
@@ -0,0 +1,695 @@
+use crate::{
+    constants::stable_pool::{
+        A_PRECISION, MAX_AMP, MAX_AMP_CHANGE, MAX_AMP_RAMP_WAYPOINTS, MIN_AMP, MIN_RAMP_DURATION,
+    },
+    ensure,
+    math::{casted_mul, MathError},
+};
+use ink::prelude::{vec, vec::Vec};
+use primitive_types::U256;
+
+/// Precision of the growth factor of a geometric ramp.
+const GROWTH_PRECISION: u128 = 1_000_000_000_000_000_000;
+
+/// Number of binary digits of the elapsed fraction of a geometric ramp segment.
+const GROWTH_EXPONENT_BITS: u32 = 32;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AmpCoefError {
+    TooLow,
+    TooHigh,
+    RampDurationTooShort,
+    ChangeTooLarge,
+    InvalidSchedule,
+    InvalidAutoAmpConfig,
+    MathError(MathError),
+}
+
+impl From<MathError> for AmpCoefError {
+    fn from(error: MathError) -> Self {
+        AmpCoefError::MathError(error)
+    }
+}
+
+/// Interpolation of the amplification coefficient between consecutive ramp waypoints.
+#[derive(Default, Debug, scale::Encode, scale::Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum RampShape {
+    /// Changes by the same amount in each unit of time.
+    #[default]
+    Linear,
+    /// Changes by the same ratio in each unit of time, which spreads large changes
+    /// more evenly for the pool prices.
+    Geometric,
+}
+
+/// Amplification coefficient, ramped over time through a list of waypoints.
+///
+/// Times are given explicitly (in milliseconds, as the block timestamp),
+/// so that the same logic is used on-chain and by off-chain models.
+#[derive(Default, Debug, scale::Encode, scale::Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct AmpCoef {
+    /// Initial amplification coefficient (with `A_PRECISION`).
+    init_amp_coef: u128,
+    /// Target for ramping up amplification coefficient (with `A_PRECISION`).
+    future_amp_coef: u128,
+    /// Initial amplification time.
+    init_time: u64,
+    /// Stop ramp up amplification time.
+    future_time: u64,
+    /// Intermediate waypoints `(amp_coef, time)` of the ramp (with `A_PRECISION`), ordered by time.
+    waypoints: Vec<(u128, u64)>,
+    /// Interpolation between consecutive waypoints.
+    ramp_shape: RampShape,
+}
+
+impl AmpCoef {
+    /// Creates amplification coefficient given as an integer `init_amp_coef`.
+    pub fn new(init_amp_coef: u128) -> Result<Self, AmpCoefError> {
+        Self::new_precise(to_precise(init_amp_coef)?)
+    }
+
+    /// Creates amplification coefficient given as `init_amp_coef` with `A_PRECISION`.
+    pub fn new_precise(init_amp_coef: u128) -> Result<Self, AmpCoefError> {
+        ensure_precise_in_bounds(init_amp_coef)?;
+        Ok(Self {
+            init_amp_coef,
+            future_amp_coef: init_amp_coef,
+            init_time: 0,
+            future_time: 0,
+            waypoints: Vec::new(),
+            ramp_shape: RampShape::Linear,
+        })
+    }
+
+    /// Returns amplification coefficient at `current_time` rounded down to an integer.
+    pub fn compute_amp_coef(&self, current_time: u64) -> Result<u128, MathError> {
+        Ok(self.compute_amp_coef_precise(current_time)? / A_PRECISION)
+    }
+
+    /// Returns amplification coefficient at `current_time` with `A_PRECISION`.
+    pub fn compute_amp_coef_precise(&self, current_time: u64) -> Result<u128, MathError> {
+        let mut segment_start = (self.init_amp_coef, self.init_time);
+        for &segment_end in self
+            .waypoints
+            .iter()
+            .chain([(self.future_amp_coef, self.future_time)].iter())
+        {
+            if current_time < segment_end.1 {
+                return self.interpolate(segment_start, segment_end, current_time);
+            }
+            segment_start = segment_end;
+        }
+        Ok(self.future_amp_coef)
+    }
+
+    /// Returns amplification coefficient at `current_time` on the segment
+    /// from `(init_amp_coef, init_time)` to `(future_amp_coef, future_time)`.
+    fn interpolate(
+        &self,
+        (init_amp_coef, init_time): (u128, u64),
+        (future_amp_coef, future_time): (u128, u64),
+        current_time: u64,
+    ) -> Result<u128, MathError> {
+        let time_range = future_time
+            .checked_sub(init_time)
+            .ok_or(MathError::SubUnderflow(51))?;
+        let time_delta = current_time
+            .checked_sub(init_time)
+            .ok_or(MathError::SubUnderflow(52))?;
+
+        match self.ramp_shape {
+            RampShape::Linear => {
+                // Compute amp factor based on ramp time
+                let amp_range = future_amp_coef.abs_diff(init_amp_coef);
+                let amp_delta = amp_range
+                    .checked_mul(time_delta as u128)
+                    .ok_or(MathError::MulOverflow(51))?
+                    .checked_div(time_range as u128)
+                    .ok_or(MathError::DivByZero(51))?;
+                if future_amp_coef >= init_amp_coef {
+                    // Ramp up
+                    init_amp_coef
+                        .checked_add(amp_delta)
+                        .ok_or(MathError::AddOverflow(51))
+                } else {
+                    // Ramp down
+                    init_amp_coef
+                        .checked_sub(amp_delta)
+                        .ok_or(MathError::SubUnderflow(55))
+                }
+            }
+            RampShape::Geometric => {
+                // init_amp_coef * (future_amp_coef / init_amp_coef) ^ (time_delta / time_range)
+                let ratio = casted_mul(future_amp_coef, GROWTH_PRECISION)
+                    .checked_div(init_amp_coef.into())
+                    .ok_or(MathError::DivByZero(52))?;
+                let growth = fractional_pow(ratio, time_delta, time_range)?;
+                casted_mul(init_amp_coef, 1)
+                    .checked_mul(growth)
+                    .ok_or(MathError::MulOverflow(54))?
+                    .checked_div(GROWTH_PRECISION.into())
+                    .ok_or(MathError::DivByZero(53))?
+                    .try_into()
+                    .map_err(|_| MathError::CastOverflow(51))
+            }
+        }
+    }
+
+    /// Starts ramping to an integer `future_amp_coef` at `current_time`.
+    pub fn ramp_amp_coef(
+        &mut self,
+        future_amp_coef: u128,
+        future_time: u64,
+        current_time: u64,
+    ) -> Result<(), AmpCoefError> {
+        self.ramp_amp_coef_precise(to_precise(future_amp_coef)?, future_time, current_time)
+    }
+
+    /// Starts ramping to `future_amp_coef` with `A_PRECISION` at `current_time`.
+    pub fn ramp_amp_coef_precise(
+        &mut self,
+        future_amp_coef: u128,
+        future_time: u64,
+        current_time: u64,
+    ) -> Result<(), AmpCoefError> {
+        self.ramp_amp_coef_schedule_precise(
+            vec![(future_amp_coef, future_time)],
+            RampShape::Linear,
+            current_time,
+        )
+    }
+
+    /// Starts ramping at `current_time` through `waypoints` of integer amplification coefficients
+    /// and the times they are reached at, interpolated with `ramp_shape`.
+    ///
+    /// Each segment between consecutive waypoints (starting with the current amplification coefficient)
+    /// is restricted as a single ramp: it takes at least `MIN_RAMP_DURATION`
+    /// and changes the amplification coefficient at most `MAX_AMP_CHANGE` times.
+    pub fn ramp_amp_coef_schedule(
+        &mut self,
+        waypoints: Vec<(u128, u64)>,
+        ramp_shape: RampShape,
+        current_time: u64,
+    ) -> Result<(), AmpCoefError> {
+        let waypoints = waypoints
+            .into_iter()
+            .map(|(amp_coef, time)| Ok((to_precise(amp_coef)?, time)))
+            .collect::<Result<_, AmpCoefError>>()?;
+        self.ramp_amp_coef_schedule_precise(waypoints, ramp_shape, current_time)
+    }
+
+    /// Same as `ramp_amp_coef_schedule`, with amplification coefficients of `waypoints`
+    /// given with `A_PRECISION`.
+    pub fn ramp_amp_coef_schedule_precise(
+        &mut self,
+        mut waypoints: Vec<(u128, u64)>,
+        ramp_shape: RampShape,
+        current_time: u64,
+    ) -> Result<(), AmpCoefError> {
+        ensure!(
+            !waypoints.is_empty() && waypoints.len() <= MAX_AMP_RAMP_WAYPOINTS,
+            AmpCoefError::InvalidSchedule
+        );
+        let current_amp_coef = self.compute_amp_coef_precise(current_time)?;
+        let mut segment_start = (current_amp_coef, current_time);
+        for &(future_amp_coef, future_time) in &waypoints {
+            ensure_precise_in_bounds(future_amp_coef)?;
+            let ramp_duration = future_time.checked_sub(segment_start.1);
+            ensure!(
+                ramp_duration.is_some() && ramp_duration.unwrap() >= MIN_RAMP_DURATION,
+                AmpCoefError::RampDurationTooShort
+            );
+            let init_amp_coef = segment_start.0;
+            ensure!(
+                (future_amp_coef >= init_amp_coef
+                    && future_amp_coef <= init_amp_coef * MAX_AMP_CHANGE)
+                    || (future_amp_coef < init_amp_coef
+                        && future_amp_coef * MAX_AMP_CHANGE >= init_amp_coef),
+                AmpCoefError::ChangeTooLarge
+            );
+            segment_start = (future_amp_coef, future_time);
+        }
+        let (future_amp_coef, future_time) =
+            waypoints.pop().ok_or(AmpCoefError::InvalidSchedule)?;
+        self.init_amp_coef = current_amp_coef;
+        self.init_time = current_time;
+        self.future_amp_coef = future_amp_coef;
+        self.future_time = future_time;
+        self.waypoints = waypoints;
+        self.ramp_shape = ramp_shape;
+        Ok(())
+    }
+
+    /// Stop ramping A at `current_time`. If ramping is not in progress, it does not influence the A.
+    pub fn stop_ramp_amp_coef(&mut self, current_time: u64) -> Result<(), AmpCoefError> {
+        let current_amp_coef = self.compute_amp_coef_precise(current_time)?;
+        self.init_amp_coef = current_amp_coef;
+        self.future_amp_coef = current_amp_coef;
+        self.init_time = current_time;
+        self.future_time = current_time;
+        self.waypoints = Vec::new();
+        self.ramp_shape = RampShape::Linear;
+        Ok(())
+    }
+
+    /// Returns a tuple of the future amplification coefficient and the ramping end time.
+    /// Returns `None` if the amplification coefficient is not in ramping period at `current_time`.
+    pub fn future_amp_coef(&self, current_time: u64) -> Option<(u128, u64)> {
+        self.future_amp_coef_precise(current_time)
+            .map(|(amp_coef, time)| (amp_coef / A_PRECISION, time))
+    }
+
+    /// Same as `future_amp_coef`, with the amplification coefficient given with `A_PRECISION`.
+    pub fn future_amp_coef_precise(&self, current_time: u64) -> Option<(u128, u64)> {
+        if current_time < self.future_time {
+            Some((self.future_amp_coef, self.future_time))
+        } else {
+            None
+        }
+    }
+
+    /// Returns interpolation of the ramp.
+    pub fn ramp_shape(&self) -> RampShape {
+        self.ramp_shape
+    }
+}
+
+/// Converts an integer amplification coefficient to one with `A_PRECISION`, checking its bounds.
+fn to_precise(amp_coef: u128) -> Result<u128, AmpCoefError> {
+    ensure!(amp_coef >= MIN_AMP, AmpCoefError::TooLow);
+    ensure!(amp_coef <= MAX_AMP, AmpCoefError::TooHigh);
+    Ok(amp_coef * A_PRECISION)
+}
+
+/// Checks bounds of an amplification coefficient with `A_PRECISION`.
+fn ensure_precise_in_bounds(amp_coef: u128) -> Result<(), AmpCoefError> {
+    ensure!(amp_coef >= MIN_AMP * A_PRECISION, AmpCoefError::TooLow);
+    ensure!(amp_coef <= MAX_AMP * A_PRECISION, AmpCoefError::TooHigh);
+    Ok(())
+}
+
+/// Returns `base ^ (num / denom)` for `num < denom`, with `base` and the result in `GROWTH_PRECISION`.
+///
+/// The exponent is expanded into `GROWTH_EXPONENT_BITS` binary digits,
+/// the `k`-th of which multiplies the result by the `2^k`-th root of `base`.
+fn fractional_pow(base: U256, num: u64, denom: u64) -> Result<U256, MathError> {
+    let one = U256::from(GROWTH_PRECISION);
+    let (mut result, mut root, mut remainder) = (one, base, num as u128);
+    for _ in 0..GROWTH_EXPONENT_BITS {
+        root = root
+            .checked_mul(one)
+            .ok_or(MathError::MulOverflow(52))?
+            .integer_sqrt();
+        remainder *= 2;
+        if remainder >= denom as u128 {
+            remainder -= denom as u128;
+            result = result
+                .checked_mul(root)
+                .ok_or(MathError::MulOverflow(53))?
+                .checked_div(one)
+                .ok_or(MathError::DivByZero(54))?;
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amp_coef_up() {
+        let amp_coef = AmpCoef {
+            init_amp_coef: 100 * A_PRECISION,
+            future_amp_coef: 1000 * A_PRECISION,
+            init_time: 100,
+            future_time: 1600,
+            ..Default::default()
+        };
+        assert_eq!(amp_coef.compute_amp_coef(100), Ok(100));
+        assert_eq!(amp_coef.compute_amp_coef(850), Ok(550));
+        assert_eq!(amp_coef.compute_amp_coef(1600), Ok(1000));
+    }
+
+    #[test]
+    fn amp_coef_down() {
+        let amp_coef = AmpCoef {
+            init_amp_coef: 1000 * A_PRECISION,
+            future_amp_coef: 100 * A_PRECISION,
+            init_time: 100,
+            future_time: 1600,
+            ..Default::default()
+        };
+        assert_eq!(amp_coef.compute_amp_coef(100), Ok(1000));
+        assert_eq!(amp_coef.compute_amp_coef(850), Ok(550));
+        assert_eq!(amp_coef.compute_amp_coef(1600), Ok(100));
+    }
+
+    #[test]
+    fn amp_coef_change_duration() {
+        let mut amp_coef = AmpCoef {
+            init_amp_coef: 1000 * A_PRECISION,
+            future_amp_coef: 100 * A_PRECISION,
+            init_time: 100,
+            future_time: 1600,
+            ..Default::default()
+        };
+        assert_eq!(
+            amp_coef.ramp_amp_coef(1000, 999, 1000),
+            Err(AmpCoefError::RampDurationTooShort)
+        );
+        assert_eq!(
+            amp_coef.ramp_amp_coef(1000, 1000 + MIN_RAMP_DURATION - 1, 1000),
+            Err(AmpCoefError::RampDurationTooShort)
+        );
+        assert_eq!(
+            amp_coef.ramp_amp_coef(1000, 1000 + MIN_RAMP_DURATION, 1000),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn amp_coef_change_too_large() {
+        let mut amp_coef = AmpCoef {
+            init_amp_coef: 100 * A_PRECISION,
+            future_amp_coef: 100 * A_PRECISION,
+            init_time: 100,
+            future_time: 1600,
+            ..Default::default()
+        };
+        assert_eq!(
+            amp_coef.ramp_amp_coef(1001, 100 + MIN_RAMP_DURATION, 100),
+            Err(AmpCoefError::ChangeTooLarge)
+        );
+        assert_eq!(
+            amp_coef.ramp_amp_coef(1000, 100 + MIN_RAMP_DURATION, 100),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn amp_coef_stop_ramp() {
+        let mut amp_coef = AmpCoef {
+            init_amp_coef: 100 * A_PRECISION,
+            future_amp_coef: 100 * A_PRECISION,
+            init_time: 100,
+            future_time: 1600,
+            ..Default::default()
+        };
+        assert_eq!(amp_coef.compute_amp_coef(100), Ok(100));
+        assert_eq!(
+            amp_coef.ramp_amp_coef(1000, 100 + MIN_RAMP_DURATION, 100),
+            Ok(())
+        );
+        let stop_time = 100 + MIN_RAMP_DURATION / 2;
+        assert!(amp_coef.stop_ramp_amp_coef(stop_time).is_ok());
+        assert_eq!(amp_coef.compute_amp_coef(stop_time), Ok(550));
+    }
+
+    #[test]
+    fn amp_coef_stop_ramp_no_change() {
+        let mut amp_coef = AmpCoef {
+            init_amp_coef: 100 * A_PRECISION,
+            future_amp_coef: 100 * A_PRECISION,
+            init_time: 100,
+            future_time: 1600,
+            ..Default::default()
+        };
+        assert_eq!(amp_coef.compute_amp_coef(100), Ok(100));
+        assert_eq!(
+            amp_coef.ramp_amp_coef(1000, 100 + MIN_RAMP_DURATION, 100),
+            Ok(())
+        );
+        assert_eq!(amp_coef.compute_amp_coef(100 + MIN_RAMP_DURATION), Ok(1000));
+        let stop_time = 100 + MIN_RAMP_DURATION * 2;
+        assert!(amp_coef.stop_ramp_amp_coef(stop_time).is_ok());
+        assert_eq!(amp_coef.compute_amp_coef(stop_time), Ok(1000));
+    }
+
+    #[test]
+    fn amp_coef_fractional_ramp() {
+        let mut amp_coef = AmpCoef::new(1).unwrap();
+        assert_eq!(
+            amp_coef.ramp_amp_coef(2, 100 + MIN_RAMP_DURATION, 100),
+            Ok(())
+        );
+        let stop_time = 100 + MIN_RAMP_DURATION / 2;
+        assert_eq!(
+            amp_coef.compute_amp_coef_precise(stop_time),
+            Ok(3 * A_PRECISION / 2)
+        );
+        assert_eq!(amp_coef.compute_amp_coef(stop_time), Ok(1));
+        assert!(amp_coef.stop_ramp_amp_coef(stop_time).is_ok());
+        let now = 100 + MIN_RAMP_DURATION;
+        assert_eq!(
+            amp_coef.compute_amp_coef_precise(now),
+            Ok(3 * A_PRECISION / 2)
+        );
+        // ramp validation uses the precise value: 1.5 * 10 = 15
+        assert_eq!(
+            amp_coef.ramp_amp_coef(16, 100 + 2 * MIN_RAMP_DURATION, now),
+            Err(AmpCoefError::ChangeTooLarge)
+        );
+        assert_eq!(
+            amp_coef.ramp_amp_coef(15, 100 + 2 * MIN_RAMP_DURATION, now),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn amp_coef_precise_init() {
+        let mut amp_coef = AmpCoef::new_precise(150).unwrap();
+        assert_eq!(amp_coef.compute_amp_coef_precise(0), Ok(150));
+        assert_eq!(amp_coef.compute_amp_coef(0), Ok(1));
+        assert_eq!(
+            amp_coef.ramp_amp_coef_precise(275, MIN_RAMP_DURATION, 0),
+            Ok(())
+        );
+        assert_eq!(
+            amp_coef.future_amp_coef_precise(0),
+            Some((275, MIN_RAMP_DURATION))
+        );
+        assert_eq!(amp_coef.future_amp_coef(0), Some((2, MIN_RAMP_DURATION)));
+        assert_eq!(
+            amp_coef.compute_amp_coef_precise(MIN_RAMP_DURATION),
+            Ok(275)
+        );
+    }
+
+    #[test]
+    fn amp_coef_precise_bounds() {
+        assert_eq!(
+            AmpCoef::new_precise(MIN_AMP * A_PRECISION - 1),
+            Err(AmpCoefError::TooLow)
+        );
+        assert_eq!(
+            AmpCoef::new_precise(MAX_AMP * A_PRECISION + 1),
+            Err(AmpCoefError::TooHigh)
+        );
+        assert_eq!(AmpCoef::new(MAX_AMP + 1), Err(AmpCoefError::TooHigh));
+        let mut amp_coef = AmpCoef::new(MIN_AMP).unwrap();
+        assert_eq!(
+            amp_coef.ramp_amp_coef_precise(MIN_AMP * A_PRECISION - 1, MIN_RAMP_DURATION, 0),
+            Err(AmpCoefError::TooLow)
+        );
+        assert_eq!(
+            amp_coef.ramp_amp_coef_schedule(
+                vec![(MAX_AMP + 1, MIN_RAMP_DURATION)],
+                RampShape::Linear,
+                0
+            ),
+            Err(AmpCoefError::TooHigh)
+        );
+    }
+
+    #[test]
+    fn amp_coef_geometric_up() {
+        let amp_coef = AmpCoef {
+            init_amp_coef: 100 * A_PRECISION,
+            future_amp_coef: 1000 * A_PRECISION,
+            init_time: 100,
+            future_time: 1700,
+            ramp_shape: RampShape::Geometric,
+            ..Default::default()
+        };
+        assert_eq!(amp_coef.compute_amp_coef(100), Ok(100));
+        // 100 * 10^(1/4) = 177.8
+        assert_eq!(amp_coef.compute_amp_coef(500), Ok(177));
+        // 100 * 10^(1/2) = 316.2
+        assert_eq!(amp_coef.compute_amp_coef(900), Ok(316));
+        assert_eq!(amp_coef.compute_amp_coef_precise(900), Ok(31622));
+        assert_eq!(amp_coef.compute_amp_coef(1700), Ok(1000));
+    }
+
+    #[test]
+    fn amp_coef_geometric_down() {
+        let amp_coef = AmpCoef {
+            init_amp_coef: 1000 * A_PRECISION,
+            future_amp_coef: 100 * A_PRECISION,
+            init_time: 100,
+            future_time: 1700,
+            ramp_shape: RampShape::Geometric,
+            ..Default::default()
+        };
+        assert_eq!(amp_coef.compute_amp_coef(100), Ok(1000));
+        // 1000 * 10^(-1/4) = 562.3
+        assert_eq!(amp_coef.compute_amp_coef(500), Ok(562));
+        // 1000 * 10^(-1/2) = 316.2
+        assert_eq!(amp_coef.compute_amp_coef(900), Ok(316));
+        assert_eq!(amp_coef.compute_amp_coef(1700), Ok(100));
+    }
+
+    #[test]
+    fn amp_coef_geometric_is_monotonic() {
+        let mut amp_coef = AmpCoef::new(50).unwrap();
+        assert_eq!(
+            amp_coef.ramp_amp_coef_schedule(
+                vec![(500, 100 + MIN_RAMP_DURATION)],
+                RampShape::Geometric,
+                100
+            ),
+            Ok(())
+        );
+        let mut last = amp_coef.compute_amp_coef_precise(100).unwrap();
+        for step in 1..=100 {
+            let amp = amp_coef
+                .compute_amp_coef_precise(100 + step * MIN_RAMP_DURATION / 100)
+                .unwrap();
+            assert!(amp >= last);
+            last = amp;
+        }
+        assert_eq!(last, 500 * A_PRECISION);
+    }
+
+    #[test]
+    fn amp_coef_multi_segment() {
+        let mut amp_coef = AmpCoef::new(100).unwrap();
+        let schedule = vec![
+            (1000, 100 + MIN_RAMP_DURATION),
+            (5000, 100 + 2 * MIN_RAMP_DURATION),
+            (500, 100 + 4 * MIN_RAMP_DURATION),
+        ];
+        assert_eq!(
+            amp_coef.ramp_amp_coef_schedule(schedule, RampShape::Linear, 100),
+            Ok(())
+        );
+        assert_eq!(amp_coef.compute_amp_coef(100), Ok(100));
+        // up, within the first and the second segment
+        assert_eq!(
+            amp_coef.compute_amp_coef(100 + MIN_RAMP_DURATION / 2),
+            Ok(550)
+        );
+        assert_eq!(amp_coef.compute_amp_coef(100 + MIN_RAMP_DURATION), Ok(1000));
+        assert_eq!(
+            amp_coef.compute_amp_coef(100 + 3 * MIN_RAMP_DURATION / 2),
+            Ok(3000)
+        );
+        // down, within the last segment
+        assert_eq!(
+            amp_coef.compute_amp_coef(100 + 3 * MIN_RAMP_DURATION),
+            Ok(2750)
+        );
+        assert_eq!(
+            amp_coef.future_amp_coef(100 + 3 * MIN_RAMP_DURATION),
+            Some((500, 100 + 4 * MIN_RAMP_DURATION))
+        );
+        assert_eq!(
+            amp_coef.compute_amp_coef(100 + 4 * MIN_RAMP_DURATION),
+            Ok(500)
+        );
+        assert_eq!(amp_coef.future_amp_coef(100 + 4 * MIN_RAMP_DURATION), None);
+    }
+
+    #[test]
+    fn amp_coef_multi_segment_stop_ramp() {
+        let mut amp_coef = AmpCoef::new(100).unwrap();
+        let schedule = vec![
+            (1000, 100 + MIN_RAMP_DURATION),
+            (100, 100 + 2 * MIN_RAMP_DURATION),
+        ];
+        assert_eq!(
+            amp_coef.ramp_amp_coef_schedule(schedule, RampShape::Geometric, 100),
+            Ok(())
+        );
+        let stop_time = 100 + 3 * MIN_RAMP_DURATION / 2;
+        assert!(amp_coef.stop_ramp_amp_coef(stop_time).is_ok());
+        assert_eq!(amp_coef.compute_amp_coef(stop_time), Ok(316));
+        assert_eq!(amp_coef.ramp_shape(), RampShape::Linear);
+        assert_eq!(amp_coef.future_amp_coef(stop_time), None);
+        assert_eq!(
+            amp_coef.compute_amp_coef(100 + 2 * MIN_RAMP_DURATION),
+            Ok(316)
+        );
+    }
+
+    #[test]
+    fn amp_coef_schedule_validation() {
+        let mut amp_coef = AmpCoef::new(100).unwrap();
+        assert_eq!(
+            amp_coef.ramp_amp_coef_schedule(vec![], RampShape::Linear, 100),
+            Err(AmpCoefError::InvalidSchedule)
+        );
+        let too_many = (1..=MAX_AMP_RAMP_WAYPOINTS as u64 + 1)
+            .map(|i| (100, 100 + i * MIN_RAMP_DURATION))
+            .collect();
+        assert_eq!(
+            amp_coef.ramp_amp_coef_schedule(too_many, RampShape::Linear, 100),
+            Err(AmpCoefError::InvalidSchedule)
+        );
+        // each segment is restricted to `MAX_AMP_CHANGE`
+        assert_eq!(
+            amp_coef.ramp_amp_coef_schedule(
+                vec![
+                    (1000, 100 + MIN_RAMP_DURATION),
+                    (10001, 100 + 2 * MIN_RAMP_DURATION)
+                ],
+                RampShape::Geometric,
+                100
+            ),
+            Err(AmpCoefError::ChangeTooLarge)
+        );
+        assert_eq!(
+            amp_coef.ramp_amp_coef_schedule(
+                vec![
+                    (1000, 100 + MIN_RAMP_DURATION),
+                    (99, 100 + 2 * MIN_RAMP_DURATION)
+                ],
+                RampShape::Geometric,
+                100
+            ),
+            Err(AmpCoefError::ChangeTooLarge)
+        );
+        // and to `MIN_RAMP_DURATION`
+        assert_eq!(
+            amp_coef.ramp_amp_coef_schedule(
+                vec![
+                    (1000, 100 + MIN_RAMP_DURATION),
+                    (2000, 100 + 2 * MIN_RAMP_DURATION - 1)
+                ],
+                RampShape::Linear,
+                100
+            ),
+            Err(AmpCoefError::RampDurationTooShort)
+        );
+        assert_eq!(
+            amp_coef.ramp_amp_coef_schedule(
+                vec![
+                    (1000, 100 + MIN_RAMP_DURATION),
+                    (10000, 100 + 2 * MIN_RAMP_DURATION)
+                ],
+                RampShape::Geometric,
+                100
+            ),
+            Ok(())
+        );
+        assert_eq!(amp_coef.ramp_shape(), RampShape::Geometric);
+    }
+}
@@ -0,0 +1,305 @@
+use crate::{
+    constants::stable_pool::{
+        A_PRECISION, IMBALANCE_PRECISION, MAX_AMP, MAX_AMP_CHANGE, MIN_AMP, MIN_RAMP_DURATION,
+    },
+    ensure,
+};
+use core::cmp::Ordering;
+use primitive_types::U256;
+
+use super::amp_coef::{AmpCoef, AmpCoefError};
+
+/// Returns `(max - min) / sum` of comparable `rated_reserves`, with `IMBALANCE_PRECISION`.
+///
+/// It is zero for a balanced pool and approaches one as the pool is drained of some token.
+pub fn imbalance(rated_reserves: &[u128]) -> u32 {
+    let (Some(&max), Some(&min)) = (rated_reserves.iter().max(), rated_reserves.iter().min())
+    else {
+        return 0;
+    };
+    let sum = rated_reserves
+        .iter()
+        .fold(U256::zero(), |sum, &reserve| sum + reserve);
+    if sum.is_zero() {
+        return 0;
+    }
+    // max - min <= sum, so the result does not exceed `IMBALANCE_PRECISION`
+    (U256::from(max - min) * IMBALANCE_PRECISION / sum).as_u32()
+}
+
+/// Owner-set parameters of the automatic amplification coefficient adjustment.
+#[derive(Debug, scale::Encode, scale::Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct AutoAmpConfig {
+    /// Lowest amplification coefficient (an integer) the pool ramps to.
+    pub min_amp_coef: u128,
+    /// Highest amplification coefficient (an integer) the pool ramps to.
+    pub max_amp_coef: u128,
+    /// Imbalance EMA (with `IMBALANCE_PRECISION`) above which the amplification coefficient is lowered.
+    pub lower_threshold: u32,
+    /// Imbalance EMA (with `IMBALANCE_PRECISION`) below which the amplification coefficient is raised.
+    pub raise_threshold: u32,
+    /// Time constant of the imbalance EMA, in milliseconds.
+    pub ema_period: u64,
+}
+
+/// Automatic adjustment of the amplification coefficient to the imbalance of the pool.
+///
+/// Tracks the exponential moving average (EMA) of the imbalance of reserves. While it is above
+/// `lower_threshold` the amplification coefficient is ramped down towards `min_amp_coef`, protecting
+/// liquidity providers during sustained depegs, and while it is below `raise_threshold` it is ramped
+/// back up towards `max_amp_coef`. Each ramp lasts `MIN_RAMP_DURATION` and is restricted
+/// to `MAX_AMP_CHANGE`, as ramps started by the owner.
+#[derive(Debug, scale::Encode, scale::Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct AutoAmp {
+    config: AutoAmpConfig,
+    /// EMA of the imbalance (with `IMBALANCE_PRECISION`).
+    imbalance_ema: u32,
+    /// Time of the last update of the EMA.
+    last_update: u64,
+}
+
+impl AutoAmp {
+    /// Starts tracking the imbalance from `imbalance` at `current_time`.
+    pub fn new(
+        config: AutoAmpConfig,
+        imbalance: u32,
+        current_time: u64,
+    ) -> Result<Self, AmpCoefError> {
+        ensure!(config.min_amp_coef >= MIN_AMP, AmpCoefError::TooLow);
+        ensure!(config.max_amp_coef <= MAX_AMP, AmpCoefError::TooHigh);
+        ensure!(
+            config.min_amp_coef <= config.max_amp_coef
+                && config.raise_threshold <= config.lower_threshold
+                && config.lower_threshold <= IMBALANCE_PRECISION
+                && config.ema_period > 0,
+            AmpCoefError::InvalidAutoAmpConfig
+        );
+        Ok(Self {
+            config,
+            imbalance_ema: imbalance,
+            last_update: current_time,
+        })
+    }
+
+    pub fn config(&self) -> AutoAmpConfig {
+        self.config
+    }
+
+    /// Returns EMA of the imbalance (with `IMBALANCE_PRECISION`) as of the last update.
+    pub fn imbalance_ema(&self) -> u32 {
+        self.imbalance_ema
+    }
+
+    /// Accounts for `imbalance` of the reserves held since the last update, and ramps `amp_coef`
+    /// if the EMA crosses a threshold at `current_time`.
+    ///
+    /// Should be called before each change of the reserves, except for proportional withdrawals,
+    /// which do not change the imbalance.
+    /// Returns a tuple of the future amplification coefficient and the ramping end time
+    /// if a ramp was started.
+    pub fn update(
+        &mut self,
+        amp_coef: &mut AmpCoef,
+        imbalance: u32,
+        current_time: u64,
+    ) -> Result<Option<(u128, u64)>, AmpCoefError> {
+        let elapsed = current_time.saturating_sub(self.last_update) as u128;
+        // ema += (imbalance - ema) * elapsed / (elapsed + ema_period)
+        let weight_denom = elapsed + self.config.ema_period as u128;
+        let ema_delta =
+            (imbalance.abs_diff(self.imbalance_ema) as u128 * elapsed / weight_denom) as u32;
+        if imbalance >= self.imbalance_ema {
+            self.imbalance_ema += ema_delta;
+        } else {
+            self.imbalance_ema -= ema_delta;
+        }
+        self.last_update = self.last_update.max(current_time);
+
+        let bound = if self.imbalance_ema > self.config.lower_threshold {
+            self.config.min_amp_coef
+        } else if self.imbalance_ema < self.config.raise_threshold {
+            self.config.max_amp_coef
+        } else {
+            return Ok(None);
+        };
+        let current_amp_coef = amp_coef.compute_amp_coef_precise(current_time)?;
+        let direction = (bound * A_PRECISION).cmp(&current_amp_coef);
+        // let ramps towards the bound finish
+        if let Some((future_amp_coef, _)) = amp_coef.future_amp_coef(current_time) {
+            if (future_amp_coef * A_PRECISION).cmp(&current_amp_coef) == direction {
+                return Ok(None);
+            }
+        }
+        let future_amp_coef = match direction {
+            Ordering::Less => {
+                let denom = MAX_AMP_CHANGE * A_PRECISION;
+                // round up, so that the change is not too large
+                bound.max((current_amp_coef + denom - 1) / denom)
+            }
+            Ordering::Greater => bound.min(current_amp_coef * MAX_AMP_CHANGE / A_PRECISION),
+            Ordering::Equal => return Ok(None),
+        };
+        if future_amp_coef * A_PRECISION == current_amp_coef {
+            return Ok(None);
+        }
+        let future_time = current_time.saturating_add(MIN_RAMP_DURATION);
+        amp_coef.ramp_amp_coef(future_amp_coef, future_time, current_time)?;
+        Ok(Some((future_amp_coef, future_time)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: u64 = 3_600_000;
+
+    const CONFIG: AutoAmpConfig = AutoAmpConfig {
+        min_amp_coef: 100,
+        max_amp_coef: 1000,
+        lower_threshold: IMBALANCE_PRECISION / 10,
+        raise_threshold: IMBALANCE_PRECISION / 50,
+        ema_period: HOUR,
+    };
+
+    #[test]
+    fn imbalance_computation() {
+        assert_eq!(imbalance(&[100, 100, 100]), 0);
+        assert_eq!(imbalance(&[150, 50]), IMBALANCE_PRECISION / 2);
+        assert_eq!(imbalance(&[0, 300, 100]), 3 * IMBALANCE_PRECISION / 4);
+        assert_eq!(imbalance(&[0, 0]), 0);
+        assert_eq!(imbalance(&[]), 0);
+        assert_eq!(imbalance(&[u128::MAX, 0]), IMBALANCE_PRECISION);
+    }
+
+    #[test]
+    fn invalid_config() {
+        let new = |config| AutoAmp::new(config, 0, 0).map(|_| ());
+        assert_eq!(new(CONFIG), Ok(()));
+        assert_eq!(
+            new(AutoAmpConfig {
+                min_amp_coef: 0,
+                ..CONFIG
+            }),
+            Err(AmpCoefError::TooLow)
+        );
+        assert_eq!(
+            new(AutoAmpConfig {
+                max_amp_coef: MAX_AMP + 1,
+                ..CONFIG
+            }),
+            Err(AmpCoefError::TooHigh)
+        );
+        assert_eq!(
+            new(AutoAmpConfig {
+                min_amp_coef: 1001,
+                ..CONFIG
+            }),
+            Err(AmpCoefError::InvalidAutoAmpConfig)
+        );
+        assert_eq!(
+            new(AutoAmpConfig {
+                raise_threshold: CONFIG.lower_threshold + 1,
+                ..CONFIG
+            }),
+            Err(AmpCoefError::InvalidAutoAmpConfig)
+        );
+        assert_eq!(
+            new(AutoAmpConfig {
+                ema_period: 0,
+                ..CONFIG
+            }),
+            Err(AmpCoefError::InvalidAutoAmpConfig)
+        );
+    }
+
+    #[test]
+    fn ema_follows_imbalance() {
+        let mut amp_coef = AmpCoef::new(1000).unwrap();
+        let mut auto_amp = AutoAmp::new(CONFIG, 0, 0).unwrap();
+        let imbalance = IMBALANCE_PRECISION / 20;
+        // no time elapsed
+        assert_eq!(auto_amp.update(&mut amp_coef, imbalance, 0), Ok(None));
+        assert_eq!(auto_amp.imbalance_ema(), 0);
+        // ema_period elapsed
+        assert_eq!(auto_amp.update(&mut amp_coef, imbalance, HOUR), Ok(None));
+        assert_eq!(auto_amp.imbalance_ema(), imbalance / 2);
+        assert_eq!(auto_amp.update(&mut amp_coef, 0, 2 * HOUR), Ok(None));
+        assert_eq!(auto_amp.imbalance_ema(), imbalance / 4);
+    }
+
+    #[test]
+    fn lowers_amp_on_sustained_imbalance() {
+        let mut amp_coef = AmpCoef::new(1000).unwrap();
+        let mut auto_amp = AutoAmp::new(CONFIG, 0, 0).unwrap();
+        let imbalance = IMBALANCE_PRECISION / 2;
+        // a short imbalance is smoothed out
+        assert_eq!(
+            auto_amp.update(&mut amp_coef, imbalance, HOUR / 10),
+            Ok(None)
+        );
+        assert_eq!(
+            auto_amp.update(&mut amp_coef, imbalance, HOUR),
+            Ok(Some((100, HOUR + MIN_RAMP_DURATION)))
+        );
+        assert_eq!(amp_coef.compute_amp_coef(HOUR), Ok(1000));
+        assert_eq!(
+            amp_coef.compute_amp_coef(HOUR + MIN_RAMP_DURATION / 2),
+            Ok(550)
+        );
+        // the ramp is not restarted
+        assert_eq!(
+            auto_amp.update(&mut amp_coef, imbalance, 2 * HOUR),
+            Ok(None)
+        );
+        assert_eq!(
+            amp_coef.future_amp_coef(2 * HOUR),
+            Some((100, HOUR + MIN_RAMP_DURATION))
+        );
+        assert_eq!(amp_coef.compute_amp_coef(HOUR + MIN_RAMP_DURATION), Ok(100));
+        // at the bound
+        assert_eq!(
+            auto_amp.update(&mut amp_coef, imbalance, 2 * MIN_RAMP_DURATION),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn raises_amp_when_balanced() {
+        let mut amp_coef = AmpCoef::new(20).unwrap();
+        let mut auto_amp = AutoAmp::new(CONFIG, IMBALANCE_PRECISION / 20, 0).unwrap();
+        // between the thresholds
+        assert_eq!(auto_amp.update(&mut amp_coef, 0, HOUR / 2), Ok(None));
+        // the change is restricted to `MAX_AMP_CHANGE`
+        assert_eq!(
+            auto_amp.update(&mut amp_coef, 0, 2 * HOUR),
+            Ok(Some((200, 2 * HOUR + MIN_RAMP_DURATION)))
+        );
+        let now = 2 * HOUR + MIN_RAMP_DURATION;
+        assert_eq!(
+            auto_amp.update(&mut amp_coef, 0, now),
+            Ok(Some((1000, now + MIN_RAMP_DURATION)))
+        );
+    }
+
+    #[test]
+    fn reverses_ramp_away_from_bound() {
+        let mut amp_coef = AmpCoef::new(500).unwrap();
+        assert_eq!(amp_coef.ramp_amp_coef(1000, MIN_RAMP_DURATION, 0), Ok(()));
+        let mut auto_amp = AutoAmp::new(CONFIG, IMBALANCE_PRECISION / 2, 0).unwrap();
+        let now = MIN_RAMP_DURATION / 2;
+        assert_eq!(
+            auto_amp.update(&mut amp_coef, IMBALANCE_PRECISION / 2, now),
+            Ok(Some((100, now + MIN_RAMP_DURATION)))
+        );
+        assert_eq!(amp_coef.compute_amp_coef(now), Ok(750));
+    }
+}
@@ -1,10 +1,11 @@
 use crate::{
-    constants::stable_pool::{FEE_DENOM, MAX_PROTOCOL_FEE, MAX_TRADE_FEE},
+    constants::stable_pool::{FEE_DENOM, MAX_PROTOCOL_FEE, MAX_TRADE_FEE, MIN_RAMP_DURATION},
+    ensure,
     math::{casted_mul, MathError},
 };
 
 #[ink::storage_item]
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct Fees {
     pub trade_fee: u32,
     pub protocol_fee: u32,
@@ -65,11 +66,148 @@ impl Fees {
                 .checked_mul(4)
                 .ok_or(MathError::MulOverflow(62))?,
         )
-        .ok_or(MathError::DivByZero(61))?;
+        .ok_or(MathError::DivByZero(62))?;
         u128_ratio(amount, adjusted_trade_fee, FEE_DENOM)
     }
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub enum FeeScheduleError {
+    InvalidFee,
+    RampDurationTooShort,
+}
+
+/// Trade and protocol fees, linearly ramped over time.
+///
+/// Times are given explicitly (in milliseconds, as the block timestamp),
+/// so that the same logic is used on-chain and by off-chain models.
+#[ink::storage_item]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FeeSchedule {
+    /// Fees at the start of the ramp.
+    init_fees: Fees,
+    /// Target fees of the ramp.
+    future_fees: Fees,
+    /// Start of the ramp.
+    init_time: u64,
+    /// End of the ramp.
+    future_time: u64,
+}
+
+impl FeeSchedule {
+    /// Creates a schedule of constant `fees`.
+    pub fn new(fees: Fees) -> Self {
+        Self {
+            init_fees: fees,
+            future_fees: fees,
+            init_time: 0,
+            future_time: 0,
+        }
+    }
+
+    /// Returns fees at `current_time`.
+    ///
+    /// Each fee is interpolated separately and rounded towards the initial one,
+    /// so it is always within the bounds of [`Fees::new`].
+    pub fn compute_fees(&self, current_time: u64) -> Fees {
+        if current_time >= self.future_time {
+            return self.future_fees;
+        }
+        if current_time <= self.init_time {
+            return self.init_fees;
+        }
+        // init_time < current_time < future_time
+        let time_range = (self.future_time - self.init_time) as u128;
+        let time_delta = (current_time - self.init_time) as u128;
+        let interpolate = |init: u32, future: u32| {
+            // fee range is below 2^32 and time delta below 2^64, so it does not overflow
+            let delta = (future.abs_diff(init) as u128 * time_delta / time_range) as u32;
+            if future >= init {
+                init + delta
+            } else {
+                init - delta
+            }
+        };
+        Fees {
+            trade_fee: interpolate(self.init_fees.trade_fee, self.future_fees.trade_fee),
+            protocol_fee: interpolate(self.init_fees.protocol_fee, self.future_fees.protocol_fee),
+        }
+    }
+
+    /// Sets constant `fees`, stopping the ramp (if any).
+    pub fn set_fees(&mut self, fees: Fees) {
+        *self = Self::new(fees);
+    }
+
+    /// Starts ramping from the fees at `current_time` to `future_trade_fee` and `future_protocol_fee`,
+    /// reached at `future_time`. The fees are validated as in [`Fees::new`].
+    pub fn ramp_fees(
+        &mut self,
+        future_trade_fee: u32,
+        future_protocol_fee: u32,
+        future_time: u64,
+        current_time: u64,
+    ) -> Result<(), FeeScheduleError> {
+        let future_fees =
+            Fees::new(future_trade_fee, future_protocol_fee).ok_or(FeeScheduleError::InvalidFee)?;
+        ensure!(
+            future_time
+                .checked_sub(current_time)
+                .is_some_and(|ramp_duration| ramp_duration >= MIN_RAMP_DURATION),
+            FeeScheduleError::RampDurationTooShort
+        );
+        self.init_fees = self.compute_fees(current_time);
+        self.init_time = current_time;
+        self.future_fees = future_fees;
+        self.future_time = future_time;
+        Ok(())
+    }
+
+    /// Stops ramping the fees at `current_time`. If ramping is not in progress, it does not influence the fees.
+    pub fn stop_ramp_fees(&mut self, current_time: u64) {
+        let fees = self.compute_fees(current_time);
+        self.init_fees = fees;
+        self.future_fees = fees;
+        self.init_time = current_time;
+        self.future_time = current_time;
+    }
+
+    /// Returns a tuple of the future fees and the ramping end time.
+    /// Returns `None` if the fees are not in ramping period at `current_time`.
+    pub fn future_fees(&self, current_time: u64) -> Option<(Fees, u64)> {
+        if current_time < self.future_time {
+            Some((self.future_fees, self.future_time))
+        } else {
+            None
+        }
+    }
+}
+
+/// Amount credited to the recipient of `amount` of a token charging `transfer_fee` on transfer.
+/// - `transfer_fee` given as an integer with 1e9 precision
+pub fn amount_net_of_transfer_fee(amount: u128, transfer_fee: u32) -> Result<u128, MathError> {
+    amount
+        .checked_sub(u128_ratio(amount, transfer_fee, FEE_DENOM)?)
+        .ok_or(MathError::SubUnderflow(63))
+}
+
+/// Amount which has to be transferred of a token charging `transfer_fee` on transfer,
+/// so that the recipient is credited at least `amount`.
+/// - `transfer_fee` given as an integer with 1e9 precision
+pub fn amount_gross_of_transfer_fee(amount: u128, transfer_fee: u32) -> Result<u128, MathError> {
+    let net_denom = FEE_DENOM
+        .checked_sub(transfer_fee)
+        .ok_or(MathError::SubUnderflow(64))?;
+    // round up
+    casted_mul(amount, FEE_DENOM.into())
+        .checked_add(net_denom.saturating_sub(1).into())
+        .ok_or(MathError::AddOverflow(63))?
+        .checked_div(net_denom.into())
+        .ok_or(MathError::DivByZero(63))?
+        .try_into()
+        .map_err(|_| MathError::CastOverflow(63))
+}
+
 fn u128_ratio(amount: u128, num: u32, denom: u32) -> Result<u128, MathError> {
     casted_mul(amount, num.into())
         .checked_div(denom.into())
@@ -79,9 +217,12 @@ fn u128_ratio(amount: u128, num: u32, denom: u32) -> Result<u128, MathError> {
 }
 #[cfg(test)]
 mod tests {
-    use crate::constants::stable_pool::{MAX_PROTOCOL_FEE, MAX_TRADE_FEE};
+    use crate::constants::stable_pool::{MAX_PROTOCOL_FEE, MAX_TRADE_FEE, MIN_RAMP_DURATION};
 
-    use super::Fees;
+    use super::{
+        amount_gross_of_transfer_fee, amount_net_of_transfer_fee, FeeSchedule, FeeScheduleError,
+        Fees,
+    };
 
     #[test]
     fn test_max_fees() {
@@ -124,4 +265,94 @@ mod tests {
             "Protocol fee should be 50%"
         );
     }
+
+    #[test]
+    fn test_transfer_fee() {
+        let transfer_fee: u32 = 30_000_000; // 3%
+        let amount: u128 = 1_000_000;
+        assert_eq!(
+            amount_net_of_transfer_fee(amount, transfer_fee).expect("Should compute net amount"),
+            970_000,
+            "Should deduct 3%"
+        );
+        assert_eq!(
+            amount_net_of_transfer_fee(amount, 0).expect("Should compute net amount"),
+            amount,
+            "Should not deduct anything"
+        );
+        for amount in [1, 7, 999, 1_000_000, 123_456_789_012] {
+            let gross = amount_gross_of_transfer_fee(amount, transfer_fee)
+                .expect("Should compute gross amount");
+            let net =
+                amount_net_of_transfer_fee(gross, transfer_fee).expect("Should compute net amount");
+            assert!(net >= amount, "Gross amount should cover the fee");
+            assert!(net - amount <= 1, "Gross amount should not overpay");
+        }
+        assert!(
+            amount_gross_of_transfer_fee(amount, 1_000_000_000).is_err(),
+            "Should fail for 100% transfer fee"
+        );
+    }
+
+    #[test]
+    fn fee_schedule_ramp() {
+        let fees = |trade_fee, protocol_fee| Fees::new(trade_fee, protocol_fee).unwrap();
+        let mut schedule = FeeSchedule::new(fees(1_000_000, 200_000_000));
+        assert_eq!(
+            schedule.ramp_fees(3_000_000, 100_000_000, 100 + MIN_RAMP_DURATION - 1, 100),
+            Err(FeeScheduleError::RampDurationTooShort)
+        );
+        assert_eq!(
+            schedule.ramp_fees(MAX_TRADE_FEE + 1, 100_000_000, 100 + MIN_RAMP_DURATION, 100),
+            Err(FeeScheduleError::InvalidFee)
+        );
+        assert_eq!(schedule.compute_fees(100), fees(1_000_000, 200_000_000));
+        assert_eq!(
+            schedule.ramp_fees(3_000_000, 100_000_000, 100 + MIN_RAMP_DURATION, 100),
+            Ok(())
+        );
+        assert_eq!(schedule.compute_fees(100), fees(1_000_000, 200_000_000));
+        let half_time = 100 + MIN_RAMP_DURATION / 2;
+        assert_eq!(
+            schedule.compute_fees(half_time),
+            fees(2_000_000, 150_000_000)
+        );
+        assert_eq!(
+            schedule.future_fees(half_time),
+            Some((fees(3_000_000, 100_000_000), 100 + MIN_RAMP_DURATION))
+        );
+        assert_eq!(
+            schedule.compute_fees(100 + MIN_RAMP_DURATION),
+            fees(3_000_000, 100_000_000)
+        );
+        assert_eq!(schedule.future_fees(100 + MIN_RAMP_DURATION), None);
+    }
+
+    #[test]
+    fn fee_schedule_stop_ramp() {
+        let fees = |trade_fee, protocol_fee| Fees::new(trade_fee, protocol_fee).unwrap();
+        let mut schedule = FeeSchedule::new(fees(4_000_000, 0));
+        assert_eq!(
+            schedule.ramp_fees(0, 0, 100 + MIN_RAMP_DURATION, 100),
+            Ok(())
+        );
+        let stop_time = 100 + MIN_RAMP_DURATION / 4;
+        schedule.stop_ramp_fees(stop_time);
+        assert_eq!(schedule.future_fees(stop_time), None);
+        assert_eq!(schedule.compute_fees(stop_time), fees(3_000_000, 0));
+        assert_eq!(
+            schedule.compute_fees(100 + MIN_RAMP_DURATION),
+            fees(3_000_000, 0)
+        );
+        // ramp starts from the current fees
+        let now = 100 + MIN_RAMP_DURATION;
+        assert_eq!(
+            schedule.ramp_fees(1_000_000, 0, now + MIN_RAMP_DURATION, now),
+            Ok(())
+        );
+        assert_eq!(
+            schedule.compute_fees(now + MIN_RAMP_DURATION / 2),
+            fees(2_000_000, 0)
+        );
+    }
 }
@@ -1,26 +1,96 @@
+pub mod amp_coef;
+pub mod auto_amp;
 pub mod fees;
+#[cfg(test)]
+mod proptests;
 
 use crate::{
-    constants::stable_pool::RATE_PRECISION,
+    constants::stable_pool::{A_PRECISION, FEE_DENOM, RATE_PRECISION, TOKEN_TARGET_DECIMALS},
     math::{casted_mul, MathError},
 };
+use core::cmp::Ordering;
 use ink::prelude::vec::Vec;
-use primitive_types::U256;
+use primitive_types::{U256, U512};
 
 use fees::Fees;
 
 /// Max number of iterations performed in Newton–Raphson method
 const MAX_ITERATIONS: u8 = 255;
 
-fn amount_to_rated(amount: u128, scaled_rate: u128) -> Result<u128, MathError> {
-    casted_mul(amount, scaled_rate)
-        .checked_div(U256::from(RATE_PRECISION))
-        .unwrap()
+/// Token rate combined with the token precision.
+///
+/// Converts token amounts to comparable amounts with `TOKEN_TARGET_DECIMALS` decimal places:
+/// `rated_amount = amount * numerator / denominator`.
+/// Tokens with fewer decimal places are scaled up (precision goes to the numerator),
+/// tokens with more decimal places are scaled down (precision goes to the denominator).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScaledRate {
+    numerator: u128,
+    denominator: u128,
+}
+
+impl ScaledRate {
+    /// Creates a scaled rate of a token with `decimals` decimal places and `rate`
+    /// given with `RATE_PRECISION`.
+    pub fn new(rate: u128, decimals: u8) -> Result<Self, MathError> {
+        if decimals <= TOKEN_TARGET_DECIMALS {
+            let precision = 10u128
+                .checked_pow((TOKEN_TARGET_DECIMALS - decimals).into())
+                .ok_or(MathError::MulOverflow(120))?;
+            Ok(Self {
+                numerator: rate
+                    .checked_mul(precision)
+                    .ok_or(MathError::MulOverflow(121))?,
+                denominator: RATE_PRECISION,
+            })
+        } else {
+            let precision = 10u128
+                .checked_pow((decimals - TOKEN_TARGET_DECIMALS).into())
+                .ok_or(MathError::MulOverflow(122))?;
+            Ok(Self {
+                numerator: rate,
+                denominator: RATE_PRECISION
+                    .checked_mul(precision)
+                    .ok_or(MathError::MulOverflow(123))?,
+            })
+        }
+    }
+}
+
+/// Divides `a * b` by `c`, rounding the result up if `round_up` is set, otherwise down.
+fn mul_div(a: u128, b: u128, c: u128, round_up: bool) -> Result<U256, MathError> {
+    let c = U256::from(c);
+    let product = casted_mul(a, b);
+    let quotient = product.checked_div(c).ok_or(MathError::DivByZero(120))?;
+    if round_up && !(product % c).is_zero() {
+        quotient
+            .checked_add(1.into())
+            .ok_or(MathError::AddOverflow(120))
+    } else {
+        Ok(quotient)
+    }
+}
+
+/// Converts `amount` to the comparable amount, rounding down.
+fn amount_to_rated(amount: u128, scaled_rate: ScaledRate) -> Result<u128, MathError> {
+    mul_div(
+        amount,
+        scaled_rate.numerator,
+        scaled_rate.denominator,
+        false,
+    )?
+    .try_into()
+    .map_err(|_| MathError::CastOverflow(120))
+}
+
+/// Converts `amount` to the comparable amount, rounding up.
+fn amount_to_rated_ceil(amount: u128, scaled_rate: ScaledRate) -> Result<u128, MathError> {
+    mul_div(amount, scaled_rate.numerator, scaled_rate.denominator, true)?
         .try_into()
-        .map_err(|_| MathError::CastOverflow(120))
+        .map_err(|_| MathError::CastOverflow(122))
 }
 
-fn amounts_to_rated(amounts: &[u128], scaled_rates: &[u128]) -> Result<Vec<u128>, MathError> {
+fn amounts_to_rated(amounts: &[u128], scaled_rates: &[ScaledRate]) -> Result<Vec<u128>, MathError> {
     amounts
         .iter()
         .zip(scaled_rates.iter())
@@ -28,15 +98,33 @@ fn amounts_to_rated(amounts: &[u128], scaled_rates: &[u128]) -> Result<Vec<u128>
         .collect()
 }
 
-fn amount_from_rated(amount: u128, scaled_rate: u128) -> Result<u128, MathError> {
-    casted_mul(amount, RATE_PRECISION)
-        .checked_div(U256::from(scaled_rate))
-        .unwrap()
-        .try_into()
-        .map_err(|_| MathError::CastOverflow(121))
+fn amounts_to_rated_ceil(
+    amounts: &[u128],
+    scaled_rates: &[ScaledRate],
+) -> Result<Vec<u128>, MathError> {
+    amounts
+        .iter()
+        .zip(scaled_rates.iter())
+        .map(|(amount, &rate)| amount_to_rated_ceil(*amount, rate))
+        .collect()
+}
+
+/// Converts comparable `amount` back to the token amount, rounding down.
+fn amount_from_rated(amount: u128, scaled_rate: ScaledRate) -> Result<u128, MathError> {
+    mul_div(
+        amount,
+        scaled_rate.denominator,
+        scaled_rate.numerator,
+        false,
+    )
+    .map_err(|_| MathError::DivByZero(121))?
+    .try_into()
+    .map_err(|_| MathError::CastOverflow(121))
 }
 
 /// Computes stable swap invariant (D)
+///
+/// `amp_coef` is given with `A_PRECISION`, as are all amplification coefficients in this module.
 fn compute_d(amounts: &Vec<u128>, amp_coef: u128) -> Result<U256, MathError> {
     // SUM{x_i}
     let amount_sum = amounts.iter().try_fold(U256::from(0), |acc, &amount| {
@@ -49,15 +137,15 @@ fn compute_d(amounts: &Vec<u128>, amp_coef: u128) -> Result<U256, MathError> {
         let n = amounts.len() as u32;
         // n^n
         let nn = n.checked_pow(n).ok_or(MathError::MulOverflow(1))?;
-        // A * n^n
+        // A * n^n (with A_PRECISION)
         let ann: U256 = casted_mul(amp_coef, nn.into());
-        // A * n^n * SUM{x_i}
+        // A * n^n * SUM{x_i} (with A_PRECISION)
         let ann_sum = ann
             .checked_mul(amount_sum)
             .ok_or(MathError::MulOverflow(2))?;
-        // A * n^n - 1
+        // A * n^n - 1 (with A_PRECISION)
         let ann_sub_one = ann
-            .checked_sub(1.into())
+            .checked_sub(A_PRECISION.into())
             .ok_or(MathError::SubUnderflow(1))?;
         // n + 1
         let n_add_one = n.checked_add(1).ok_or(MathError::AddOverflow(2))?;
@@ -75,6 +163,8 @@ fn compute_d(amounts: &Vec<u128>, amp_coef: u128) -> Result<U256, MathError> {
 }
 
 /// Computes next step's approximation of D in Newton-Raphson method.
+/// `ann_sum` and `ann_sub_one` are given with `A_PRECISION`, so both the numerator
+/// and the denominator are scaled by `A_PRECISION`.
 /// Returns d_next, or error if any math error occurred.
 fn compute_d_next(
     d_prev: U256,
@@ -94,11 +184,13 @@ fn compute_d_next(
             .checked_div(amount.into())
             .ok_or(MathError::DivByZero(1))?;
     }
-    d_prod = d_prod.checked_div(nn.into()).unwrap();
+    d_prod = d_prod
+        .checked_div(nn.into())
+        .ok_or(MathError::DivByZero(18))?;
     let numerator = d_prev
         .checked_mul(
             d_prod
-                .checked_mul(n.into())
+                .checked_mul((n as u128 * A_PRECISION).into())
                 .ok_or(MathError::MulOverflow(5))?
                 .checked_add(ann_sum)
                 .ok_or(MathError::AddOverflow(3))?,
@@ -109,7 +201,7 @@ fn compute_d_next(
         .ok_or(MathError::MulOverflow(7))?
         .checked_add(
             d_prod
-                .checked_mul(n_add_one.into())
+                .checked_mul((n_add_one as u128 * A_PRECISION).into())
                 .ok_or(MathError::MulOverflow(8))?,
         )
         .ok_or(MathError::AddOverflow(4))?;
@@ -161,6 +253,8 @@ fn compute_y(
     c = c
         .checked_mul(d)
         .ok_or(MathError::MulOverflow(12))?
+        .checked_mul(A_PRECISION.into())
+        .ok_or(MathError::MulOverflow(26))?
         .checked_div(
             ann.checked_mul((n).checked_pow(n).ok_or(MathError::MulOverflow(13))?.into())
                 .ok_or(MathError::MulOverflow(14))?,
@@ -168,6 +262,8 @@ fn compute_y(
         .ok_or(MathError::DivByZero(5))?;
     // reserves_sum + d / ( A * n^n)
     let b: U256 = d
+        .checked_mul(A_PRECISION.into())
+        .ok_or(MathError::MulOverflow(27))?
         .checked_div(ann)
         .ok_or(MathError::DivByZero(6))?
         .checked_add(reserves_sum)
@@ -214,6 +310,10 @@ fn swap_to(
     fees: &Fees,
     amp_coef: u128,
 ) -> Result<(u128, u128), MathError> {
+    // nothing to swap, the rounding error of `y` could otherwise be paid out
+    if token_in_amount == 0 {
+        return Ok((0, 0));
+    }
     let y = compute_y(
         token_in_amount
             .checked_add(current_reserves[token_in_id])
@@ -237,8 +337,17 @@ fn swap_to(
     Ok((amount_swapped, fee))
 }
 
+/// Computes the invariant (D) of the pool with `reserves`, in comparable amounts.
+pub fn rated_compute_d(
+    rates: &[ScaledRate],
+    reserves: &[u128],
+    amp_coef: u128,
+) -> Result<U256, MathError> {
+    compute_d(&amounts_to_rated(reserves, rates)?, amp_coef)
+}
+
 pub fn rated_swap_to(
-    rates: &[u128],
+    rates: &[ScaledRate],
     token_in_id: usize,
     token_in_amount: u128,
     token_out_id: usize,
@@ -298,7 +407,7 @@ fn swap_from(
 }
 
 pub fn rated_swap_from(
-    rates: &[u128],
+    rates: &[ScaledRate],
     token_in_id: usize,
     token_out_amount: u128,
     token_out_id: usize,
@@ -306,7 +415,8 @@ pub fn rated_swap_from(
     fees: &Fees,
     amp_coef: u128,
 ) -> Result<(u128, u128), MathError> {
-    let r_token_out_amount = amount_to_rated(token_out_amount, rates[token_out_id])?;
+    // round up the amount taken out of the pool, for the protocol advantage
+    let r_token_out_amount = amount_to_rated_ceil(token_out_amount, rates[token_out_id])?;
     let r_current_reserves = amounts_to_rated(current_reserves, rates)?;
     let (r_dy, r_fee) = swap_from(
         token_in_id,
@@ -324,6 +434,372 @@ pub fn rated_swap_from(
     Ok((dy, fee))
 }
 
+/// Marginal price of `token_x_id` in `token_y_id` at the given reserves, i.e. the amount of
+/// `token_y_id` paid for an infinitesimal amount of `token_x_id`, without fees.
+///
+/// Differentiating the invariant gives `price = (y / x) * (Ann * x + P) / (Ann * y + P)`
+/// where `P = D^(n+1) / (n^n * Prod{x_i})`.
+struct SpotPrice {
+    x: U256,
+    y: U256,
+    /// A * n^n (with A_PRECISION)
+    ann: U256,
+    /// P (with A_PRECISION)
+    p: U256,
+    /// Ann * y + P (with A_PRECISION)
+    ann_y_p: U256,
+    /// price with RATE_PRECISION
+    price: U256,
+}
+
+impl SpotPrice {
+    fn new(
+        reserves: &Vec<u128>,
+        token_x_id: usize,
+        token_y_id: usize,
+        amp_coef: u128,
+    ) -> Result<Self, MathError> {
+        let n = reserves.len() as u32;
+        let nn = n.checked_pow(n).ok_or(MathError::MulOverflow(130))?;
+        let ann: U256 = casted_mul(amp_coef, nn.into());
+        let d = compute_d(reserves, amp_coef)?;
+        // d_prod = D^(n+1) / Prod{x_i}
+        let mut d_prod = d;
+        for &reserve in reserves {
+            d_prod = d_prod
+                .checked_mul(d)
+                .ok_or(MathError::MulOverflow(131))?
+                .checked_div(reserve.into())
+                .ok_or(MathError::DivByZero(130))?;
+        }
+        let p = d_prod
+            .checked_mul(A_PRECISION.into())
+            .ok_or(MathError::MulOverflow(132))?
+            .checked_div(nn.into())
+            .ok_or(MathError::DivByZero(131))?;
+        let x = U256::from(reserves[token_x_id]);
+        let y = U256::from(reserves[token_y_id]);
+        let ann_x_p = ann
+            .checked_mul(x)
+            .ok_or(MathError::MulOverflow(133))?
+            .checked_add(p)
+            .ok_or(MathError::AddOverflow(130))?;
+        let ann_y_p = ann
+            .checked_mul(y)
+            .ok_or(MathError::MulOverflow(134))?
+            .checked_add(p)
+            .ok_or(MathError::AddOverflow(131))?;
+        let price = ann_x_p
+            .checked_mul(RATE_PRECISION.into())
+            .ok_or(MathError::MulOverflow(135))?
+            .checked_div(ann_y_p)
+            .ok_or(MathError::DivByZero(132))?
+            .checked_mul(y)
+            .ok_or(MathError::MulOverflow(136))?
+            .checked_div(x)
+            .ok_or(MathError::DivByZero(133))?;
+        Ok(Self {
+            x,
+            y,
+            ann,
+            p,
+            ann_y_p,
+            price,
+        })
+    }
+
+    /// Returns `-d(price)/dx` along the curve (with RATE_PRECISION) as a fraction
+    /// `(numerator, denominator)`, or `None` if the price does not decrease or the terms overflow.
+    ///
+    /// Along the curve `dy/dx = -price` and `dP/dx = P * (price / y - 1 / x)`, which gives
+    /// `d(price)/dx = (Ann * (1 - price) * (y - x * price) - P * y / x - P * price^2 * x / y) / (x * (Ann * y + P))`.
+    fn slope(&self) -> Option<(U256, U256)> {
+        let rp = U256::from(RATE_PRECISION);
+        let x_price = self.x.checked_mul(self.price)?;
+        let y_rp = self.y.checked_mul(rp)?;
+        // Ann * |1 - price| * |y - x * price|
+        let curvature = self
+            .ann
+            .checked_mul(rp.abs_diff(self.price))?
+            .checked_mul(y_rp.abs_diff(x_price))?
+            .checked_div(rp)?;
+        // P * y / x
+        let p_y = self
+            .p
+            .checked_mul(rp)?
+            .checked_mul(self.y)?
+            .checked_div(self.x)?;
+        // P * price^2 * x / y
+        let p_x = self
+            .price
+            .checked_mul(self.price)?
+            .checked_div(rp)?
+            .checked_mul(self.p)?
+            .checked_mul(self.x)?
+            .checked_div(self.y)?;
+        let decrease = p_y.checked_add(p_x)?;
+        let numerator = if (self.price < rp) == (x_price < y_rp) {
+            decrease.checked_sub(curvature)?
+        } else {
+            decrease.checked_add(curvature)?
+        };
+        if numerator.is_zero() {
+            return None;
+        }
+        Some((numerator, self.x.checked_mul(self.ann_y_p)?))
+    }
+}
+
+/// Finds the largest `x >= low` not above the root of a monotonic function, given that `low` is below it.
+///
+/// `newton_step(x)` returns how `x` compares to the root and Newton's approximation of the root (if any).
+/// The approximations are taken as long as they stay within the known bracket of the root,
+/// otherwise the bracket is bisected (or doubled, while there is no upper bound yet).
+fn newton_bracketed(
+    low: u128,
+    newton_step: impl Fn(u128) -> Result<(Ordering, Option<u128>), MathError>,
+) -> Result<u128, MathError> {
+    let mut low = low;
+    let mut high: Option<u128> = None;
+    let mut x = low;
+    for _ in 0..MAX_ITERATIONS {
+        let (ordering, next) = newton_step(x)?;
+        match ordering {
+            Ordering::Less => low = x,
+            Ordering::Equal => return Ok(x),
+            Ordering::Greater => high = Some(x),
+        }
+        if high.is_some_and(|high| high - low <= 1) {
+            return Ok(low);
+        }
+        x = match next {
+            Some(next) if next.abs_diff(x) <= 1 => {
+                if ordering == Ordering::Less {
+                    return Ok(x);
+                }
+                // converged from above, `x - 1` is still above `low`
+                x - 1
+            }
+            Some(next) if low < next && high.map_or(true, |high| next < high) => next,
+            _ => match high {
+                Some(high) => low + (high - low) / 2,
+                None => low.checked_mul(2).ok_or(MathError::MulOverflow(137))?,
+            },
+        };
+    }
+    Err(MathError::Precision(3))
+}
+
+/// Returns reserves after swapping `token_in_id` to `token_out_id`
+/// so that the reserve of `token_in_id` becomes `new_reserve_in`.
+/// The trade fee stays in the pool.
+fn reserves_after_swap(
+    token_in_id: usize,
+    new_reserve_in: u128,
+    token_out_id: usize,
+    current_reserves: &Vec<u128>,
+    fees: &Fees,
+    amp_coef: u128,
+) -> Result<Vec<u128>, MathError> {
+    let (amount_out, _) = swap_to(
+        token_in_id,
+        new_reserve_in
+            .checked_sub(current_reserves[token_in_id])
+            .ok_or(MathError::SubUnderflow(130))?,
+        token_out_id,
+        current_reserves,
+        fees,
+        amp_coef,
+    )?;
+    let mut new_reserves = current_reserves.clone();
+    new_reserves[token_in_id] = new_reserve_in;
+    new_reserves[token_out_id] = current_reserves[token_out_id]
+        .checked_sub(amount_out)
+        .ok_or(MathError::SubUnderflow(131))?;
+    Ok(new_reserves)
+}
+
+/// Computes the amount of `token_in_id` to swap to `token_out_id` which lowers
+/// the spot price of `token_in_id` in `token_out_id` to `target_price` (with RATE_PRECISION).
+/// Returns the largest amount that does not lower the price below the target,
+/// zero if the price is already at or below the target.
+///
+/// The trade fee stays in the pool, which is accounted for in the resulting price.
+/// NOTE: it does not check if `token_in_id` != `token_out_id`.
+fn swap_amount_for_price(
+    token_in_id: usize,
+    token_out_id: usize,
+    target_price: u128,
+    current_reserves: &Vec<u128>,
+    fees: &Fees,
+    amp_coef: u128,
+) -> Result<u128, MathError> {
+    let target_price = U256::from(target_price);
+    let reserve_in = current_reserves[token_in_id];
+    let new_reserve_in = newton_bracketed(reserve_in, |new_reserve_in| {
+        let new_reserves = reserves_after_swap(
+            token_in_id,
+            new_reserve_in,
+            token_out_id,
+            current_reserves,
+            fees,
+            amp_coef,
+        )?;
+        let spot = SpotPrice::new(&new_reserves, token_in_id, token_out_id, amp_coef)?;
+        let ordering = target_price.cmp(&spot.price);
+        // x + (price - target) / slope
+        let next = spot.slope().and_then(|(numerator, denominator)| {
+            let step: u128 = U256::try_from(
+                spot.price.abs_diff(target_price).full_mul(denominator) / U512::from(numerator),
+            )
+            .ok()?
+            .try_into()
+            .ok()?;
+            if ordering == Ordering::Less {
+                new_reserve_in.checked_add(step)
+            } else {
+                new_reserve_in.checked_sub(step)
+            }
+        });
+        Ok((ordering, next))
+    })?;
+    new_reserve_in
+        .checked_sub(reserve_in)
+        .ok_or(MathError::SubUnderflow(132))
+}
+
+/// Computes the amount of `token_in_id` to swap to `token_out_id` which raises the ratio
+/// of their reserves (`reserve_in / reserve_out`) to `target_ratio` (with RATE_PRECISION).
+/// Returns the largest amount that does not raise the ratio above the target,
+/// zero if the ratio is already at or above the target.
+///
+/// NOTE: it does not check if `token_in_id` != `token_out_id`.
+fn swap_amount_for_ratio(
+    token_in_id: usize,
+    token_out_id: usize,
+    target_ratio: u128,
+    current_reserves: &Vec<u128>,
+    fees: &Fees,
+    amp_coef: u128,
+) -> Result<u128, MathError> {
+    let target_ratio = U256::from(target_ratio);
+    let reserve_in = current_reserves[token_in_id];
+    // 1 - trade fee (with FEE_DENOM)
+    let fee_complement = FEE_DENOM
+        .checked_sub(fees.trade_fee)
+        .ok_or(MathError::SubUnderflow(133))?;
+    let new_reserve_in = newton_bracketed(reserve_in, |new_reserve_in| {
+        let new_reserves = reserves_after_swap(
+            token_in_id,
+            new_reserve_in,
+            token_out_id,
+            current_reserves,
+            fees,
+            amp_coef,
+        )?;
+        // f(x) = x * RATE_PRECISION - target_ratio * y
+        let scaled_in = casted_mul(new_reserve_in, RATE_PRECISION);
+        let scaled_out = target_ratio
+            .checked_mul(new_reserves[token_out_id].into())
+            .ok_or(MathError::MulOverflow(138))?;
+        let ordering = scaled_in.cmp(&scaled_out);
+        // dy/dx = -(1 - fee) * price, so f'(x) = RATE_PRECISION + target_ratio * (1 - fee) * price
+        let spot = SpotPrice::new(&new_reserves, token_in_id, token_out_id, amp_coef)?;
+        let next = target_ratio
+            .checked_mul(spot.price)
+            .and_then(|slope| slope.checked_mul(fee_complement.into()))
+            .and_then(|slope| slope.checked_div(casted_mul(RATE_PRECISION, FEE_DENOM.into())))
+            .and_then(|slope| slope.checked_add(RATE_PRECISION.into()))
+            .and_then(|slope| scaled_in.abs_diff(scaled_out).checked_div(slope))
+            .and_then(|step| u128::try_from(step).ok())
+            .and_then(|step| {
+                if ordering == Ordering::Less {
+                    new_reserve_in.checked_add(step)
+                } else {
+                    new_reserve_in.checked_sub(step)
+                }
+            });
+        Ok((ordering, next))
+    })?;
+    new_reserve_in
+        .checked_sub(reserve_in)
+        .ok_or(MathError::SubUnderflow(134))
+}
+
+/// Computes the imbalance of `current_reserves` (see [`auto_amp::imbalance`])
+/// after converting them to comparable amounts.
+pub fn rated_imbalance(rates: &[ScaledRate], current_reserves: &[u128]) -> Result<u32, MathError> {
+    Ok(auto_amp::imbalance(&amounts_to_rated(
+        current_reserves,
+        rates,
+    )?))
+}
+
+/// Computes the spot price of `token_in_id` in `token_out_id` (with RATE_PRECISION),
+/// i.e. the comparable amount of `token_out_id` paid for an infinitesimal comparable amount
+/// of `token_in_id`, without fees.
+pub fn rated_spot_price(
+    rates: &[ScaledRate],
+    token_in_id: usize,
+    token_out_id: usize,
+    current_reserves: &[u128],
+    amp_coef: u128,
+) -> Result<u128, MathError> {
+    let r_current_reserves = amounts_to_rated(current_reserves, rates)?;
+    SpotPrice::new(&r_current_reserves, token_in_id, token_out_id, amp_coef)?
+        .price
+        .try_into()
+        .map_err(|_| MathError::CastOverflow(130))
+}
+
+/// Computes the amount of `token_in_id` to swap to `token_out_id` which lowers
+/// their spot price (see [`rated_spot_price`]) to `target_price`, rounded down.
+/// Returns zero if the price is already at or below the target.
+pub fn rated_swap_amount_for_price(
+    rates: &[ScaledRate],
+    token_in_id: usize,
+    token_out_id: usize,
+    target_price: u128,
+    current_reserves: &[u128],
+    fees: &Fees,
+    amp_coef: u128,
+) -> Result<u128, MathError> {
+    let r_current_reserves = amounts_to_rated(current_reserves, rates)?;
+    let r_amount = swap_amount_for_price(
+        token_in_id,
+        token_out_id,
+        target_price,
+        &r_current_reserves,
+        fees,
+        amp_coef,
+    )?;
+    amount_from_rated(r_amount, rates[token_in_id])
+}
+
+/// Computes the amount of `token_in_id` to swap to `token_out_id` which raises the ratio
+/// of their comparable reserves to `target_ratio` (with RATE_PRECISION), rounded down.
+/// Returns zero if the ratio is already at or above the target.
+pub fn rated_swap_amount_for_ratio(
+    rates: &[ScaledRate],
+    token_in_id: usize,
+    token_out_id: usize,
+    target_ratio: u128,
+    current_reserves: &[u128],
+    fees: &Fees,
+    amp_coef: u128,
+) -> Result<u128, MathError> {
+    let r_current_reserves = amounts_to_rated(current_reserves, rates)?;
+    let r_amount = swap_amount_for_ratio(
+        token_in_id,
+        token_out_id,
+        target_ratio,
+        &r_current_reserves,
+        fees,
+        amp_coef,
+    )?;
+    amount_from_rated(r_amount, rates[token_in_id])
+}
+
 /// Given `deposit_amounts` user want deposit, calculates how many lpt
 /// are required to be minted.
 /// Returns a tuple of (lpt to mint, fee)
@@ -419,7 +895,7 @@ fn compute_lp_amount_for_deposit(
 }
 
 pub fn rated_compute_lp_amount_for_deposit(
-    rates: &[u128],
+    rates: &[ScaledRate],
     deposit_amounts: &[u128],
     old_reserves: &[u128],
     pool_token_supply: u128,
@@ -457,8 +933,19 @@ pub fn compute_amounts_given_lp(
     Ok(amounts)
 }
 
+/// Divides `a` by `b`, rounding the result up.
+fn checked_div_ceil(a: U256, b: U256) -> Option<U256> {
+    let quotient = a.checked_div(b)?;
+    if (a % b).is_zero() {
+        Some(quotient)
+    } else {
+        quotient.checked_add(1.into())
+    }
+}
+
 /// Given `withdraw_amounts` user want get, calculates how many lpt
-/// are required to be burnt
+/// are required to be burnt, rounded up for the protocol advantage.
+/// At least one share is burnt for a non-zero withdrawal, regardless of the rounding of D.
 /// Returns a tuple of (lpt to burn, fee part)
 fn compute_lp_amount_for_withdraw(
     withdraw_amounts: &[u128],
@@ -468,6 +955,7 @@ fn compute_lp_amount_for_withdraw(
     amp_coef: u128,
 ) -> Result<(u128, u128), MathError> {
     let n_coins = old_reserves.len() as u32;
+    let min_burn_shares = u128::from(withdraw_amounts.iter().any(|&amount| amount > 0));
     // Initial invariant, D0
     let d_0 = compute_d(old_reserves, amp_coef)?;
 
@@ -507,13 +995,16 @@ fn compute_lp_amount_for_withdraw(
         // (d1-d2) => fee part,
         // burn_shares = diff_shares + fee part
 
-        let burn_shares = U256::from(pool_token_supply)
-            .checked_mul(d_0.checked_sub(d_2).ok_or(MathError::SubUnderflow(28))?)
-            .ok_or(MathError::MulOverflow(23))?
-            .checked_div(d_0)
-            .ok_or(MathError::DivByZero(15))?
-            .try_into()
-            .map_err(|_| MathError::CastOverflow(8))?;
+        let burn_shares = checked_div_ceil(
+            U256::from(pool_token_supply)
+                .checked_mul(d_0.checked_sub(d_2).ok_or(MathError::SubUnderflow(28))?)
+                .ok_or(MathError::MulOverflow(23))?,
+            d_0,
+        )
+        .ok_or(MathError::DivByZero(15))?
+        .try_into()
+        .map_err(|_| MathError::CastOverflow(8))?;
+        let burn_shares = u128::max(burn_shares, min_burn_shares);
         let diff_shares = U256::from(pool_token_supply)
             .checked_mul(d_0.checked_sub(d_1).ok_or(MathError::SubUnderflow(29))?)
             .ok_or(MathError::MulOverflow(24))?
@@ -528,26 +1019,30 @@ fn compute_lp_amount_for_withdraw(
                 .ok_or(MathError::SubUnderflow(30))?,
         ))
     } else {
-        let burn_shares = U256::from(pool_token_supply)
-            .checked_mul(d_0.checked_sub(d_1).ok_or(MathError::SubUnderflow(31))?)
-            .ok_or(MathError::MulOverflow(25))?
-            .checked_div(d_0)
-            .ok_or(MathError::DivByZero(17))?
-            .try_into()
-            .map_err(|_| MathError::CastOverflow(10))?;
+        let burn_shares = checked_div_ceil(
+            U256::from(pool_token_supply)
+                .checked_mul(d_0.checked_sub(d_1).ok_or(MathError::SubUnderflow(31))?)
+                .ok_or(MathError::MulOverflow(25))?,
+            d_0,
+        )
+        .ok_or(MathError::DivByZero(17))?
+        .try_into()
+        .map_err(|_| MathError::CastOverflow(10))?;
+        let burn_shares = u128::max(burn_shares, min_burn_shares);
         Ok((burn_shares, 0))
     }
 }
 
 pub fn rated_compute_lp_amount_for_withdraw(
-    rates: &[u128],
+    rates: &[ScaledRate],
     withdraw_amounts: &[u128],
     old_reserves: &[u128],
     pool_token_supply: u128,
     fees: Option<&Fees>,
     amp_coef: u128,
 ) -> Result<(u128, u128), MathError> {
-    let r_withdraw_amounts = amounts_to_rated(withdraw_amounts, rates)?;
+    // round up the amounts taken out of the pool, for the protocol advantage
+    let r_withdraw_amounts = amounts_to_rated_ceil(withdraw_amounts, rates)?;
     let r_old_reserves = amounts_to_rated(old_reserves, rates)?;
 
     compute_lp_amount_for_withdraw(
@@ -565,7 +1060,7 @@ mod tests {
 
     #[test]
     fn d_computation_high_amp_coef() {
-        let amp_coef: u128 = 1_000_000_000_000;
+        let amp_coef: u128 = 1_000_000_000_000 * A_PRECISION;
         let reserve_0: u128 = 400_000_000_000;
         let reserve_1: u128 = 500_000_000_000;
         let d = compute_d(&Vec::from([reserve_0, reserve_1]), amp_coef).expect("Should compute D");
@@ -578,7 +1073,7 @@ mod tests {
 
     #[test]
     fn d_computation_low_amp_coef() {
-        let amp_coef: u128 = 1;
+        let amp_coef: u128 = A_PRECISION;
         let reserve_0: u128 = 400_000_000_000;
         let reserve_1: u128 = 500_000_000_000;
         let d = compute_d(&Vec::from([reserve_0, reserve_1]), amp_coef).expect("Should compute D");
@@ -593,9 +1088,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn d_computation_fractional_amp_coef() {
+        let reserves = Vec::from([400_000_000_000u128, 500_000_000_000]);
+        let d_1 = compute_d(&reserves, A_PRECISION).expect("Should compute D");
+        let d_1_5 = compute_d(&reserves, 3 * A_PRECISION / 2).expect("Should compute D");
+        let d_2 = compute_d(&reserves, 2 * A_PRECISION).expect("Should compute D");
+        assert!(
+            d_1 < d_1_5 && d_1_5 < d_2,
+            "Invariant should be monotonic in amplification coefficient"
+        );
+    }
+
     #[test]
     fn y_computation_high_amp_coef() {
-        let amp_coef: u128 = 1_000_000_000_000;
+        let amp_coef: u128 = 1_000_000_000_000 * A_PRECISION;
         let reserve_0: u128 = 500_000_000_000;
         let reserve_1: u128 = 500_000_000_000;
         let reserve_delta: u128 = 40_000_000_000;
@@ -617,7 +1124,7 @@ mod tests {
 
     #[test]
     fn y_computation_low_amp_coef() {
-        let amp_coef: u128 = 1;
+        let amp_coef: u128 = A_PRECISION;
         let reserve_0: u128 = 400_000_000_000;
         let reserve_1: u128 = 500_000_000_000;
         let reserve_delta: u128 = 40_000_000_000;
@@ -643,11 +1150,11 @@ mod tests {
 
     #[test]
     fn swap_to_computation_no_fees() {
-        let amp_coef: u128 = 1000;
+        let amp_coef: u128 = 1000 * A_PRECISION;
         let fees = Fees::zero();
         let reserves: Vec<u128> = vec![100000000000, 100000000000];
         let token_in = 10000000000;
-        let rates: [u128; 2] = [RATE_PRECISION, RATE_PRECISION];
+        let rates = [ScaledRate::new(RATE_PRECISION, TOKEN_TARGET_DECIMALS).unwrap(); 2];
         // ref https://github.com/ref-finance/ref-contracts/blob/be5c0e33465c13a05dab6e5e9ff9f8af414e16a7/ref-exchange/src/stable_swap/mod.rs#L744
         let expect_token_out = 9999495232;
         let (amount_out, fee) = rated_swap_to(&rates, 0, token_in, 1, &reserves, &fees, amp_coef)
@@ -658,12 +1165,12 @@ mod tests {
 
     #[test]
     fn swap_from_computation_no_fees() {
-        let amp_coef: u128 = 1000;
+        let amp_coef: u128 = 1000 * A_PRECISION;
         let fees = Fees::zero();
         let reserves: Vec<u128> = vec![100000000000, 100000000000];
         let token_out = 9999495232;
         let expect_token_in = 10000000000;
-        let rates: [u128; 2] = [RATE_PRECISION, RATE_PRECISION];
+        let rates = [ScaledRate::new(RATE_PRECISION, TOKEN_TARGET_DECIMALS).unwrap(); 2];
 
         let (amount_in, fee) = rated_swap_from(&rates, 0, token_out, 1, &reserves, &fees, amp_coef)
             .expect("Should return swap result");
@@ -673,14 +1180,14 @@ mod tests {
 
     #[test]
     fn swap_to_computation_with_fees() {
-        let amp_coef: u128 = 1000;
+        let amp_coef: u128 = 1000 * A_PRECISION;
         let fees = Fees::new(10000000, 0).unwrap(); // 1% fee
         let reserves: Vec<u128> = vec![100000000000, 100000000000];
         let token_in = 10000000000;
         let expect_token_out = 9999495232;
         let expect_fee = expect_token_out / 100;
         let expect_token_out_minus_fee = expect_token_out - expect_fee;
-        let rates: [u128; 2] = [RATE_PRECISION, RATE_PRECISION];
+        let rates = [ScaledRate::new(RATE_PRECISION, TOKEN_TARGET_DECIMALS).unwrap(); 2];
 
         let (amount_out, fee) = rated_swap_to(&rates, 0, token_in, 1, &reserves, &fees, amp_coef)
             .expect("Should return swap result");
@@ -693,14 +1200,14 @@ mod tests {
 
     #[test]
     fn swap_from_computation_with_fees() {
-        let amp_coef: u128 = 1000;
+        let amp_coef: u128 = 1000 * A_PRECISION;
         let fees = Fees::new(10000000, 0).unwrap(); // 1% fee
         let reserves: Vec<u128> = vec![100000000000, 100000000000];
         let token_out = 9999495232;
         let expect_fee: u128 = 9999495232 / 100;
         let token_out_minus_expect_fee = token_out - expect_fee;
         let expect_token_in = 10000000000;
-        let rates: [u128; 2] = [RATE_PRECISION, RATE_PRECISION];
+        let rates = [ScaledRate::new(RATE_PRECISION, TOKEN_TARGET_DECIMALS).unwrap(); 2];
 
         let (amount_in, fee) = rated_swap_from(
             &rates,
@@ -718,11 +1225,11 @@ mod tests {
 
     #[test]
     fn swap_to_from_computation() {
-        let amp_coef: u128 = 1000;
+        let amp_coef: u128 = 1000 * A_PRECISION;
         let fees = Fees::new(2137, 0).unwrap();
         let reserves: Vec<u128> = vec![12341234123412341234, 5343245543253432435];
         let token_0_in: u128 = 62463425433;
-        let rates: [u128; 2] = [RATE_PRECISION, RATE_PRECISION];
+        let rates = [ScaledRate::new(RATE_PRECISION, TOKEN_TARGET_DECIMALS).unwrap(); 2];
 
         let (amount_out, fee_out) =
             rated_swap_to(&rates, 0, token_0_in, 1, &reserves, &fees, amp_coef)
@@ -736,11 +1243,11 @@ mod tests {
 
     #[test]
     fn swap_from_to_computation() {
-        let amp_coef: u128 = 1000;
+        let amp_coef: u128 = 1000 * A_PRECISION;
         let fees = Fees::new(2137, 0).unwrap();
         let reserves: Vec<u128> = vec![12341234123412341234, 5343245543253432435];
         let token_0_out: u128 = 62463425433;
-        let rates: [u128; 2] = [RATE_PRECISION, RATE_PRECISION];
+        let rates = [ScaledRate::new(RATE_PRECISION, TOKEN_TARGET_DECIMALS).unwrap(); 2];
 
         let (amount_in, fee_in) =
             rated_swap_from(&rates, 0, token_0_out, 1, &reserves, &fees, amp_coef)
@@ -754,7 +1261,7 @@ mod tests {
 
     #[test]
     fn withdraw_liquidity_by_share_and_by_amounts_equality_1() {
-        let amp_coef: u128 = 85;
+        let amp_coef: u128 = 85 * A_PRECISION;
         let fees = Fees::new(2137, 0).unwrap();
         let reserves: Vec<u128> = Vec::from([500_000_000_000, 500_000_000_000]);
         let token_supply = compute_d(&reserves, amp_coef).unwrap().as_u128();
@@ -778,7 +1285,7 @@ mod tests {
 
     #[test]
     fn deposit_liquidity_by_share_and_by_amounts_equality_1() {
-        let amp_coef: u128 = 85;
+        let amp_coef: u128 = 85 * A_PRECISION;
         let fees = Fees::new(2137, 0).unwrap();
         let reserves: Vec<u128> = Vec::from([500_000_000_000, 500_000_000_000]);
         let token_supply = compute_d(&reserves, amp_coef).unwrap().as_u128();
@@ -796,4 +1303,203 @@ mod tests {
         assert_eq!(fee_part, 0, "Fee should be 0");
         assert_eq!(share, share_by_deposit, "Deposit amounts differ.");
     }
+
+    #[test]
+    fn scaled_rate_conversions() {
+        // 6 decimals, rate 1.5
+        let rate = ScaledRate::new(RATE_PRECISION * 3 / 2, 6).unwrap();
+        assert_eq!(
+            amount_to_rated(1_000_000, rate),
+            Ok(1_500_000_000_000_000_000)
+        );
+        assert_eq!(
+            amount_from_rated(1_500_000_000_000_000_000, rate),
+            Ok(1_000_000)
+        );
+        // 24 decimals, rate 1.0
+        let rate = ScaledRate::new(RATE_PRECISION, 24).unwrap();
+        assert_eq!(amount_to_rated(10u128.pow(24), rate), Ok(10u128.pow(18)));
+        assert_eq!(amount_from_rated(10u128.pow(18), rate), Ok(10u128.pow(24)));
+        // downscaling rounds in both directions
+        assert_eq!(amount_to_rated(1_999_999, rate), Ok(1));
+        assert_eq!(amount_to_rated_ceil(1_999_999, rate), Ok(2));
+        assert_eq!(amount_to_rated_ceil(2_000_000, rate), Ok(2));
+    }
+
+    #[test]
+    fn swap_with_downscaled_token() {
+        let amp_coef: u128 = 1000 * A_PRECISION;
+        let fees = Fees::new(0, 0).unwrap();
+        // 24 and 18 decimals tokens
+        let rates = [
+            ScaledRate::new(RATE_PRECISION, 24).unwrap(),
+            ScaledRate::new(RATE_PRECISION, 18).unwrap(),
+        ];
+        let reserves: Vec<u128> = vec![100_000 * 10u128.pow(24), 100_000 * 10u128.pow(18)];
+
+        let (amount_out, _) = rated_swap_to(
+            &rates,
+            0,
+            10u128.pow(24) + 999_999,
+            1,
+            &reserves,
+            &fees,
+            amp_coef,
+        )
+        .expect("Should return swap result");
+        let (same_amount_out, _) =
+            rated_swap_to(&rates, 0, 10u128.pow(24), 1, &reserves, &fees, amp_coef)
+                .expect("Should return swap result");
+        assert_eq!(amount_out, same_amount_out, "Dust should not be swapped");
+        assert!(amount_out < 10u128.pow(18));
+
+        let (amount_in, _) =
+            rated_swap_from(&rates, 1, 10u128.pow(24) - 1, 0, &reserves, &fees, amp_coef)
+                .expect("Should return swap result");
+        let (rounded_amount_in, _) =
+            rated_swap_from(&rates, 1, 10u128.pow(24), 0, &reserves, &fees, amp_coef)
+                .expect("Should return swap result");
+        assert_eq!(
+            amount_in, rounded_amount_in,
+            "Output amount should be rounded up"
+        );
+    }
+
+    #[test]
+    fn swap_nothing_in_imbalanced_pool() {
+        let fees = Fees::new(47, 131755004).unwrap();
+        let amp_coef = 78194354;
+        let rates = [
+            ScaledRate::new(57559675699229, 33).unwrap(),
+            ScaledRate::new(562947366395137, 22).unwrap(),
+        ];
+        let reserves = [
+            10633881802837967100116750496891416447,
+            324518553658426726783156020576000,
+        ];
+        assert_eq!(
+            rated_swap_to(&rates, 0, 0, 1, &reserves, &fees, amp_coef),
+            Ok((0, 0)),
+            "Nothing should be swapped"
+        );
+    }
+
+    #[test]
+    fn withdraw_burns_at_least_one_share() {
+        let fees = Fees::new(0, 327680).unwrap();
+        let amp_coef = 100;
+        let rates = [
+            ScaledRate::new(56679209893888, 33).unwrap(),
+            ScaledRate::new(65537, 33).unwrap(),
+        ];
+        let reserves = [2535301200474905547067115962361, u128::MAX];
+        let supply = rated_compute_d(&rates, &reserves, amp_coef)
+            .unwrap()
+            .as_u128();
+        // withdraw a single comparable unit, which does not change D
+        let withdraw_amounts = [508793615990, 0];
+        let (burn_shares, _) = rated_compute_lp_amount_for_withdraw(
+            &rates,
+            &withdraw_amounts,
+            &reserves,
+            supply,
+            Some(&fees),
+            amp_coef,
+        )
+        .expect("Should compute burned shares");
+        assert_eq!(burn_shares, 1, "Should burn a share");
+    }
+
+    #[test]
+    fn spot_price_computation() {
+        let amp_coef: u128 = 100 * A_PRECISION;
+        let rates = [ScaledRate::new(RATE_PRECISION, TOKEN_TARGET_DECIMALS).unwrap(); 2];
+        let reserves = [10u128.pow(24); 2];
+        assert_eq!(
+            rated_spot_price(&rates, 0, 1, &reserves, amp_coef),
+            Ok(RATE_PRECISION)
+        );
+
+        let reserves = [3 * 10u128.pow(24), 10u128.pow(24)];
+        let price = rated_spot_price(&rates, 0, 1, &reserves, amp_coef).unwrap();
+        let inverse_price = rated_spot_price(&rates, 1, 0, &reserves, amp_coef).unwrap();
+        assert!(price < RATE_PRECISION, "Abundant token should be cheaper");
+        assert!(
+            (price * inverse_price / RATE_PRECISION).abs_diff(RATE_PRECISION) <= 10,
+            "Prices should be inverse of each other"
+        );
+        // a small swap is executed at the spot price
+        let amount_in = 10u128.pow(18);
+        let (amount_out, _) =
+            rated_swap_to(&rates, 0, amount_in, 1, &reserves, &Fees::zero(), amp_coef)
+                .expect("Should return swap result");
+        assert!(
+            (amount_out * RATE_PRECISION / amount_in).abs_diff(price) < RATE_PRECISION / 1_000_000
+        );
+    }
+
+    #[test]
+    fn swap_amount_for_price_computation() {
+        let amp_coef: u128 = 100 * A_PRECISION;
+        let fees = Fees::new(1_000_000, 0).unwrap(); // 0.1% fee
+                                                     // 18 decimals, and 6 decimals with rate 1.5
+        let rates = [
+            ScaledRate::new(RATE_PRECISION, 18).unwrap(),
+            ScaledRate::new(RATE_PRECISION * 3 / 2, 6).unwrap(),
+        ];
+        let reserves = [500_000 * 10u128.pow(18), 800_000 * 10u128.pow(6)];
+        let target_price = RATE_PRECISION;
+
+        let amount_in =
+            rated_swap_amount_for_price(&rates, 0, 1, target_price, &reserves, &fees, amp_coef)
+                .expect("Should compute swap amount");
+        let (amount_out, _) = rated_swap_to(&rates, 0, amount_in, 1, &reserves, &fees, amp_coef)
+            .expect("Should return swap result");
+        let new_reserves = [reserves[0] + amount_in, reserves[1] - amount_out];
+        let price = rated_spot_price(&rates, 0, 1, &new_reserves, amp_coef).unwrap();
+        assert!(
+            price >= target_price,
+            "Price should not overshoot the target"
+        );
+        assert!(price - target_price < RATE_PRECISION / 1_000_000);
+
+        assert_eq!(
+            rated_swap_amount_for_price(&rates, 1, 0, target_price, &reserves, &fees, amp_coef),
+            Ok(0),
+            "Price is already below the target"
+        );
+    }
+
+    #[test]
+    fn swap_amount_for_ratio_computation() {
+        let amp_coef: u128 = 1000 * A_PRECISION;
+        let fees = Fees::new(4_000_000, 0).unwrap(); // 0.4% fee
+        let rates = [ScaledRate::new(RATE_PRECISION, TOKEN_TARGET_DECIMALS).unwrap(); 3];
+        let reserves = [
+            100_000 * 10u128.pow(18),
+            900_000 * 10u128.pow(18),
+            500_000 * 10u128.pow(18),
+        ];
+        // 2:3 ratio of token 0 to token 1
+        let target_ratio = RATE_PRECISION * 2 / 3;
+
+        let amount_in =
+            rated_swap_amount_for_ratio(&rates, 0, 1, target_ratio, &reserves, &fees, amp_coef)
+                .expect("Should compute swap amount");
+        let (amount_out, _) = rated_swap_to(&rates, 0, amount_in, 1, &reserves, &fees, amp_coef)
+            .expect("Should return swap result");
+        let ratio = casted_mul(reserves[0] + amount_in, RATE_PRECISION)
+            / U256::from(reserves[1] - amount_out);
+        assert!(
+            ratio <= target_ratio.into(),
+            "Ratio should not overshoot the target"
+        );
+        assert!(ratio + 1 >= target_ratio.into());
+
+        assert_eq!(
+            rated_swap_amount_for_ratio(&rates, 2, 0, target_ratio, &reserves, &fees, amp_coef),
+            Ok(0),
+            "Ratio is already above the target"
+        );
+    }
 }
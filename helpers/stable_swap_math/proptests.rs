@@ -0,0 +1,270 @@
+//! Property-based tests of the stable swap invariants over random pools.
+use super::*;
+use crate::constants::stable_pool::{MAX_AMP, MAX_COINS, MAX_PROTOCOL_FEE, MAX_TRADE_FEE, MIN_AMP};
+use proptest::prelude::*;
+
+/// Max error of the computed D, in comparable amounts.
+const D_TOLERANCE: u128 = 10;
+
+#[derive(Debug, Clone)]
+struct Pool {
+    rates: Vec<ScaledRate>,
+    reserves: Vec<u128>,
+    amp_coef: u128,
+    trade_fee: u32,
+    protocol_fee: u32,
+}
+
+impl Pool {
+    fn fees(&self) -> Fees {
+        Fees::new(self.trade_fee, self.protocol_fee).unwrap()
+    }
+
+    /// Invariant of the pool in comparable amounts.
+    fn d(&self, reserves: &[u128]) -> U256 {
+        compute_d(
+            &amounts_to_rated(reserves, &self.rates).unwrap(),
+            self.amp_coef,
+        )
+        .unwrap()
+    }
+
+    /// Initial LP token supply, equal to the invariant.
+    fn supply(&self) -> u128 {
+        self.d(&self.reserves).as_u128()
+    }
+}
+
+fn pool() -> impl Strategy<Value = Pool> {
+    (2..=MAX_COINS).prop_flat_map(|n| {
+        (
+            prop::collection::vec(prop::sample::select(vec![6u8, 12, 18, 24]), n),
+            prop::collection::vec(RATE_PRECISION / 2..=RATE_PRECISION * 4, n),
+            prop::collection::vec(1_000u128..=1_000_000_000, n),
+            (MIN_AMP * A_PRECISION)..=(MAX_AMP * A_PRECISION),
+            0..=MAX_TRADE_FEE,
+            0..=MAX_PROTOCOL_FEE,
+        )
+            .prop_map(
+                |(decimals, rates, whole_reserves, amp_coef, trade_fee, protocol_fee)| Pool {
+                    rates: rates
+                        .iter()
+                        .zip(decimals.iter())
+                        .map(|(&rate, &decimals)| ScaledRate::new(rate, decimals).unwrap())
+                        .collect(),
+                    reserves: whole_reserves
+                        .iter()
+                        .zip(decimals.iter())
+                        .map(|(&reserve, &decimals)| reserve * 10u128.pow(decimals.into()))
+                        .collect(),
+                    amp_coef,
+                    trade_fee,
+                    protocol_fee,
+                },
+            )
+    })
+}
+
+/// Pool with a pair of distinct token ids.
+fn pool_with_pair() -> impl Strategy<Value = (Pool, usize, usize)> {
+    pool().prop_flat_map(|pool| {
+        let n = pool.reserves.len();
+        (Just(pool), 0..n, 1..n).prop_map(move |(pool, i, offset)| (pool, i, (i + offset) % n))
+    })
+}
+
+/// Amounts of each token as per mille of the respective reserves.
+fn amounts(pool: &Pool, per_mille: &[u128]) -> Vec<u128> {
+    pool.reserves
+        .iter()
+        .zip(per_mille.iter())
+        .map(|(reserve, per_mille)| reserve * per_mille / 1000)
+        .collect()
+}
+
+fn add(a: &[u128], b: &[u128]) -> Vec<u128> {
+    a.iter().zip(b.iter()).map(|(a, b)| a + b).collect()
+}
+
+/// Rejects the test case if the computation failed, e.g. due to insufficient reserves.
+fn ok<T>(result: Result<T, MathError>) -> Result<T, TestCaseError> {
+    result.map_err(|err| TestCaseError::reject(format!("{err:?}")))
+}
+
+/// Checks that `d_1 / supply_1 >= d_0 / supply_0`, up to `D_TOLERANCE`.
+fn assert_virtual_price_not_decreased(d_0: U256, supply_0: u128, d_1: U256, supply_1: u128) {
+    assert!(
+        (d_1 + D_TOLERANCE) * U256::from(supply_0) >= d_0 * U256::from(supply_1),
+        "Virtual price decreased: {d_0}/{supply_0} -> {d_1}/{supply_1}"
+    );
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    #[test]
+    fn d_grows_with_deposits(
+        pool in pool(),
+        per_mille in prop::collection::vec(0u128..=10_000, MAX_COINS),
+    ) {
+        let deposit = amounts(&pool, &per_mille);
+        let d_0 = pool.d(&pool.reserves);
+        let d_1 = pool.d(&add(&pool.reserves, &deposit));
+        prop_assert!(d_1 + D_TOLERANCE >= d_0);
+    }
+
+    #[test]
+    fn swap_round_trip_never_profits(
+        (pool, i, j) in pool_with_pair(),
+        per_mille in 1u128..=500,
+    ) {
+        let fees = pool.fees();
+        let amount_in = pool.reserves[i] * per_mille / 1000;
+        let (amount_out, _) = ok(rated_swap_to(
+            &pool.rates, i, amount_in, j, &pool.reserves, &fees, pool.amp_coef,
+        ))?;
+        prop_assume!(amount_out > 0);
+
+        let mut reserves = pool.reserves.clone();
+        reserves[i] += amount_in;
+        reserves[j] -= amount_out;
+        // buy back the `amount_in` of token `i` with token `j`
+        if let Ok((amount_back_in, _)) = rated_swap_from(
+            &pool.rates, j, amount_in, i, &reserves, &fees, pool.amp_coef,
+        ) {
+            prop_assert!(
+                amount_back_in >= amount_out,
+                "Buying back {} for {} out of {}", amount_in, amount_back_in, amount_out
+            );
+        }
+        // sell back the `amount_out` of token `j`
+        let (amount_back_out, _) = ok(rated_swap_to(
+            &pool.rates, j, amount_out, i, &reserves, &fees, pool.amp_coef,
+        ))?;
+        prop_assert!(amount_back_out <= amount_in);
+    }
+
+    #[test]
+    fn swap_from_then_swap_to_never_profits(
+        (pool, i, j) in pool_with_pair(),
+        per_mille in 1u128..=500,
+    ) {
+        let fees = pool.fees();
+        let amount_out = pool.reserves[j] * per_mille / 1000;
+        let (amount_in, _) = ok(rated_swap_from(
+            &pool.rates, i, amount_out, j, &pool.reserves, &fees, pool.amp_coef,
+        ))?;
+
+        let mut reserves = pool.reserves.clone();
+        reserves[i] += amount_in;
+        reserves[j] -= amount_out;
+        let (amount_back_out, _) = ok(rated_swap_to(
+            &pool.rates, j, amount_out, i, &reserves, &fees, pool.amp_coef,
+        ))?;
+        prop_assert!(amount_back_out <= amount_in);
+    }
+
+    #[test]
+    fn deposit_then_withdraw_never_returns_more(
+        pool in pool(),
+        per_mille in prop::collection::vec(0u128..=2_000, MAX_COINS),
+    ) {
+        let fees = pool.fees();
+        let deposit = amounts(&pool, &per_mille);
+        prop_assume!(deposit.iter().any(|&amount| amount > 0));
+        let supply_0 = pool.supply();
+        let d_0 = pool.d(&pool.reserves);
+        let supply = supply_0;
+        let (shares, fee_part) = ok(rated_compute_lp_amount_for_deposit(
+            &pool.rates, &deposit, &pool.reserves, supply, Some(&fees), pool.amp_coef,
+        ))?;
+        let reserves = add(&pool.reserves, &deposit);
+        let supply = supply + shares + ok(fees.protocol_trade_fee(fee_part))?;
+
+        // withdrawing the same amounts requires burning at least the minted shares
+        let (burn, _) = ok(rated_compute_lp_amount_for_withdraw(
+            &pool.rates, &deposit, &reserves, supply, Some(&fees), pool.amp_coef,
+        ))?;
+        prop_assert!(burn >= shares, "Burn {} < minted {}", burn, shares);
+
+        // withdrawing all minted shares proportionally does not extract value from the pool
+        let withdrawn = ok(compute_amounts_given_lp(shares, &reserves, supply))?;
+        let reserves: Vec<u128> = reserves
+            .iter()
+            .zip(withdrawn.iter())
+            .map(|(reserve, amount)| reserve - amount)
+            .collect();
+        assert_virtual_price_not_decreased(d_0, supply_0, pool.d(&reserves), supply - shares);
+    }
+
+    #[test]
+    fn virtual_price_never_decreases(
+        (pool, i, j) in pool_with_pair(),
+        swap_per_mille in 1u128..=500,
+        per_mille in prop::collection::vec(0u128..=2_000, MAX_COINS),
+    ) {
+        let fees = pool.fees();
+        let supply = pool.supply();
+        let d_0 = pool.d(&pool.reserves);
+
+        // swap, protocol fee is minted as LP tokens
+        let amount_in = pool.reserves[i] * swap_per_mille / 1000;
+        let (amount_out, fee) = ok(rated_swap_to(
+            &pool.rates, i, amount_in, j, &pool.reserves, &fees, pool.amp_coef,
+        ))?;
+        let mut reserves = pool.reserves.clone();
+        reserves[i] += amount_in;
+        reserves[j] -= amount_out;
+        let d_1 = pool.d(&reserves);
+        let protocol_fee = ok(fees.protocol_trade_fee(fee))?;
+        let (protocol_shares, _) = ok(rated_compute_lp_amount_for_deposit(
+            &pool.rates,
+            &(0..reserves.len()).map(|id| if id == j { protocol_fee } else { 0 }).collect::<Vec<_>>(),
+            &reserves,
+            supply,
+            None,
+            pool.amp_coef,
+        ))?;
+        let supply_1 = supply + protocol_shares;
+        assert_virtual_price_not_decreased(d_0, supply, d_1, supply_1);
+
+        // imbalanced deposit
+        let deposit = amounts(&pool, &per_mille);
+        prop_assume!(deposit.iter().any(|&amount| amount > 0));
+        let (shares, fee_part) = ok(rated_compute_lp_amount_for_deposit(
+            &pool.rates, &deposit, &reserves, supply_1, Some(&fees), pool.amp_coef,
+        ))?;
+        let reserves = add(&reserves, &deposit);
+        let supply_2 = supply_1 + shares + ok(fees.protocol_trade_fee(fee_part))?;
+        let d_2 = pool.d(&reserves);
+        assert_virtual_price_not_decreased(d_1, supply_1, d_2, supply_2);
+
+        // imbalanced withdrawal of the deposit
+        let (burn, fee_part) = ok(rated_compute_lp_amount_for_withdraw(
+            &pool.rates, &deposit, &reserves, supply_2, Some(&fees), pool.amp_coef,
+        ))?;
+        let reserves: Vec<u128> =
+            reserves.iter().zip(deposit.iter()).map(|(reserve, amount)| reserve - amount).collect();
+        let supply_3 = supply_2 - burn + ok(fees.protocol_trade_fee(fee_part))?;
+        let d_3 = pool.d(&reserves);
+        assert_virtual_price_not_decreased(d_2, supply_2, d_3, supply_3);
+    }
+
+    #[test]
+    fn compute_y_converges(
+        (pool, i, j) in pool_with_pair(),
+        per_mille in 0u128..=10_000,
+        decrease in any::<bool>(),
+    ) {
+        let reserves = amounts_to_rated(&pool.reserves, &pool.rates).unwrap();
+        let delta = reserves[i] * per_mille / 10_000;
+        let new_reserve_x = if decrease {
+            reserves[i] - delta / 2
+        } else {
+            reserves[i] + delta
+        };
+        prop_assume!(new_reserve_x > 0);
+        let result = compute_y(new_reserve_x, &reserves, i, j, pool.amp_coef);
+        prop_assert!(result.is_ok(), "compute_y failed: {:?}", result);
+    }
+}
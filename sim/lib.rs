@@ -0,0 +1,20 @@
+//! Off-chain model of the `StablePoolContract`.
+//!
+//! [`StablePoolSim`] keeps the full state of a pool (reserves, rates, amplification
+//! coefficient ramp, fees and LP token supply) and runs the contract messages with
+//! the same `amm_helpers::stable_swap_math` and rounding as the contract does,
+//! so that quotes and simulated trades match the on-chain results exactly.
+//!
+//! Token transfers are not modeled: deposited amounts are credited to the pool
+//! net of the expected transfer fees, as quoted by the contract.
+mod lp_token;
+mod pool;
+
+pub use amm_helpers::stable_swap_math::{
+    amp_coef::{AmpCoef, RampShape},
+    fees::{FeeSchedule, Fees},
+};
+pub use ink::primitives::AccountId;
+pub use lp_token::LpToken;
+pub use pool::{StablePoolSim, DEAD_ADDRESS};
+pub use traits::{MathError, StablePoolError};
@@ -0,0 +1,81 @@
+use ink::primitives::AccountId;
+use psp22::PSP22Error;
+use std::collections::BTreeMap;
+
+/// LP token balances, as kept by the `PSP22Data` of the contract.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LpToken {
+    total_supply: u128,
+    balances: BTreeMap<AccountId, u128>,
+}
+
+impl LpToken {
+    pub fn total_supply(&self) -> u128 {
+        self.total_supply
+    }
+
+    pub fn balance_of(&self, owner: AccountId) -> u128 {
+        self.balances.get(&owner).copied().unwrap_or_default()
+    }
+
+    /// Sets the total supply synced from the chain.
+    ///
+    /// Balances are kept only for the accounts known to the model,
+    /// so they do not need to sum up to the total supply.
+    pub fn set_total_supply(&mut self, total_supply: u128) {
+        self.total_supply = total_supply;
+    }
+
+    pub fn set_balance(&mut self, owner: AccountId, balance: u128) {
+        self.balances.insert(owner, balance);
+    }
+
+    pub fn mint(&mut self, to: AccountId, value: u128) -> Result<(), PSP22Error> {
+        if value == 0 {
+            return Ok(());
+        }
+        let new_supply = self
+            .total_supply
+            .checked_add(value)
+            .ok_or(PSP22Error::Custom(String::from(
+                "Max PSP22 supply exceeded. Max supply limited to 2^128-1.",
+            )))?;
+        self.total_supply = new_supply;
+        let balance = self.balances.entry(to).or_default();
+        *balance = balance.saturating_add(value);
+        Ok(())
+    }
+
+    pub fn burn(&mut self, from: AccountId, value: u128) -> Result<(), PSP22Error> {
+        if value == 0 {
+            return Ok(());
+        }
+        let balance = self.balance_of(from);
+        if balance < value {
+            return Err(PSP22Error::InsufficientBalance);
+        }
+        self.balances.insert(from, balance - value);
+        self.total_supply = self.total_supply.saturating_sub(value);
+        Ok(())
+    }
+
+    pub fn transfer(
+        &mut self,
+        from: AccountId,
+        to: AccountId,
+        value: u128,
+    ) -> Result<(), PSP22Error> {
+        if from == to || value == 0 {
+            return Ok(());
+        }
+        let from_balance = self.balance_of(from);
+        if from_balance < value {
+            return Err(PSP22Error::InsufficientBalance);
+        }
+        self.balances.insert(from, from_balance - value);
+        // Total supply is limited by u128.MAX so no overflow is possible
+        let to_balance = self.balances.entry(to).or_default();
+        *to_balance = to_balance.saturating_add(value);
+        Ok(())
+    }
+}
@@ -0,0 +1,1348 @@
+use crate::lp_token::LpToken;
+use amm_helpers::{
+    constants::stable_pool::{
+        FEE_DENOM, MAX_COINS, MAX_TOKEN_DECIMALS, MINIMUM_LIQUIDITY, MIN_INITIAL_D, RATE_PRECISION,
+    },
+    ensure,
+    stable_swap_math::{
+        self as math,
+        amp_coef::{AmpCoef, RampShape},
+        auto_amp::{AutoAmp, AutoAmpConfig},
+        fees::{amount_gross_of_transfer_fee, amount_net_of_transfer_fee, FeeSchedule, Fees},
+        ScaledRate,
+    },
+};
+use ink::primitives::AccountId;
+use traits::{MathError, StablePoolError};
+
+/// Address holding the permanently locked `MINIMUM_LIQUIDITY`, as in the contract.
+pub const DEAD_ADDRESS: [u8; 32] = [0u8; 32];
+
+/// State of a `StablePoolContract` together with its messages.
+///
+/// Messages have the same checks, order of computations and errors as the contract ones.
+/// A failed message leaves the state untouched, as a reverted transaction does.
+#[derive(Debug, Clone)]
+pub struct StablePoolSim {
+    /// List of tokens.
+    tokens: Vec<AccountId>,
+    /// Tokens decimals, used for normalization.
+    tokens_decimals: Vec<u8>,
+    /// Reserves of tokens
+    reserves: Vec<u128>,
+    /// Current token rates (with `RATE_PRECISION`), either constant or set from the rate providers.
+    token_rates: Vec<u128>,
+    /// Expected fees charged by tokens on transfer, in 1e9 precision (`None` for regular tokens).
+    ///
+    /// Amounts of these tokens are credited to the pool net of the expected fee.
+    transfer_fees: Vec<Option<u32>>,
+    /// Amplification coefficient.
+    amp_coef: AmpCoef,
+    /// Automatic adjustment of the amplification coefficient (if enabled).
+    auto_amp: Option<AutoAmp>,
+    /// Fees, ramped over time.
+    fees: FeeSchedule,
+    /// Who receives protocol fees (if any).
+    fee_receiver: Option<AccountId>,
+    /// LP token balances.
+    lp_token: LpToken,
+    /// Current block timestamp, in milliseconds.
+    time: u64,
+}
+
+impl StablePoolSim {
+    /// Creates an empty pool, as the `new_pool` constructor of the contract, at block `time`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_pool(
+        tokens: Vec<AccountId>,
+        tokens_decimals: Vec<u8>,
+        token_rates: Vec<u128>,
+        transfer_fees: Vec<Option<u32>>,
+        amp_coef: AmpCoef,
+        fees: Option<Fees>,
+        fee_receiver: Option<AccountId>,
+        time: u64,
+    ) -> Result<Self, StablePoolError> {
+        let mut unique_tokens = tokens.clone();
+        unique_tokens.sort();
+        unique_tokens.dedup();
+        let token_count = tokens.len();
+        ensure!(
+            unique_tokens.len() == token_count,
+            StablePoolError::IdenticalTokenId
+        );
+        ensure!(
+            token_count == tokens_decimals.len()
+                && token_count == token_rates.len()
+                && token_count == transfer_fees.len()
+                && (2..=MAX_COINS).contains(&token_count),
+            StablePoolError::IncorrectTokenCount
+        );
+
+        ensure!(
+            tokens_decimals.iter().all(|&d| d <= MAX_TOKEN_DECIMALS),
+            StablePoolError::TooLargeTokenDecimal
+        );
+
+        ensure!(
+            transfer_fees
+                .iter()
+                .all(|fee| fee.map_or(true, |fee| fee < FEE_DENOM)),
+            StablePoolError::InvalidFee
+        );
+
+        Ok(Self {
+            tokens,
+            reserves: vec![0; token_count],
+            tokens_decimals,
+            token_rates,
+            transfer_fees,
+            amp_coef,
+            auto_amp: None,
+            fees: FeeSchedule::new(fees.ok_or(StablePoolError::InvalidFee)?),
+            fee_receiver,
+            lp_token: LpToken::default(),
+            time,
+        })
+    }
+
+    /// Creates an empty pool of tokens with constant rates, as the `new_stable` constructor.
+    pub fn new_stable(
+        tokens: Vec<AccountId>,
+        tokens_decimals: Vec<u8>,
+        init_amp_coef: u128,
+        trade_fee: u32,
+        protocol_fee: u32,
+        fee_receiver: Option<AccountId>,
+    ) -> Result<Self, StablePoolError> {
+        let token_rates = vec![RATE_PRECISION; tokens.len()];
+        let transfer_fees = vec![None; tokens.len()];
+        Self::new_pool(
+            tokens,
+            tokens_decimals,
+            token_rates,
+            transfer_fees,
+            AmpCoef::new(init_amp_coef)?,
+            Fees::new(trade_fee, protocol_fee),
+            fee_receiver,
+            0,
+        )
+    }
+
+    /// Current block timestamp, in milliseconds.
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    /// Moves the block timestamp to `time`, which affects the amplification coefficient ramp.
+    pub fn set_time(&mut self, time: u64) {
+        self.time = time;
+    }
+
+    /// Moves the block timestamp forward by `duration` milliseconds.
+    pub fn advance_time(&mut self, duration: u64) {
+        self.time = self.time.saturating_add(duration);
+    }
+
+    /// Sets reserves synced from the chain.
+    pub fn set_reserves(&mut self, reserves: Vec<u128>) -> Result<(), StablePoolError> {
+        ensure!(
+            reserves.len() == self.tokens.len(),
+            StablePoolError::IncorrectAmountsCount
+        );
+        self.reserves = reserves;
+        Ok(())
+    }
+
+    /// Sets the current rate of `token` (with `RATE_PRECISION`), e.g. read from its rate provider.
+    pub fn set_token_rate(&mut self, token: AccountId, rate: u128) -> Result<(), StablePoolError> {
+        let token_id = self.token_id(token)?;
+        self.token_rates[token_id] = rate;
+        Ok(())
+    }
+
+    /// Sets the amplification coefficient synced from the chain.
+    pub fn set_amp_coef(&mut self, amp_coef: AmpCoef) {
+        self.amp_coef = amp_coef;
+    }
+
+    /// LP token balances, e.g. for syncing the total supply from the chain.
+    pub fn lp_token_mut(&mut self) -> &mut LpToken {
+        &mut self.lp_token
+    }
+
+    pub fn lp_token(&self) -> &LpToken {
+        &self.lp_token
+    }
+
+    pub fn total_supply(&self) -> u128 {
+        self.lp_token.total_supply()
+    }
+
+    pub fn balance_of(&self, owner: AccountId) -> u128 {
+        self.lp_token.balance_of(owner)
+    }
+
+    pub fn tokens(&self) -> Vec<AccountId> {
+        self.tokens.clone()
+    }
+
+    pub fn reserves(&self) -> Vec<u128> {
+        self.reserves.clone()
+    }
+
+    pub fn tokens_decimals(&self) -> Vec<u8> {
+        self.tokens_decimals.clone()
+    }
+
+    pub fn token_rates(&self) -> Vec<u128> {
+        self.token_rates.clone()
+    }
+
+    pub fn transfer_fees(&self) -> Vec<Option<u32>> {
+        self.transfer_fees.clone()
+    }
+
+    pub fn amp_coef(&self) -> Result<u128, StablePoolError> {
+        Ok(self.amp_coef.compute_amp_coef(self.time)?)
+    }
+
+    pub fn amp_coef_precise(&self) -> Result<u128, StablePoolError> {
+        Ok(self.amp_coef.compute_amp_coef_precise(self.time)?)
+    }
+
+    pub fn future_amp_coef(&self) -> Option<(u128, u64)> {
+        self.amp_coef.future_amp_coef(self.time)
+    }
+
+    pub fn future_amp_coef_precise(&self) -> Option<(u128, u64)> {
+        self.amp_coef.future_amp_coef_precise(self.time)
+    }
+
+    pub fn fees(&self) -> (u32, u32) {
+        let fees = self.current_fees();
+        (fees.trade_fee, fees.protocol_fee)
+    }
+
+    pub fn future_fees(&self) -> Option<(u32, u32, u64)> {
+        self.fees
+            .future_fees(self.time)
+            .map(|(fees, future_time)| (fees.trade_fee, fees.protocol_fee, future_time))
+    }
+
+    pub fn fee_receiver(&self) -> Option<AccountId> {
+        self.fee_receiver
+    }
+
+    pub fn set_fees(&mut self, trade_fee: u32, protocol_fee: u32) -> Result<(), StablePoolError> {
+        self.fees
+            .set_fees(Fees::new(trade_fee, protocol_fee).ok_or(StablePoolError::InvalidFee)?);
+        Ok(())
+    }
+
+    pub fn ramp_fees(
+        &mut self,
+        future_trade_fee: u32,
+        future_protocol_fee: u32,
+        future_time: u64,
+    ) -> Result<(), StablePoolError> {
+        Ok(self.fees.ramp_fees(
+            future_trade_fee,
+            future_protocol_fee,
+            future_time,
+            self.time,
+        )?)
+    }
+
+    pub fn stop_ramp_fees(&mut self) {
+        self.fees.stop_ramp_fees(self.time);
+    }
+
+    pub fn set_fee_receiver(&mut self, fee_receiver: Option<AccountId>) {
+        self.fee_receiver = fee_receiver;
+    }
+
+    pub fn ramp_amp_coef(
+        &mut self,
+        future_amp_coef: u128,
+        future_time: u64,
+    ) -> Result<(), StablePoolError> {
+        Ok(self
+            .amp_coef
+            .ramp_amp_coef(future_amp_coef, future_time, self.time)?)
+    }
+
+    pub fn ramp_amp_coef_schedule(
+        &mut self,
+        waypoints: Vec<(u128, u64)>,
+        ramp_shape: RampShape,
+    ) -> Result<(), StablePoolError> {
+        Ok(self
+            .amp_coef
+            .ramp_amp_coef_schedule(waypoints, ramp_shape, self.time)?)
+    }
+
+    pub fn ramp_amp_coef_precise(
+        &mut self,
+        future_amp_coef: u128,
+        future_time: u64,
+    ) -> Result<(), StablePoolError> {
+        Ok(self
+            .amp_coef
+            .ramp_amp_coef_precise(future_amp_coef, future_time, self.time)?)
+    }
+
+    pub fn ramp_amp_coef_schedule_precise(
+        &mut self,
+        waypoints: Vec<(u128, u64)>,
+        ramp_shape: RampShape,
+    ) -> Result<(), StablePoolError> {
+        Ok(self
+            .amp_coef
+            .ramp_amp_coef_schedule_precise(waypoints, ramp_shape, self.time)?)
+    }
+
+    pub fn auto_amp(&self) -> Option<(AutoAmpConfig, u32)> {
+        self.auto_amp
+            .map(|auto_amp| (auto_amp.config(), auto_amp.imbalance_ema()))
+    }
+
+    pub fn set_auto_amp(&mut self, config: Option<AutoAmpConfig>) -> Result<(), StablePoolError> {
+        self.auto_amp = match config {
+            Some(config) => {
+                let imbalance = math::rated_imbalance(&self.get_scaled_rates()?, &self.reserves)?;
+                Some(AutoAmp::new(config, imbalance, self.time)?)
+            }
+            None => None,
+        };
+        Ok(())
+    }
+
+    pub fn stop_ramp_amp_coef(&mut self) -> Result<(), StablePoolError> {
+        Ok(self.amp_coef.stop_ramp_amp_coef(self.time)?)
+    }
+
+    /// Returns `(shares, fee_part)` minted to `to`, as `StablePoolActions::add_liquidity`.
+    pub fn add_liquidity(
+        &mut self,
+        min_share_amount: u128,
+        amounts: Vec<u128>,
+        to: AccountId,
+    ) -> Result<(u128, u128), StablePoolError> {
+        self.revert_on_error(|this| this._add_liquidity(min_share_amount, amounts, to))
+    }
+
+    /// Returns amounts withdrawn for `shares` of `caller`, as `StablePoolActions::remove_liquidity_by_shares`.
+    pub fn remove_liquidity_by_shares(
+        &mut self,
+        caller: AccountId,
+        shares: u128,
+        min_amounts: Vec<u128>,
+    ) -> Result<Vec<u128>, StablePoolError> {
+        self.revert_on_error(|this| this._remove_liquidity_by_shares(caller, shares, min_amounts))
+    }
+
+    /// Returns `(shares, fee_part)` burned from `caller`, as `StablePoolActions::remove_liquidity_by_amounts`.
+    pub fn remove_liquidity_by_amounts(
+        &mut self,
+        caller: AccountId,
+        max_share_amount: u128,
+        amounts: Vec<u128>,
+    ) -> Result<(u128, u128), StablePoolError> {
+        self.revert_on_error(|this| {
+            this._remove_liquidity_by_amounts(caller, max_share_amount, amounts)
+        })
+    }
+
+    /// Returns `(amount_out, fee)`, as `StablePoolActions::swap_exact_in`.
+    pub fn swap_exact_in(
+        &mut self,
+        token_in: AccountId,
+        token_out: AccountId,
+        token_in_amount: u128,
+        min_token_out_amount: u128,
+    ) -> Result<(u128, u128), StablePoolError> {
+        self.revert_on_error(|this| {
+            this._swap_exact_in(token_in, token_out, token_in_amount, min_token_out_amount)
+        })
+    }
+
+    /// Returns `(amount_in, fee)`, as `StablePoolActions::swap_exact_out`.
+    pub fn swap_exact_out(
+        &mut self,
+        token_in: AccountId,
+        token_out: AccountId,
+        token_out_amount: u128,
+        max_token_in_amount: u128,
+    ) -> Result<(u128, u128), StablePoolError> {
+        self.revert_on_error(|this| {
+            this._swap_exact_out(token_in, token_out, token_out_amount, max_token_in_amount)
+        })
+    }
+
+    pub fn get_swap_amount_out(
+        &self,
+        token_in: AccountId,
+        token_out: AccountId,
+        token_in_amount: u128,
+    ) -> Result<(u128, u128), StablePoolError> {
+        let (token_in_id, token_out_id) = self.check_tokens(token_in, token_out)?;
+        let token_in_amount = self.net_of_transfer_fee(token_in_id, token_in_amount)?;
+        let rates = self.get_scaled_rates()?;
+        Ok(math::rated_swap_to(
+            &rates,
+            token_in_id,
+            token_in_amount,
+            token_out_id,
+            &self.reserves,
+            &self.current_fees(),
+            self.amp_coef_precise()?,
+        )?)
+    }
+
+    pub fn get_swap_amount_in(
+        &self,
+        token_in: AccountId,
+        token_out: AccountId,
+        token_out_amount: u128,
+    ) -> Result<(u128, u128), StablePoolError> {
+        let (token_in_id, token_out_id) = self.check_tokens(token_in, token_out)?;
+        let rates = self.get_scaled_rates()?;
+        let (token_in_amount, fee) = math::rated_swap_from(
+            &rates,
+            token_in_id,
+            token_out_amount,
+            token_out_id,
+            &self.reserves,
+            &self.current_fees(),
+            self.amp_coef_precise()?,
+        )?;
+        Ok((
+            self.gross_of_transfer_fee(token_in_id, token_in_amount)?,
+            fee,
+        ))
+    }
+
+    pub fn get_spot_price(
+        &self,
+        token_in: AccountId,
+        token_out: AccountId,
+    ) -> Result<u128, StablePoolError> {
+        let (token_in_id, token_out_id) = self.check_tokens(token_in, token_out)?;
+        let rates = self.get_scaled_rates()?;
+        Ok(math::rated_spot_price(
+            &rates,
+            token_in_id,
+            token_out_id,
+            &self.reserves,
+            self.amp_coef_precise()?,
+        )?)
+    }
+
+    pub fn get_swap_amount_in_for_price(
+        &self,
+        token_in: AccountId,
+        token_out: AccountId,
+        target_price: u128,
+    ) -> Result<u128, StablePoolError> {
+        let (token_in_id, token_out_id) = self.check_tokens(token_in, token_out)?;
+        let rates = self.get_scaled_rates()?;
+        let token_in_amount = math::rated_swap_amount_for_price(
+            &rates,
+            token_in_id,
+            token_out_id,
+            target_price,
+            &self.reserves,
+            &self.current_fees(),
+            self.amp_coef_precise()?,
+        )?;
+        Ok(self.gross_of_transfer_fee(token_in_id, token_in_amount)?)
+    }
+
+    pub fn get_swap_amount_in_for_ratio(
+        &self,
+        token_in: AccountId,
+        token_out: AccountId,
+        target_ratio: u128,
+    ) -> Result<u128, StablePoolError> {
+        let (token_in_id, token_out_id) = self.check_tokens(token_in, token_out)?;
+        let rates = self.get_scaled_rates()?;
+        let token_in_amount = math::rated_swap_amount_for_ratio(
+            &rates,
+            token_in_id,
+            token_out_id,
+            target_ratio,
+            &self.reserves,
+            &self.current_fees(),
+            self.amp_coef_precise()?,
+        )?;
+        Ok(self.gross_of_transfer_fee(token_in_id, token_in_amount)?)
+    }
+
+    pub fn get_mint_liquidity_for_amounts(
+        &self,
+        amounts: Vec<u128>,
+    ) -> Result<(u128, u128), StablePoolError> {
+        ensure!(
+            amounts.len() == self.tokens.len(),
+            StablePoolError::IncorrectAmountsCount
+        );
+        let amounts = amounts
+            .iter()
+            .enumerate()
+            .map(|(id, &amount)| self.net_of_transfer_fee(id, amount))
+            .collect::<Result<Vec<u128>, MathError>>()?;
+        let rates = self.get_scaled_rates()?;
+        let (shares, fee_part) = math::rated_compute_lp_amount_for_deposit(
+            &rates,
+            &amounts,
+            &self.reserves,
+            self.total_supply(),
+            Some(&self.current_fees()),
+            self.amp_coef_precise()?,
+        )?;
+        Ok((shares - self.locked_liquidity(shares)?, fee_part))
+    }
+
+    pub fn get_amounts_for_liquidity_mint(
+        &self,
+        liquidity: u128,
+    ) -> Result<Vec<u128>, StablePoolError> {
+        Ok(math::compute_amounts_given_lp(
+            liquidity,
+            &self.reserves,
+            self.total_supply(),
+        )?)
+    }
+
+    pub fn get_burn_liquidity_for_amounts(
+        &self,
+        amounts: Vec<u128>,
+    ) -> Result<(u128, u128), StablePoolError> {
+        ensure!(
+            amounts.len() == self.tokens.len(),
+            StablePoolError::IncorrectAmountsCount
+        );
+        let rates = self.get_scaled_rates()?;
+        math::rated_compute_lp_amount_for_withdraw(
+            &rates,
+            &amounts,
+            &self.reserves,
+            self.total_supply(),
+            Some(&self.current_fees()),
+            self.amp_coef_precise()?,
+        )
+        .map_err(StablePoolError::MathError)
+    }
+
+    pub fn get_amounts_for_liquidity_burn(
+        &self,
+        liquidity: u128,
+    ) -> Result<Vec<u128>, StablePoolError> {
+        ensure!(
+            liquidity <= self.total_supply(),
+            StablePoolError::InsufficientLiquidity
+        );
+        Ok(math::compute_amounts_given_lp(
+            liquidity,
+            &self.reserves,
+            self.total_supply(),
+        )?)
+    }
+
+    /// Restores the state if `f` fails, as reverting the contract message does.
+    fn revert_on_error<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, StablePoolError>,
+    ) -> Result<T, StablePoolError> {
+        let state = (self.reserves.clone(), self.lp_token.clone());
+        let result = f(self);
+        if result.is_err() {
+            (self.reserves, self.lp_token) = state;
+        }
+        result
+    }
+
+    fn get_scaled_rates(&self) -> Result<Vec<ScaledRate>, MathError> {
+        self.token_rates
+            .iter()
+            .zip(self.tokens_decimals.iter())
+            .map(|(&rate, &decimals)| ScaledRate::new(rate, decimals))
+            .collect()
+    }
+
+    /// Fees at the current block timestamp.
+    fn current_fees(&self) -> Fees {
+        self.fees.compute_fees(self.time)
+    }
+
+    /// Returns expected amount of `token_id` credited to the recipient of a transfer of `amount`.
+    fn net_of_transfer_fee(&self, token_id: usize, amount: u128) -> Result<u128, MathError> {
+        match self.transfer_fees[token_id] {
+            Some(fee) => amount_net_of_transfer_fee(amount, fee),
+            None => Ok(amount),
+        }
+    }
+
+    /// Returns amount of `token_id` which has to be transferred
+    /// for the recipient to be credited with at least `amount`.
+    fn gross_of_transfer_fee(&self, token_id: usize, amount: u128) -> Result<u128, MathError> {
+        match self.transfer_fees[token_id] {
+            Some(fee) => amount_gross_of_transfer_fee(amount, fee),
+            None => Ok(amount),
+        }
+    }
+
+    fn token_id(&self, token: AccountId) -> Result<usize, StablePoolError> {
+        self.tokens
+            .iter()
+            .position(|&id| id == token)
+            .ok_or(StablePoolError::InvalidTokenId(token))
+    }
+
+    /// Checks if tokens are valid and returns the tokens ids
+    fn check_tokens(
+        &self,
+        token_in: AccountId,
+        token_out: AccountId,
+    ) -> Result<(usize, usize), StablePoolError> {
+        ensure!(token_in != token_out, StablePoolError::IdenticalTokenId);
+        let token_in_id = self.token_id(token_in)?;
+        let token_out_id = self.token_id(token_out)?;
+        Ok((token_in_id, token_out_id))
+    }
+
+    /// Amount of `token_id` credited to the pool for a transfer of `amount`.
+    fn transfer_in(&self, token_id: usize, amount: u128) -> Result<u128, StablePoolError> {
+        let amount = self.net_of_transfer_fee(token_id, amount)?;
+        ensure!(amount > 0, StablePoolError::InsufficientInputAmount);
+        Ok(amount)
+    }
+
+    /// Calculates lpt equivalent of the protocol fee and mints it to the `fee_to` if one is set.
+    fn mint_protocol_fee(&mut self, fee: u128, token_id: usize) -> Result<(), StablePoolError> {
+        if let Some(fee_to) = self.fee_receiver {
+            let protocol_fee = self.current_fees().protocol_trade_fee(fee)?;
+            if protocol_fee > 0 {
+                let rates = self.get_scaled_rates()?;
+                let mut protocol_deposit_amounts = vec![0u128; self.tokens.len()];
+                protocol_deposit_amounts[token_id] = protocol_fee;
+                let mut reserves = self.reserves.clone();
+                reserves[token_id] = reserves[token_id]
+                    .checked_sub(protocol_fee)
+                    .ok_or(MathError::SubUnderflow(102))?;
+                let (protocol_fee_lp, _) = math::rated_compute_lp_amount_for_deposit(
+                    &rates,
+                    &protocol_deposit_amounts,
+                    &reserves,
+                    self.total_supply(),
+                    None, // no fees
+                    self.amp_coef_precise()?,
+                )?;
+                self.lp_token.mint(fee_to, protocol_fee_lp)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Updates the automatic amplification coefficient adjustment (if enabled), as the contract
+    /// does before the reserves change.
+    fn update_auto_amp(&mut self) -> Result<(), StablePoolError> {
+        let Some(mut auto_amp) = self.auto_amp else {
+            return Ok(());
+        };
+        let imbalance = math::rated_imbalance(&self.get_scaled_rates()?, &self.reserves)?;
+        auto_amp.update(&mut self.amp_coef, imbalance, self.time)?;
+        self.auto_amp = Some(auto_amp);
+        Ok(())
+    }
+
+    fn decrease_reserve(&mut self, token_id: usize, amount: u128) -> Result<(), StablePoolError> {
+        self.reserves[token_id] = self.reserves[token_id]
+            .checked_sub(amount)
+            .ok_or(MathError::SubUnderflow(101))?;
+        Ok(())
+    }
+
+    fn increase_reserve(&mut self, token_id: usize, amount: u128) -> Result<(), StablePoolError> {
+        self.reserves[token_id] = self.reserves[token_id]
+            .checked_add(amount)
+            .ok_or(MathError::AddOverflow(101))?;
+        Ok(())
+    }
+
+    /// Returns the amount of `shares` to be locked forever, see `StablePoolContract`.
+    fn locked_liquidity(&self, shares: u128) -> Result<u128, StablePoolError> {
+        if self.total_supply() > 0 {
+            return Ok(0);
+        }
+        ensure!(
+            shares >= MIN_INITIAL_D,
+            StablePoolError::InsufficientInitialLiquidity
+        );
+        Ok(MINIMUM_LIQUIDITY)
+    }
+
+    fn _add_liquidity(
+        &mut self,
+        min_share_amount: u128,
+        amounts: Vec<u128>,
+        to: AccountId,
+    ) -> Result<(u128, u128), StablePoolError> {
+        self.update_auto_amp()?;
+        ensure!(
+            amounts.len() == self.tokens.len(),
+            StablePoolError::IncorrectAmountsCount
+        );
+        let amounts = amounts
+            .iter()
+            .enumerate()
+            .map(|(id, &amount)| self.net_of_transfer_fee(id, amount))
+            .collect::<Result<Vec<u128>, MathError>>()?;
+        // Check that at least one amount is non-zero
+        ensure!(
+            amounts.iter().any(|&amount| amount > 0),
+            StablePoolError::ZeroAmounts
+        );
+
+        let rates = self.get_scaled_rates()?;
+
+        // calc lp tokens (shares_to_mint, fee)
+        let (shares, fee_part) = math::rated_compute_lp_amount_for_deposit(
+            &rates,
+            &amounts,
+            &self.reserves,
+            self.total_supply(),
+            Some(&self.current_fees()),
+            self.amp_coef_precise()?,
+        )?;
+
+        // lock minimum liquidity on the first deposit
+        let locked = self.locked_liquidity(shares)?;
+        self.lp_token.mint(DEAD_ADDRESS.into(), locked)?;
+        let shares = shares - locked;
+
+        // Check min shares
+        ensure!(
+            shares >= min_share_amount,
+            StablePoolError::LiquidityMintedBelowMin {
+                expected: min_share_amount,
+                actual: shares,
+            }
+        );
+
+        // mint shares
+        self.lp_token.mint(to, shares)?;
+
+        // mint protocol fee
+        if let Some(fee_to) = self.fee_receiver {
+            let protocol_fee = self.current_fees().protocol_trade_fee(fee_part)?;
+            self.lp_token.mint(fee_to, protocol_fee)?;
+        }
+
+        // update reserves
+        for (i, &amount) in amounts.iter().enumerate() {
+            self.increase_reserve(i, amount)?;
+        }
+        Ok((shares, fee_part))
+    }
+
+    fn _remove_liquidity_by_shares(
+        &mut self,
+        caller: AccountId,
+        shares: u128,
+        min_amounts: Vec<u128>,
+    ) -> Result<Vec<u128>, StablePoolError> {
+        // no auto amp update, as in the contract
+        let amounts = math::compute_amounts_given_lp(shares, &self.reserves, self.total_supply())?;
+
+        // Check if enough tokens are withdrawn
+        if let Some((token_id, (&amount, &min_amount))) = amounts
+            .iter()
+            .zip(min_amounts.iter())
+            .enumerate()
+            .find(|(_, (amount, min_amount))| amount < min_amount)
+        {
+            return Err(StablePoolError::OutputAmountBelowMin {
+                token: self.tokens[token_id],
+                expected: min_amount,
+                actual: amount,
+            });
+        }
+        // Check that at least one amount is non-zero
+        ensure!(
+            amounts.iter().any(|&amount| amount > 0),
+            StablePoolError::ZeroAmounts
+        );
+
+        // update reserves
+        for (i, &amount) in amounts.iter().enumerate() {
+            self.decrease_reserve(i, amount)?;
+        }
+
+        // Burn liquidity
+        self.lp_token.burn(caller, shares)?;
+        Ok(amounts)
+    }
+
+    fn _remove_liquidity_by_amounts(
+        &mut self,
+        caller: AccountId,
+        max_share_amount: u128,
+        amounts: Vec<u128>,
+    ) -> Result<(u128, u128), StablePoolError> {
+        ensure!(
+            amounts.len() == self.tokens.len(),
+            StablePoolError::IncorrectAmountsCount
+        );
+        // Check that at least one amount is non-zero
+        ensure!(
+            amounts.iter().any(|&amount| amount > 0),
+            StablePoolError::ZeroAmounts
+        );
+
+        self.update_auto_amp()?;
+        let rates = self.get_scaled_rates()?;
+
+        // calc comparable amounts
+        let (shares_to_burn, fee_part) = math::rated_compute_lp_amount_for_withdraw(
+            &rates,
+            &amounts,
+            &self.reserves,
+            self.total_supply(),
+            Some(&self.current_fees()),
+            self.amp_coef_precise()?,
+        )?;
+
+        // check max shares
+        ensure!(
+            shares_to_burn <= max_share_amount,
+            StablePoolError::LiquidityBurnedAboveMax {
+                expected: max_share_amount,
+                actual: shares_to_burn,
+            }
+        );
+        // burn shares
+        self.lp_token.burn(caller, shares_to_burn)?;
+        // mint protocol fee
+        if let Some(fee_to) = self.fee_receiver {
+            let protocol_fee = self.current_fees().protocol_trade_fee(fee_part)?;
+            self.lp_token.mint(fee_to, protocol_fee)?;
+        }
+        // update reserves
+        for (i, &amount) in amounts.iter().enumerate() {
+            self.decrease_reserve(i, amount)?;
+        }
+        Ok((shares_to_burn, fee_part))
+    }
+
+    fn _swap_exact_in(
+        &mut self,
+        token_in: AccountId,
+        token_out: AccountId,
+        token_in_amount: u128,
+        min_token_out_amount: u128,
+    ) -> Result<(u128, u128), StablePoolError> {
+        //check token ids
+        let (token_in_id, token_out_id) = self.check_tokens(token_in, token_out)?;
+
+        self.update_auto_amp()?;
+
+        // get transferred token_in amount
+        let token_in_amount = self.transfer_in(token_in_id, token_in_amount)?;
+
+        let rates = self.get_scaled_rates()?;
+
+        // calc amount_out and fees
+        let (token_out_amount, fee) = math::rated_swap_to(
+            &rates,
+            token_in_id,
+            token_in_amount,
+            token_out_id,
+            &self.reserves,
+            &self.current_fees(),
+            self.amp_coef_precise()?,
+        )?;
+
+        // Check if swapped amount is not less than min_token_out_amount
+        ensure!(
+            token_out_amount >= min_token_out_amount,
+            StablePoolError::OutputAmountBelowMin {
+                token: token_out,
+                expected: min_token_out_amount,
+                actual: token_out_amount,
+            }
+        );
+        // update reserves
+        self.increase_reserve(token_in_id, token_in_amount)?;
+        self.decrease_reserve(token_out_id, token_out_amount)?;
+
+        // mint protocol fee
+        self.mint_protocol_fee(fee, token_out_id)?;
+        Ok((token_out_amount, fee))
+    }
+
+    fn _swap_exact_out(
+        &mut self,
+        token_in: AccountId,
+        token_out: AccountId,
+        token_out_amount: u128,
+        max_token_in_amount: u128,
+    ) -> Result<(u128, u128), StablePoolError> {
+        //check token ids
+        let (token_in_id, token_out_id) = self.check_tokens(token_in, token_out)?;
+
+        ensure!(
+            token_out_amount > 0,
+            StablePoolError::InsufficientOutputAmount
+        );
+
+        self.update_auto_amp()?;
+
+        let rates = self.get_scaled_rates()?;
+
+        // calc amount_in and fees
+        let (token_in_amount, fee) = math::rated_swap_from(
+            &rates,
+            token_in_id,
+            token_out_amount,
+            token_out_id,
+            &self.reserves,
+            &self.current_fees(),
+            self.amp_coef_precise()?,
+        )?;
+
+        // Account for the transfer fee of token_in (if any)
+        let token_in_gross_amount = self.gross_of_transfer_fee(token_in_id, token_in_amount)?;
+
+        // Check if in token_in_amount is as constrained by the user
+        ensure!(
+            token_in_gross_amount <= max_token_in_amount,
+            StablePoolError::InputAmountAboveMax {
+                token: token_in,
+                expected: max_token_in_amount,
+                actual: token_in_gross_amount,
+            }
+        );
+
+        // transfer token_in
+        let token_in_credited = self.transfer_in(token_in_id, token_in_gross_amount)?;
+        ensure!(
+            token_in_credited >= token_in_amount,
+            StablePoolError::InputAmountCreditedBelowExpected {
+                token: token_in,
+                expected: token_in_amount,
+                actual: token_in_credited,
+            }
+        );
+
+        // update reserves
+        self.increase_reserve(token_in_id, token_in_credited)?;
+        self.decrease_reserve(token_out_id, token_out_amount)?;
+
+        // mint protocol fee
+        self.mint_protocol_fee(fee, token_out_id)?;
+        // note that fee is applied to token_out (same as in _swap_exact_in)
+        Ok((token_in_gross_amount, fee))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amm_helpers::constants::stable_pool::{IMBALANCE_PRECISION, MIN_RAMP_DURATION};
+    use scale::Encode;
+
+    const ONE_LPT: u128 = 10u128.pow(18);
+    const ONE_DAI: u128 = 10u128.pow(18);
+    const ONE_USDT: u128 = 10u128.pow(6);
+    const ONE_USDC: u128 = 10u128.pow(6);
+
+    fn account(id: u8) -> AccountId {
+        AccountId::from([id; 32])
+    }
+
+    fn tokens() -> Vec<AccountId> {
+        vec![account(101), account(102), account(103)]
+    }
+
+    fn fee_receiver() -> AccountId {
+        account(42)
+    }
+
+    /// Same pool as in the `tests_add_remove_lp::test_01` drink test.
+    fn dai_usdt_usdc_pool() -> StablePoolSim {
+        let mut pool = StablePoolSim::new_stable(
+            tokens(),
+            vec![18, 6, 6],
+            10_000,
+            2_500_000,
+            200_000_000,
+            Some(fee_receiver()),
+        )
+        .unwrap();
+        pool.add_liquidity(
+            1,
+            vec![100000 * ONE_DAI, 100000 * ONE_USDT, 100000 * ONE_USDC],
+            account(1),
+        )
+        .unwrap();
+        pool
+    }
+
+    #[test]
+    fn first_deposit_locks_minimum_liquidity() {
+        let pool = dai_usdt_usdc_pool();
+        assert_eq!(pool.total_supply(), 300000 * ONE_LPT);
+        assert_eq!(pool.balance_of(DEAD_ADDRESS.into()), MINIMUM_LIQUIDITY);
+        assert_eq!(
+            pool.balance_of(account(1)),
+            300000 * ONE_LPT - MINIMUM_LIQUIDITY
+        );
+    }
+
+    #[test]
+    fn matches_contract_rounding() {
+        // Expected values are taken from the `tests_add_remove_lp::test_01` drink test.
+        let (bob, charlie, dave) = (account(1), account(2), account(3));
+        let mut pool = dai_usdt_usdc_pool();
+        pool.add_liquidity(
+            1,
+            vec![500 * ONE_DAI, 500 * ONE_USDT, 500 * ONE_USDC],
+            charlie,
+        )
+        .unwrap();
+        pool.remove_liquidity_by_shares(charlie, 300 * ONE_LPT, vec![ONE_DAI, ONE_USDT, ONE_USDC])
+            .unwrap();
+        assert_eq!(
+            pool.add_liquidity(1, vec![100 * ONE_DAI, 200 * ONE_USDT, 400 * ONE_USDC], dave),
+            Ok((699687497426279107411, 312499998280117962))
+        );
+        assert_eq!(pool.balance_of(fee_receiver()), 62499999656023592);
+        assert_eq!(
+            pool.total_supply(),
+            301200 * ONE_LPT + 699687497426279107411 + 62499999656023592
+        );
+        assert_eq!(
+            pool.remove_liquidity_by_amounts(
+                charlie,
+                550 * ONE_LPT,
+                vec![ONE_DAI, 500 * ONE_USDT, ONE_USDC]
+            ),
+            Ok((502623448746385017123, 623853418327862984))
+        );
+        assert_eq!(
+            pool.balance_of(charlie),
+            1200 * ONE_LPT - 502623448746385017123
+        );
+        assert_eq!(
+            pool.reserves(),
+            vec![100499 * ONE_DAI, 100100 * ONE_USDT, 100799 * ONE_USDC]
+        );
+        assert_eq!(
+            pool.total_supply(),
+            301200 * ONE_LPT + 699687497426279107411 + 62499999656023592 - 502623448746385017123
+                + 124770683665572596
+        );
+        assert_eq!(pool.balance_of(bob), 300000 * ONE_LPT - MINIMUM_LIQUIDITY);
+    }
+
+    #[test]
+    fn swap_matches_quote_and_mints_protocol_fee() {
+        let mut pool = dai_usdt_usdc_pool();
+        let tokens = tokens();
+        let quote = pool
+            .get_swap_amount_out(tokens[0], tokens[1], 1000 * ONE_DAI)
+            .unwrap();
+        let supply = pool.total_supply();
+        assert_eq!(
+            pool.swap_exact_in(tokens[0], tokens[1], 1000 * ONE_DAI, 0),
+            Ok(quote)
+        );
+        let minted = pool.balance_of(fee_receiver());
+        assert!(minted > 0);
+        assert_eq!(pool.total_supply(), supply + minted);
+
+        let (amount_in, _) = pool
+            .get_swap_amount_in(tokens[1], tokens[2], 100 * ONE_USDC)
+            .unwrap();
+        assert_eq!(
+            pool.swap_exact_out(tokens[1], tokens[2], 100 * ONE_USDC, amount_in)
+                .map(|(amount_in, _)| amount_in),
+            Ok(amount_in)
+        );
+    }
+
+    #[test]
+    fn swap_restores_peg() {
+        let mut pool = dai_usdt_usdc_pool();
+        let tokens = tokens();
+        pool.swap_exact_in(tokens[1], tokens[0], 50000 * ONE_USDT, 0)
+            .unwrap();
+        let price = pool.get_spot_price(tokens[0], tokens[1]).unwrap();
+        assert!(price > RATE_PRECISION);
+
+        let amount_in = pool
+            .get_swap_amount_in_for_price(tokens[0], tokens[1], RATE_PRECISION)
+            .unwrap();
+        pool.swap_exact_in(tokens[0], tokens[1], amount_in, 0)
+            .unwrap();
+        let price = pool.get_spot_price(tokens[0], tokens[1]).unwrap();
+        assert!(price.abs_diff(RATE_PRECISION) < RATE_PRECISION / 1_000_000);
+
+        let amount_in = pool
+            .get_swap_amount_in_for_ratio(tokens[2], tokens[1], 2 * RATE_PRECISION)
+            .unwrap();
+        pool.swap_exact_in(tokens[2], tokens[1], amount_in, 0)
+            .unwrap();
+        let reserves = pool.reserves();
+        let ratio = reserves[2] * RATE_PRECISION / reserves[1];
+        assert!(
+            ratio <= 2 * RATE_PRECISION && 2 * RATE_PRECISION - ratio < RATE_PRECISION / 1_000_000
+        );
+    }
+
+    #[test]
+    fn failed_message_reverts_state() {
+        let mut pool = dai_usdt_usdc_pool();
+        let tokens = tokens();
+        let (reserves, supply) = (pool.reserves(), pool.total_supply());
+        let (amount_out, _) = pool
+            .get_swap_amount_out(tokens[0], tokens[1], 1000 * ONE_DAI)
+            .unwrap();
+        assert_eq!(
+            pool.swap_exact_in(tokens[0], tokens[1], 1000 * ONE_DAI, u128::MAX),
+            Err(StablePoolError::OutputAmountBelowMin {
+                token: tokens[1],
+                expected: u128::MAX,
+                actual: amount_out,
+            })
+        );
+        assert_eq!(
+            pool.remove_liquidity_by_shares(account(2), ONE_LPT, vec![0; 3]),
+            Err(StablePoolError::PSP22Error(
+                psp22::PSP22Error::InsufficientBalance
+            ))
+        );
+        assert_eq!(pool.reserves(), reserves);
+        assert_eq!(pool.total_supply(), supply);
+    }
+
+    #[test]
+    fn slippage_errors_carry_context() {
+        let mut pool = dai_usdt_usdc_pool();
+        let tokens = tokens();
+        let error = pool
+            .remove_liquidity_by_shares(account(1), 300 * ONE_LPT, vec![0, 101 * ONE_USDT, 0])
+            .unwrap_err();
+        assert_eq!(
+            error,
+            StablePoolError::OutputAmountBelowMin {
+                token: tokens[1],
+                expected: 101 * ONE_USDT,
+                actual: 100 * ONE_USDT,
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Output amount of token 0x{} is 100000000, below the minimum of 101000000",
+                "66".repeat(32)
+            )
+        );
+        // as returned by a dry-run of the contract message
+        let output = Ok::<Result<Vec<u128>, _>, ink::LangError>(Err(error)).encode();
+        assert_eq!(
+            traits::decode_dry_run_error(&output),
+            Ok(Some(StablePoolError::OutputAmountBelowMin {
+                token: tokens[1],
+                expected: 101 * ONE_USDT,
+                actual: 100 * ONE_USDT,
+            }))
+        );
+        let output = Ok::<Result<Vec<u128>, StablePoolError>, ink::LangError>(Ok(vec![])).encode();
+        assert_eq!(traits::decode_dry_run_error(&output), Ok(None));
+
+        let error = pool
+            .add_liquidity(u128::MAX, vec![ONE_DAI, 0, 0], account(1))
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            StablePoolError::LiquidityMintedBelowMin {
+                expected: u128::MAX,
+                ..
+            }
+        ));
+        assert_eq!(
+            StablePoolError::MathError(MathError::SubUnderflow(7)).to_string(),
+            "Math error SubUnderflow(7): swap: output amount exceeds the reserve"
+        );
+    }
+
+    #[test]
+    fn amp_coef_ramps_with_time() {
+        let mut pool = dai_usdt_usdc_pool();
+        pool.set_time(1000);
+        assert_eq!(pool.ramp_amp_coef(1000, 1000 + MIN_RAMP_DURATION), Ok(()));
+        let tokens = tokens();
+        let quote_before = pool
+            .get_swap_amount_out(tokens[0], tokens[1], 10000 * ONE_DAI)
+            .unwrap();
+        pool.advance_time(MIN_RAMP_DURATION / 2);
+        assert_eq!(pool.amp_coef(), Ok(5500));
+        assert_eq!(
+            pool.future_amp_coef(),
+            Some((1000, 1000 + MIN_RAMP_DURATION))
+        );
+        let quote_after = pool
+            .get_swap_amount_out(tokens[0], tokens[1], 10000 * ONE_DAI)
+            .unwrap();
+        assert!(quote_after.0 < quote_before.0);
+        pool.advance_time(MIN_RAMP_DURATION);
+        assert_eq!(pool.amp_coef(), Ok(1000));
+        assert_eq!(pool.future_amp_coef(), None);
+    }
+
+    #[test]
+    fn amp_coef_ramps_through_waypoints() {
+        let mut pool = dai_usdt_usdc_pool();
+        pool.set_time(1000);
+        assert_eq!(
+            pool.ramp_amp_coef_schedule(vec![], RampShape::Geometric),
+            Err(StablePoolError::AmpCoefInvalidSchedule)
+        );
+        assert_eq!(
+            pool.ramp_amp_coef_schedule(
+                vec![
+                    (1000, 1000 + MIN_RAMP_DURATION),
+                    (100, 1000 + 2 * MIN_RAMP_DURATION)
+                ],
+                RampShape::Geometric
+            ),
+            Ok(())
+        );
+        pool.advance_time(MIN_RAMP_DURATION / 2);
+        assert_eq!(pool.amp_coef(), Ok(3162));
+        pool.advance_time(MIN_RAMP_DURATION);
+        assert_eq!(pool.amp_coef(), Ok(316));
+        assert_eq!(
+            pool.future_amp_coef(),
+            Some((100, 1000 + 2 * MIN_RAMP_DURATION))
+        );
+        pool.advance_time(MIN_RAMP_DURATION);
+        assert_eq!(pool.amp_coef(), Ok(100));
+    }
+
+    #[test]
+    fn auto_amp_follows_depeg() {
+        const HOUR: u64 = 3_600_000;
+        let mut pool = dai_usdt_usdc_pool();
+        let config = AutoAmpConfig {
+            min_amp_coef: 1000,
+            max_amp_coef: 10_000,
+            lower_threshold: IMBALANCE_PRECISION / 10,
+            raise_threshold: IMBALANCE_PRECISION / 50,
+            ema_period: HOUR,
+        };
+        assert_eq!(
+            pool.set_auto_amp(Some(AutoAmpConfig {
+                raise_threshold: IMBALANCE_PRECISION,
+                ..config
+            })),
+            Err(StablePoolError::InvalidAutoAmpConfig)
+        );
+        assert_eq!(pool.set_auto_amp(Some(config)), Ok(()));
+        assert_eq!(pool.auto_amp(), Some((config, 0)));
+        let tokens = tokens();
+        // depeg of DAI drains the pool of USDT
+        pool.swap_exact_in(tokens[0], tokens[1], 90_000 * ONE_DAI, 0)
+            .unwrap();
+        pool.advance_time(2 * HOUR);
+        pool.swap_exact_in(tokens[0], tokens[2], ONE_DAI, 0)
+            .unwrap();
+        let (_, imbalance_ema) = pool.auto_amp().unwrap();
+        assert!(imbalance_ema > config.lower_threshold);
+        let ramp_start = pool.time();
+        assert_eq!(
+            pool.future_amp_coef(),
+            Some((1000, ramp_start + MIN_RAMP_DURATION))
+        );
+        assert_eq!(pool.amp_coef(), Ok(10_000));
+        pool.advance_time(MIN_RAMP_DURATION);
+        assert_eq!(pool.amp_coef(), Ok(1000));
+        // repeg restores the balance, and the amplification coefficient
+        let (usdt_in, _) = pool
+            .get_swap_amount_in(tokens[1], tokens[0], pool.reserves()[0] - 100_000 * ONE_DAI)
+            .unwrap();
+        pool.swap_exact_in(tokens[1], tokens[0], usdt_in, 0)
+            .unwrap();
+        pool.advance_time(100 * HOUR);
+        pool.swap_exact_in(tokens[2], tokens[1], ONE_USDC, 0)
+            .unwrap();
+        let (_, imbalance_ema) = pool.auto_amp().unwrap();
+        assert!(imbalance_ema < config.raise_threshold);
+        assert_eq!(
+            pool.future_amp_coef(),
+            Some((10_000, pool.time() + MIN_RAMP_DURATION))
+        );
+        assert_eq!(pool.set_auto_amp(None), Ok(()));
+        assert_eq!(pool.auto_amp(), None);
+    }
+
+    #[test]
+    fn fees_ramp_with_time() {
+        let mut pool = dai_usdt_usdc_pool();
+        pool.set_time(1000);
+        let (trade_fee, protocol_fee) = pool.fees();
+        assert_eq!(
+            pool.ramp_fees(3 * trade_fee, protocol_fee, 1000 + MIN_RAMP_DURATION - 1),
+            Err(StablePoolError::FeeRampDurationTooShort)
+        );
+        assert_eq!(
+            pool.ramp_fees(3 * trade_fee, protocol_fee, 1000 + MIN_RAMP_DURATION),
+            Ok(())
+        );
+        let tokens = tokens();
+        let quote_before = pool
+            .get_swap_amount_out(tokens[0], tokens[1], 10000 * ONE_DAI)
+            .unwrap();
+        pool.advance_time(MIN_RAMP_DURATION / 2);
+        assert_eq!(pool.fees(), (2 * trade_fee, protocol_fee));
+        assert_eq!(
+            pool.future_fees(),
+            Some((3 * trade_fee, protocol_fee, 1000 + MIN_RAMP_DURATION))
+        );
+        let quote_after = pool
+            .get_swap_amount_out(tokens[0], tokens[1], 10000 * ONE_DAI)
+            .unwrap();
+        assert!(quote_after.1 > quote_before.1);
+        pool.stop_ramp_fees();
+        pool.advance_time(MIN_RAMP_DURATION);
+        assert_eq!(pool.fees(), (2 * trade_fee, protocol_fee));
+        assert_eq!(pool.future_fees(), None);
+    }
+
+    #[test]
+    fn transfer_fee_is_deducted_from_deposits() {
+        let mut pool = StablePoolSim::new_pool(
+            tokens(),
+            vec![18, 6, 6],
+            vec![RATE_PRECISION; 3],
+            vec![Some(10_000_000), None, None],
+            AmpCoef::new(10_000).unwrap(),
+            Fees::new(2_500_000, 200_000_000),
+            None,
+            0,
+        )
+        .unwrap();
+        let amounts = vec![100000 * ONE_DAI, 100000 * ONE_USDT, 100000 * ONE_USDC];
+        let quote = pool
+            .get_mint_liquidity_for_amounts(amounts.clone())
+            .unwrap();
+        assert_eq!(pool.add_liquidity(1, amounts, account(1)), Ok(quote));
+        assert_eq!(
+            pool.reserves(),
+            vec![99000 * ONE_DAI, 100000 * ONE_USDT, 100000 * ONE_USDC]
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Evaluates amplification coefficients and trade fees of a stable pool on a scenario
+//! of random trades and depegs, using the off-chain model of the pool contract.
+//!
+//! Usage: `stable-swap-tune <scenario.toml>`
+//!
+//! Prints a CSV report with a row for each `(amp, trade_fee)` of the scenario grid.
+mod report;
+mod scenario;
+mod simulation;
+
+use report::Report;
+use scenario::Scenario;
+use simulation::{GridPoint, Simulation};
+use std::{env, path::PathBuf, process::ExitCode, thread};
+
+fn evaluate(scenario: &Scenario, point: GridPoint) -> Result<Report, String> {
+    let slippage = {
+        let simulation = Simulation::new(scenario, point, 0)?;
+        scenario
+            .slippage_sizes
+            .iter()
+            .map(|&size| simulation.slippage(size))
+            .collect()
+    };
+    let runs = (0..scenario.runs)
+        .map(|run| Ok(Simulation::new(scenario, point, run)?.run()))
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(Report::new(point, &runs, slippage))
+}
+
+fn main() -> ExitCode {
+    let Some(path) = env::args().nth(1).map(PathBuf::from) else {
+        eprintln!("Usage: stable-swap-tune <scenario.toml>");
+        return ExitCode::FAILURE;
+    };
+    let scenario = match Scenario::load(&path) {
+        Ok(scenario) => scenario,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let points: Vec<GridPoint> = scenario
+        .amp
+        .iter()
+        .flat_map(|&amp| {
+            scenario
+                .trade_fee
+                .iter()
+                .map(move |&trade_fee| GridPoint { amp, trade_fee })
+        })
+        .collect();
+    // grid points are independent, evaluate them in parallel
+    let reports = thread::scope(|s| {
+        let scenario = &scenario;
+        let handles: Vec<_> = points
+            .iter()
+            .map(|&point| s.spawn(move || evaluate(scenario, point)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("Evaluation panicked"))
+            .collect::<Result<Vec<_>, String>>()
+    });
+    match reports {
+        Ok(reports) => {
+            println!("{}", Report::csv_header(&scenario.slippage_sizes));
+            for report in reports {
+                println!("{}", report.csv_row());
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
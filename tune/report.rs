@@ -0,0 +1,104 @@
+//! Aggregation of the runs of a grid point.
+use crate::simulation::{GridPoint, RunResult};
+
+/// Quantile of the LP PnL reported as the bad case.
+const WORST_CASE_QUANTILE: f64 = 0.05;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub point: GridPoint,
+    pub lp_pnl_mean: f64,
+    /// `WORST_CASE_QUANTILE` of the LP PnL over runs.
+    pub lp_pnl_worst: f64,
+    pub fee_income_mean: f64,
+    /// Max imbalance over all steps of all runs.
+    pub worst_imbalance: f64,
+    /// Slippage for each of the scenario `slippage_sizes` (`None` if the swap fails).
+    pub slippage: Vec<Option<f64>>,
+}
+
+impl Report {
+    pub fn new(point: GridPoint, runs: &[RunResult], slippage: Vec<Option<f64>>) -> Self {
+        let mean = |metric: fn(&RunResult) -> f64| {
+            runs.iter().map(metric).sum::<f64>() / runs.len() as f64
+        };
+        let mut lp_pnl: Vec<f64> = runs.iter().map(|run| run.lp_pnl).collect();
+        lp_pnl.sort_by(f64::total_cmp);
+        let worst_id = (WORST_CASE_QUANTILE * lp_pnl.len() as f64) as usize;
+        Self {
+            point,
+            lp_pnl_mean: mean(|run| run.lp_pnl),
+            lp_pnl_worst: lp_pnl.get(worst_id).copied().unwrap_or(f64::NAN),
+            fee_income_mean: mean(|run| run.fee_income),
+            worst_imbalance: runs
+                .iter()
+                .map(|run| run.worst_imbalance)
+                .fold(f64::NAN, f64::max),
+            slippage,
+        }
+    }
+
+    pub fn csv_header(slippage_sizes: &[f64]) -> String {
+        let mut columns: Vec<String> = [
+            "amp",
+            "trade_fee",
+            "lp_pnl_mean",
+            "lp_pnl_p5",
+            "fee_income_mean",
+            "worst_imbalance",
+        ]
+        .map(String::from)
+        .to_vec();
+        columns.extend(slippage_sizes.iter().map(|size| format!("slippage_{size}")));
+        columns.join(",")
+    }
+
+    pub fn csv_row(&self) -> String {
+        let mut columns = vec![
+            self.point.amp.to_string(),
+            self.point.trade_fee.to_string(),
+            format!("{:.8}", self.lp_pnl_mean),
+            format!("{:.8}", self.lp_pnl_worst),
+            format!("{:.8}", self.fee_income_mean),
+            format!("{:.6}", self.worst_imbalance),
+        ];
+        columns.extend(self.slippage.iter().map(|slippage| match slippage {
+            Some(slippage) => format!("{slippage:.8}"),
+            None => String::new(),
+        }));
+        columns.join(",")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregates_runs() {
+        let run = |lp_pnl, worst_imbalance| RunResult {
+            lp_pnl,
+            fee_income: 0.01,
+            worst_imbalance,
+        };
+        let point = GridPoint {
+            amp: 100,
+            trade_fee: 1_000_000,
+        };
+        let report = Report::new(
+            point,
+            &[run(0.02, 0.1), run(-0.04, 0.3), run(0.05, 0.2)],
+            vec![Some(0.001), None],
+        );
+        assert_eq!(report.lp_pnl_worst, -0.04);
+        assert_eq!(report.worst_imbalance, 0.3);
+        assert_eq!(
+            Report::csv_header(&[0.01, 0.1]),
+            "amp,trade_fee,lp_pnl_mean,lp_pnl_p5,fee_income_mean,worst_imbalance,slippage_0.01,slippage_0.1"
+        );
+        assert_eq!(
+            report.csv_row(),
+            "100,1000000,0.01000000,-0.04000000,0.01000000,0.300000,0.00100000,"
+        );
+    }
+}
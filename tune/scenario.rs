@@ -0,0 +1,377 @@
+//! Scenario files.
+//!
+//! A scenario is a TOML file describing the pool, the grid of parameters to evaluate,
+//! the random trade flow and the market prices. Market prices are either given by
+//! `[[depeg]]` segments or by a CSV file with one row of token prices per step.
+use std::{fs, path::Path};
+use toml_edit::{Document, Item, Value};
+
+pub type Result<T> = std::result::Result<T, String>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scenario {
+    /// Decimals of each token.
+    pub decimals: Vec<u8>,
+    /// Initial reserves, in whole tokens.
+    pub initial_reserves: Vec<u128>,
+    /// Protocol fee (in 1e9 precision), part of the `trade_fee`.
+    pub protocol_fee: u32,
+    /// Integer amplification coefficients to evaluate.
+    pub amp: Vec<u128>,
+    /// Trade fees (in 1e9 precision) to evaluate.
+    pub trade_fee: Vec<u32>,
+    /// Number of Monte Carlo runs per grid point.
+    pub runs: usize,
+    /// Number of steps of each run.
+    pub steps: usize,
+    pub seed: u64,
+    pub flow: Flow,
+    pub market: Market,
+    /// Trade sizes (as fractions of the pool value) of the reported slippage curve.
+    pub slippage_sizes: Vec<f64>,
+}
+
+/// Random trades made at each step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flow {
+    pub trades_per_step: usize,
+    /// Min size of a trade, as a fraction of the pool value.
+    pub min_trade: f64,
+    /// Max size of a trade, as a fraction of the pool value.
+    pub max_trade: f64,
+    /// Whether arbitrageurs align the pool with the market prices after each step.
+    pub arbitrage: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Market {
+    /// All tokens at the price of 1, except for the depegged ones.
+    Depegs(Vec<Depeg>),
+    /// Prices of each token at each step. The last row holds for the remaining steps.
+    PricePath(Vec<Vec<f64>>),
+}
+
+/// Price of `token` during steps `from_step..to_step`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Depeg {
+    pub token: usize,
+    pub from_step: usize,
+    pub to_step: usize,
+    pub price: f64,
+}
+
+impl Scenario {
+    /// Loads a TOML scenario. A `prices` CSV path is relative to the scenario file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+        Self::parse(&content, |prices| {
+            let prices_path = base_dir.join(prices);
+            fs::read_to_string(&prices_path).map_err(|e| format!("{}: {e}", prices_path.display()))
+        })
+    }
+
+    /// Parses a TOML scenario, reading the `prices` CSV file with `read_prices`.
+    pub fn parse(content: &str, read_prices: impl FnOnce(&str) -> Result<String>) -> Result<Self> {
+        let doc: Document = content.parse().map_err(|e| format!("Invalid TOML: {e}"))?;
+
+        let pool = table(&doc, "pool")?;
+        let decimals = ints(pool, "pool.decimals")?;
+        let initial_reserves = ints(pool, "pool.initial_reserves")?;
+        let protocol_fee = opt_int(pool, "pool.protocol_fee")?.unwrap_or(0);
+
+        let grid = table(&doc, "grid")?;
+        let amp = ints(grid, "grid.amp")?;
+        let trade_fee = ints(grid, "grid.trade_fee")?;
+
+        let simulation = table(&doc, "simulation")?;
+        let runs = opt_int(simulation, "simulation.runs")?.unwrap_or(10);
+        let steps = opt_int(simulation, "simulation.steps")?.unwrap_or(100);
+        let seed = opt_int(simulation, "simulation.seed")?.unwrap_or(0);
+
+        let flow = table(&doc, "flow")?;
+        let flow = Flow {
+            trades_per_step: opt_int(flow, "flow.trades_per_step")?.unwrap_or(1),
+            min_trade: opt_float(flow, "flow.min_trade")?.unwrap_or(0.0001),
+            max_trade: opt_float(flow, "flow.max_trade")?.unwrap_or(0.01),
+            arbitrage: opt_bool(flow, "flow.arbitrage")?.unwrap_or(true),
+        };
+
+        let market = match (doc.get("prices"), doc.get("depeg")) {
+            (Some(_), Some(_)) => return Err("Both prices and depeg given".to_string()),
+            (Some(prices), None) => {
+                let prices = prices
+                    .as_str()
+                    .ok_or("prices has to be a path to a CSV file")?;
+                Market::PricePath(parse_price_path(&read_prices(prices)?)?)
+            }
+            (None, depegs) => Market::Depegs(parse_depegs(depegs)?),
+        };
+
+        let slippage_sizes = match doc.get("report") {
+            Some(report) => floats(report, "report.slippage_sizes")?,
+            None => vec![0.001, 0.01, 0.1],
+        };
+
+        let scenario = Self {
+            decimals,
+            initial_reserves,
+            protocol_fee,
+            amp,
+            trade_fee,
+            runs,
+            steps,
+            seed,
+            flow,
+            market,
+            slippage_sizes,
+        };
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    pub fn n_coins(&self) -> usize {
+        self.decimals.len()
+    }
+
+    /// Market prices of tokens at `step`.
+    pub fn prices_at(&self, step: usize) -> Vec<f64> {
+        match &self.market {
+            Market::Depegs(depegs) => {
+                let mut prices = vec![1.0; self.n_coins()];
+                for depeg in depegs {
+                    if (depeg.from_step..depeg.to_step).contains(&step) {
+                        prices[depeg.token] = depeg.price;
+                    }
+                }
+                prices
+            }
+            Market::PricePath(path) => path[step.min(path.len() - 1)].clone(),
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        let n_coins = self.n_coins();
+        if self.initial_reserves.len() != n_coins {
+            return Err("pool.initial_reserves and pool.decimals differ in length".to_string());
+        }
+        if self.amp.is_empty() || self.trade_fee.is_empty() {
+            return Err("Empty grid".to_string());
+        }
+        if !(0.0 < self.flow.min_trade && self.flow.min_trade <= self.flow.max_trade) {
+            return Err("Expected 0 < flow.min_trade <= flow.max_trade".to_string());
+        }
+        match &self.market {
+            Market::Depegs(depegs) => {
+                if let Some(depeg) = depegs.iter().find(|depeg| depeg.token >= n_coins) {
+                    return Err(format!("Invalid depeg token {}", depeg.token));
+                }
+            }
+            Market::PricePath(path) => {
+                if path.is_empty() || path.iter().any(|prices| prices.len() != n_coins) {
+                    return Err(format!("Expected {n_coins} prices in each row"));
+                }
+            }
+        }
+        let prices_valid = (0..self.steps)
+            .flat_map(|step| self.prices_at(step))
+            .all(|price| price.is_finite() && price > 0.0);
+        if !prices_valid {
+            return Err("Prices have to be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Parses rows of comma separated prices, skipping empty lines, `#` comments
+/// and a header row.
+fn parse_price_path(csv: &str) -> Result<Vec<Vec<f64>>> {
+    let mut rows = csv
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .peekable();
+    let is_header = |line: &&str| {
+        line.split(',')
+            .any(|field| field.trim().parse::<f64>().is_err())
+    };
+    if rows.peek().map_or(false, is_header) {
+        rows.next();
+    }
+    rows.enumerate()
+        .map(|(row, line)| {
+            line.split(',')
+                .map(|field| {
+                    field
+                        .trim()
+                        .parse::<f64>()
+                        .map_err(|e| format!("Invalid price in row {row}: {e}"))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn parse_depegs(depegs: Option<&Item>) -> Result<Vec<Depeg>> {
+    let Some(depegs) = depegs else {
+        return Ok(vec![]);
+    };
+    let depegs = depegs
+        .as_array_of_tables()
+        .ok_or("depeg has to be an array of tables")?;
+    depegs
+        .iter()
+        .map(|depeg| {
+            let depeg = Item::Table(depeg.clone());
+            Ok(Depeg {
+                token: int(&depeg, "depeg.token")?,
+                from_step: int(&depeg, "depeg.from_step")?,
+                to_step: int(&depeg, "depeg.to_step")?,
+                price: opt_float(&depeg, "depeg.price")?.ok_or("Missing depeg.price")?,
+            })
+        })
+        .collect()
+}
+
+fn table<'a>(doc: &'a Document, key: &str) -> Result<&'a Item> {
+    doc.get(key)
+        .filter(|item| item.is_table_like())
+        .ok_or(format!("Missing [{key}]"))
+}
+
+/// Value of `path` (`table.key`) in `item`.
+fn value<'a>(item: &'a Item, path: &str) -> Option<&'a Item> {
+    let key = path.rsplit('.').next().unwrap_or(path);
+    item.get(key)
+}
+
+fn int<T: TryFrom<i64>>(item: &Item, path: &str) -> Result<T> {
+    opt_int(item, path)?.ok_or(format!("Missing {path}"))
+}
+
+fn opt_int<T: TryFrom<i64>>(item: &Item, path: &str) -> Result<Option<T>> {
+    value(item, path)
+        .map(|v| {
+            v.as_integer()
+                .and_then(|v| T::try_from(v).ok())
+                .ok_or(format!("Invalid {path}"))
+        })
+        .transpose()
+}
+
+fn opt_float(item: &Item, path: &str) -> Result<Option<f64>> {
+    value(item, path)
+        .map(|v| {
+            v.as_value()
+                .and_then(as_f64)
+                .ok_or(format!("Invalid {path}"))
+        })
+        .transpose()
+}
+
+fn opt_bool(item: &Item, path: &str) -> Result<Option<bool>> {
+    value(item, path)
+        .map(|v| v.as_bool().ok_or(format!("Invalid {path}")))
+        .transpose()
+}
+
+fn ints<T: TryFrom<i64>>(item: &Item, path: &str) -> Result<Vec<T>> {
+    array(item, path)?
+        .map(|v| {
+            v.as_integer()
+                .and_then(|v| T::try_from(v).ok())
+                .ok_or(format!("Invalid {path}"))
+        })
+        .collect()
+}
+
+fn floats(item: &Item, path: &str) -> Result<Vec<f64>> {
+    array(item, path)?
+        .map(|v| as_f64(v).ok_or(format!("Invalid {path}")))
+        .collect()
+}
+
+fn array<'a>(item: &'a Item, path: &str) -> Result<impl Iterator<Item = &'a Value>> {
+    value(item, path)
+        .and_then(Item::as_array)
+        .map(|array| array.iter())
+        .ok_or(format!("Missing {path} array"))
+}
+
+/// Accepts both TOML floats and integers.
+fn as_f64(value: &Value) -> Option<f64> {
+    value
+        .as_float()
+        .or_else(|| value.as_integer().map(|v| v as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCENARIO: &str = r#"
+[pool]
+decimals = [18, 6]
+initial_reserves = [1_000_000, 1_000_000]
+protocol_fee = 200_000_000
+
+[grid]
+amp = [100, 1000]
+trade_fee = [1_000_000]
+
+[simulation]
+runs = 2
+steps = 10
+
+[flow]
+max_trade = 0.05
+
+[[depeg]]
+token = 1
+from_step = 3
+to_step = 6
+price = 0.9
+"#;
+
+    fn no_prices(_: &str) -> Result<String> {
+        Err("Unexpected prices".to_string())
+    }
+
+    #[test]
+    fn parses_depeg_scenario() {
+        let scenario = Scenario::parse(SCENARIO, no_prices).unwrap();
+        assert_eq!(scenario.decimals, vec![18, 6]);
+        assert_eq!(scenario.amp, vec![100, 1000]);
+        assert_eq!(scenario.flow.min_trade, 0.0001);
+        assert_eq!(scenario.flow.max_trade, 0.05);
+        assert_eq!(scenario.prices_at(2), vec![1.0, 1.0]);
+        assert_eq!(scenario.prices_at(3), vec![1.0, 0.9]);
+        assert_eq!(scenario.prices_at(6), vec![1.0, 1.0]);
+    }
+
+    #[test]
+    fn parses_price_path() {
+        let scenario = SCENARIO
+            .split("[[depeg]]")
+            .next()
+            .unwrap()
+            .replace("[pool]", "prices = \"prices.csv\"\n\n[pool]");
+        let scenario = Scenario::parse(&scenario, |path| {
+            assert_eq!(path, "prices.csv");
+            Ok("usdc,usdt\n1.0,1.0\n# depeg\n1,0.95\n".to_string())
+        })
+        .unwrap();
+        assert_eq!(scenario.prices_at(0), vec![1.0, 1.0]);
+        assert_eq!(scenario.prices_at(1), vec![1.0, 0.95]);
+        assert_eq!(scenario.prices_at(5), vec![1.0, 0.95]);
+    }
+
+    #[test]
+    fn rejects_invalid_depeg_token() {
+        let scenario = SCENARIO.replace("token = 1", "token = 2");
+        assert_eq!(
+            Scenario::parse(&scenario, no_prices),
+            Err("Invalid depeg token 2".to_string())
+        );
+    }
+}
@@ -0,0 +1,318 @@
+//! Monte Carlo runs of a scenario on the off-chain pool model.
+use crate::scenario::Scenario;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use stable_swap_sim::{AccountId, StablePoolSim};
+
+/// Relative precision of the optimal arbitrage trade search.
+const ARBITRAGE_PRECISION: f64 = 1e-4;
+
+/// Max number of arbitrage trades per step.
+const MAX_ARBITRAGE_TRADES: usize = 8;
+
+const LP: [u8; 32] = [100; 32];
+const FEE_RECEIVER: [u8; 32] = [200; 32];
+
+/// Outcome of a single run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunResult {
+    /// Value of the LP position relative to holding the initial deposit, minus 1.
+    pub lp_pnl: f64,
+    /// Value of trade fees paid to the pool, relative to the initial pool value.
+    pub fee_income: f64,
+    /// Max over steps of `(max reserve - min reserve) / sum of reserves`, in token units.
+    pub worst_imbalance: f64,
+}
+
+/// Pool parameters evaluated by the runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridPoint {
+    pub amp: u128,
+    pub trade_fee: u32,
+}
+
+fn token(id: usize) -> AccountId {
+    AccountId::from([id as u8 + 1; 32])
+}
+
+/// Token amount worth `value` at `price`.
+fn amount(value: f64, price: f64, decimals: u8) -> u128 {
+    (value / price * 10f64.powi(decimals.into())) as u128
+}
+
+/// Value of a token `amount` at `price`.
+fn value(amount: u128, price: f64, decimals: u8) -> f64 {
+    amount as f64 / 10f64.powi(decimals.into()) * price
+}
+
+pub struct Simulation<'a> {
+    scenario: &'a Scenario,
+    pool: StablePoolSim,
+    rng: StdRng,
+    fee_income: f64,
+}
+
+impl<'a> Simulation<'a> {
+    /// Pool holding the initial reserves deposited by the LP.
+    pub fn new(scenario: &'a Scenario, point: GridPoint, run: usize) -> Result<Self, String> {
+        let n_coins = scenario.n_coins();
+        let mut pool = StablePoolSim::new_stable(
+            (0..n_coins).map(token).collect(),
+            scenario.decimals.clone(),
+            point.amp,
+            point.trade_fee,
+            scenario.protocol_fee,
+            Some(FEE_RECEIVER.into()),
+        )
+        .map_err(|e| format!("Invalid pool {point:?}: {e:?}"))?;
+        let deposit = scenario
+            .initial_reserves
+            .iter()
+            .zip(scenario.decimals.iter())
+            .map(|(&reserve, &decimals)| reserve * 10u128.pow(decimals.into()))
+            .collect();
+        pool.add_liquidity(0, deposit, LP.into())
+            .map_err(|e| format!("Initial deposit failed: {e:?}"))?;
+        Ok(Self {
+            scenario,
+            pool,
+            rng: StdRng::seed_from_u64(scenario.seed.wrapping_add(run as u64)),
+            fee_income: 0.0,
+        })
+    }
+
+    /// Runs all steps of the scenario.
+    pub fn run(mut self) -> RunResult {
+        let initial_reserves = self.pool.reserves();
+        let initial_value = self.pool_value(&vec![1.0; self.scenario.n_coins()]);
+        let mut worst_imbalance = self.imbalance();
+        let mut prices = self.scenario.prices_at(0);
+        for step in 0..self.scenario.steps {
+            prices = self.scenario.prices_at(step);
+            for _ in 0..self.scenario.flow.trades_per_step {
+                self.random_trade(&prices);
+            }
+            if self.scenario.flow.arbitrage {
+                self.arbitrage(&prices);
+            }
+            worst_imbalance = worst_imbalance.max(self.imbalance());
+        }
+
+        let hodl_value: f64 = initial_reserves
+            .iter()
+            .zip(self.scenario.decimals.iter())
+            .zip(prices.iter())
+            .map(|((&reserve, &decimals), &price)| value(reserve, price, decimals))
+            .sum();
+        let lp_share = self.pool.balance_of(LP.into()) as f64 / self.pool.total_supply() as f64;
+        let lp_value = lp_share * self.pool_value(&prices);
+        RunResult {
+            lp_pnl: lp_value / hodl_value - 1.0,
+            fee_income: self.fee_income / initial_value,
+            worst_imbalance,
+        }
+    }
+
+    /// Swaps a random pair, of a log-uniformly distributed size.
+    fn random_trade(&mut self, prices: &[f64]) {
+        let n_coins = self.scenario.n_coins();
+        let token_in = self.rng.gen_range(0..n_coins);
+        let token_out = (token_in + self.rng.gen_range(1..n_coins)) % n_coins;
+        let flow = &self.scenario.flow;
+        let size = self
+            .rng
+            .gen_range(flow.min_trade.ln()..=flow.max_trade.ln())
+            .exp();
+        let amount_in = amount(
+            size * self.pool_value(prices),
+            prices[token_in],
+            self.scenario.decimals[token_in],
+        );
+        self.swap(token_in, token_out, amount_in, prices);
+    }
+
+    /// Makes the most profitable trades at market `prices`, until there are none.
+    fn arbitrage(&mut self, prices: &[f64]) {
+        for _ in 0..MAX_ARBITRAGE_TRADES {
+            let best = self
+                .pairs()
+                .filter(|&(token_in, token_out)| self.is_profitable(token_in, token_out, prices))
+                .filter_map(|(token_in, token_out)| {
+                    let (amount_in, profit) = self.best_trade(token_in, token_out, prices);
+                    (profit > 0.0).then_some((token_in, token_out, amount_in, profit))
+                })
+                .max_by(|a, b| a.3.total_cmp(&b.3));
+            match best {
+                Some((token_in, token_out, amount_in, _)) => {
+                    self.swap(token_in, token_out, amount_in, prices)
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Whether a small trade is profitable, which (by concavity) is required for any trade to be.
+    fn is_profitable(&self, token_in: usize, token_out: usize, prices: &[f64]) -> bool {
+        let probe = (self.pool.reserves()[token_in] as f64 * ARBITRAGE_PRECISION) as u128;
+        self.profit(token_in, token_out, probe.max(1), prices) > 0.0
+    }
+
+    /// Returns the most profitable `(amount_in, profit)` of swapping `token_in` to `token_out`.
+    ///
+    /// The profit is concave in the amount, so it is maximized by ternary search.
+    fn best_trade(&self, token_in: usize, token_out: usize, prices: &[f64]) -> (u128, f64) {
+        let (mut low, mut high) = (0u128, self.pool.reserves()[token_in].saturating_mul(2));
+        while high - low > 2 && (high - low) as f64 > ARBITRAGE_PRECISION * high as f64 {
+            let third = (high - low) / 3;
+            let (a, b) = (low + third, high - third);
+            if self.profit(token_in, token_out, a, prices)
+                < self.profit(token_in, token_out, b, prices)
+            {
+                low = a;
+            } else {
+                high = b;
+            }
+        }
+        (high, self.profit(token_in, token_out, high, prices))
+    }
+
+    fn profit(&self, token_in: usize, token_out: usize, amount_in: u128, prices: &[f64]) -> f64 {
+        let decimals = &self.scenario.decimals;
+        match self
+            .pool
+            .get_swap_amount_out(token(token_in), token(token_out), amount_in)
+        {
+            Ok((amount_out, _)) => {
+                value(amount_out, prices[token_out], decimals[token_out])
+                    - value(amount_in, prices[token_in], decimals[token_in])
+            }
+            Err(_) => f64::NEG_INFINITY,
+        }
+    }
+
+    fn swap(&mut self, token_in: usize, token_out: usize, amount_in: u128, prices: &[f64]) {
+        if let Ok((_, fee)) =
+            self.pool
+                .swap_exact_in(token(token_in), token(token_out), amount_in, 0)
+        {
+            self.fee_income += value(fee, prices[token_out], self.scenario.decimals[token_out]);
+        }
+    }
+
+    fn pairs(&self) -> impl Iterator<Item = (usize, usize)> {
+        let n_coins = self.scenario.n_coins();
+        (0..n_coins)
+            .flat_map(move |i| (0..n_coins).map(move |j| (i, j)))
+            .filter(|(i, j)| i != j)
+    }
+
+    fn pool_value(&self, prices: &[f64]) -> f64 {
+        self.pool
+            .reserves()
+            .iter()
+            .zip(self.scenario.decimals.iter())
+            .zip(prices.iter())
+            .map(|((&reserve, &decimals), &price)| value(reserve, price, decimals))
+            .sum()
+    }
+
+    fn imbalance(&self) -> f64 {
+        let reserves: Vec<f64> = self
+            .pool
+            .reserves()
+            .iter()
+            .zip(self.scenario.decimals.iter())
+            .map(|(&reserve, &decimals)| value(reserve, 1.0, decimals))
+            .collect();
+        let max = reserves.iter().copied().fold(f64::MIN, f64::max);
+        let min = reserves.iter().copied().fold(f64::MAX, f64::min);
+        (max - min) / reserves.iter().sum::<f64>()
+    }
+
+    /// Slippage (including the trade fee) of swapping the first token to the second one
+    /// at the initial pool state, for trades of `size` of the pool value.
+    pub fn slippage(&self, size: f64) -> Option<f64> {
+        let decimals = &self.scenario.decimals;
+        let amount_in = amount(
+            size * self.pool_value(&vec![1.0; decimals.len()]),
+            1.0,
+            decimals[0],
+        );
+        let (amount_out, _) = self
+            .pool
+            .get_swap_amount_out(token(0), token(1), amount_in)
+            .ok()?;
+        Some(1.0 - value(amount_out, 1.0, decimals[1]) / value(amount_in, 1.0, decimals[0]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenario::{Depeg, Flow, Market};
+
+    fn scenario(depegs: Vec<Depeg>) -> Scenario {
+        Scenario {
+            decimals: vec![18, 6, 6],
+            initial_reserves: vec![1_000_000; 3],
+            protocol_fee: 200_000_000,
+            amp: vec![1000],
+            trade_fee: vec![1_000_000],
+            runs: 1,
+            steps: 20,
+            seed: 7,
+            flow: Flow {
+                trades_per_step: 2,
+                min_trade: 0.0001,
+                max_trade: 0.01,
+                arbitrage: true,
+            },
+            market: Market::Depegs(depegs),
+            slippage_sizes: vec![],
+        }
+    }
+
+    const POINT: GridPoint = GridPoint {
+        amp: 1000,
+        trade_fee: 1_000_000,
+    };
+
+    #[test]
+    fn runs_are_deterministic() {
+        let scenario = scenario(vec![]);
+        let run = || Simulation::new(&scenario, POINT, 0).unwrap().run();
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn noise_trades_earn_fees() {
+        let result = Simulation::new(&scenario(vec![]), POINT, 0).unwrap().run();
+        assert!(result.fee_income > 0.0);
+        assert!(result.lp_pnl > 0.0);
+    }
+
+    #[test]
+    fn arbitrage_follows_depeg() {
+        let scenario = scenario(vec![Depeg {
+            token: 1,
+            from_step: 0,
+            to_step: 20,
+            price: 0.9,
+        }]);
+        let result = Simulation::new(&scenario, POINT, 0).unwrap().run();
+        assert!(result.worst_imbalance > 0.5);
+        assert!(result.lp_pnl < 0.0);
+    }
+
+    #[test]
+    fn slippage_decreases_with_amp() {
+        let scenario = scenario(vec![]);
+        let slippage = |amp| {
+            Simulation::new(&scenario, GridPoint { amp, ..POINT }, 0)
+                .unwrap()
+                .slippage(0.1)
+                .unwrap()
+        };
+        assert!(slippage(10) > slippage(1000));
+        assert!(slippage(1000) > 0.001);
+    }
+}